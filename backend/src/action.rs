@@ -0,0 +1,410 @@
+use crate::combat::{apply_combat_result, resolve_battle, validate_battle, BattleResolution};
+use crate::error::RuleError;
+use crate::events::GameEvent;
+use crate::game_state::{BattleDeclaration, GameState};
+use crate::hex::Hex;
+use crate::map::Map;
+use crate::movement::validate_move;
+use crate::replacement::{apply_replacement, validate_replacement_placement};
+use crate::retreat::{execute_retreat, RetreatResult};
+use crate::unit::Units;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A single player decision that changes the game state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Move {
+        unit_id: String,
+        to: Hex,
+    },
+    DeclareBattle {
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    },
+    ResolveBattle {
+        battle_index: usize,
+        /// The attacker that takes the step if the result is an AL
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        loss: Option<String>,
+    },
+    Retreat {
+        unit_id: String,
+        to: Hex,
+    },
+    Replacement {
+        unit_id: String,
+        hex: Option<Hex>,
+    },
+    AdvancePhase,
+}
+
+/// What applying an action produced
+#[derive(Debug, Clone)]
+pub struct ActionOutcome {
+    pub events: Vec<GameEvent>,
+    pub resolution: Option<BattleResolution>,
+    pub retreat: Option<RetreatResult>,
+}
+
+impl ActionOutcome {
+    fn event(event: GameEvent) -> Self {
+        ActionOutcome {
+            events: vec![event],
+            resolution: None,
+            retreat: None,
+        }
+    }
+}
+
+/// Validate and apply an action to the game state
+///
+/// The state is left untouched when the action is rejected.
+pub fn apply_action(
+    action: &Action,
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<ActionOutcome, RuleError> {
+    match action {
+        Action::Move { unit_id, to } => {
+            validate_move(unit_id, to, state, units, map)?;
+
+            let unit = state
+                .get_unit_mut(unit_id)
+                .ok_or_else(|| RuleError::UnitNotFound(unit_id.clone()))?;
+            let from = unit.hex();
+            unit.move_to(*to);
+            state.mark_moved(unit_id);
+
+            // Update city control if moved into a city
+            if let Some(ref city) = map.get_hex(to).and_then(|mh| mh.city.clone()) {
+                if let Some(unit_def) = units.get(unit_id) {
+                    state.update_city_control(&city.name, unit_def.side);
+                }
+            }
+
+            Ok(ActionOutcome::event(GameEvent::UnitMoved {
+                unit_id: unit_id.clone(),
+                from,
+                to: *to,
+            }))
+        }
+
+        Action::DeclareBattle {
+            attacker_ids,
+            defender_id,
+        } => {
+            validate_battle(attacker_ids, defender_id, state, units)?;
+            state.pending_battles.push(BattleDeclaration {
+                attackers: attacker_ids.clone(),
+                defender: defender_id.clone(),
+                resolved: false,
+            });
+
+            Ok(ActionOutcome::event(GameEvent::BattleDeclared {
+                battle_index: state.pending_battles.len() - 1,
+                attacker_ids: attacker_ids.clone(),
+                defender_id: defender_id.clone(),
+            }))
+        }
+
+        Action::ResolveBattle { battle_index, loss } => {
+            require_combat_phase(state)?;
+            let battle = state
+                .pending_battles
+                .get(*battle_index)
+                .cloned()
+                .ok_or(RuleError::BattleNotFound(*battle_index))?;
+            if battle.resolved {
+                return Err(RuleError::BattleAlreadyResolved(*battle_index));
+            }
+
+            // Resolve on a copy so a failure part-way leaves no die roll used
+            let mut next = state.clone();
+            let resolution = resolve_battle(&battle, &mut next, units, map)?;
+            apply_combat_result(
+                &resolution.result,
+                &battle,
+                loss.as_deref(),
+                &mut next,
+                units,
+            )?;
+            next.pending_battles[*battle_index].resolved = true;
+            *state = next;
+
+            Ok(ActionOutcome {
+                events: vec![GameEvent::DieRolled {
+                    battle_index: *battle_index,
+                    die_roll: resolution.die_roll,
+                    odds: resolution.modified_odds.clone(),
+                    result: resolution.result.clone(),
+                }],
+                resolution: Some(resolution),
+                retreat: None,
+            })
+        }
+
+        Action::Retreat { unit_id, to } => {
+            require_combat_phase(state)?;
+            let pending = state
+                .pending_retreats
+                .iter()
+                .position(|id| id == unit_id)
+                .ok_or_else(|| RuleError::NoRetreatPending(unit_id.clone()))?;
+            let result = execute_retreat(unit_id, to, state, units, map)?;
+            state.pending_retreats.remove(pending);
+
+            Ok(ActionOutcome {
+                events: vec![GameEvent::UnitRetreated {
+                    unit_id: unit_id.clone(),
+                    to: *to,
+                }],
+                resolution: None,
+                retreat: Some(result),
+            })
+        }
+
+        Action::Replacement { unit_id, hex } => {
+            if !state.phase.is_replacement_phase() {
+                return Err(RuleError::WrongPhase("replacement"));
+            }
+            if let Some(ref h) = hex {
+                validate_replacement_placement(unit_id, h, state, units, map)?;
+            }
+            apply_replacement(unit_id, hex.as_ref(), state, units)?;
+
+            Ok(ActionOutcome::event(GameEvent::ReplacementApplied {
+                unit_id: unit_id.clone(),
+                hex: *hex,
+            }))
+        }
+
+        Action::AdvancePhase => {
+            state.advance_phase();
+
+            Ok(ActionOutcome::event(GameEvent::PhaseAdvanced {
+                turn: state.turn,
+                phase: state.phase.clone(),
+            }))
+        }
+    }
+}
+
+fn require_combat_phase(state: &GameState) -> Result<(), RuleError> {
+    if state.phase.is_combat_phase() {
+        Ok(())
+    } else {
+        Err(RuleError::WrongPhase("combat"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::CombatResult;
+    use crate::game_state::Phase;
+    use crate::test_support::{test_map, unit};
+    use crate::turn_file::state_hash;
+    use crate::unit::{Side, UnitState, UnitStrength, UnitType};
+
+    fn test_units() -> Units {
+        Units {
+            units: vec![
+                unit("XLVII", Side::German, UnitType::Panzer, 9, 6),
+                unit("16A", Side::Soviet, UnitType::Infantry, 8, 4),
+                unit("V", Side::German, UnitType::Infantry, 6, 4),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_move_action() {
+        let (map, units) = (test_map(3, 1, None), test_units());
+        let mut state = GameState::new();
+        state.units.push(UnitState::new(
+            "XLVII".to_string(),
+            Some(Hex::new(0, 0)),
+            UnitStrength::Full,
+        ));
+
+        let action = Action::Move {
+            unit_id: "XLVII".to_string(),
+            to: Hex::new(2, 0),
+        };
+        let outcome = apply_action(&action, &mut state, &units, &map).unwrap();
+
+        assert_eq!(state.get_unit("XLVII").unwrap().hex(), Some(Hex::new(2, 0)));
+        assert!(state.has_moved("XLVII"));
+        assert_eq!(
+            outcome.events,
+            vec![GameEvent::UnitMoved {
+                unit_id: "XLVII".to_string(),
+                from: Some(Hex::new(0, 0)),
+                to: Hex::new(2, 0),
+            }]
+        );
+
+        // Moving twice in a phase is rejected
+        assert_eq!(
+            apply_action(&action, &mut state, &units, &map).unwrap_err(),
+            RuleError::AlreadyMoved
+        );
+    }
+
+    #[test]
+    fn test_invalid_battle_index() {
+        let (map, units) = (test_map(3, 1, None), test_units());
+        let mut state = GameState::new();
+        let action = Action::ResolveBattle {
+            battle_index: 0,
+            loss: None,
+        };
+        assert_eq!(
+            apply_action(&action, &mut state, &units, &map).unwrap_err(),
+            RuleError::WrongPhase("combat")
+        );
+
+        state.phase = Phase::GermanCombat;
+
+        assert_eq!(
+            apply_action(&action, &mut state, &units, &map).unwrap_err(),
+            RuleError::BattleNotFound(0)
+        );
+        assert_eq!(state.dice.rolls, 0);
+    }
+
+    /// XLVII next to 16A in the German combat phase
+    fn combat_state() -> GameState {
+        let mut state = GameState::with_seed("action-test");
+        state.phase = Phase::GermanCombat;
+        for (id, q) in [("XLVII", 0), ("16A", 1)] {
+            let hex = Some(Hex::new(q, 0));
+            state
+                .units
+                .push(UnitState::new(id.to_string(), hex, UnitStrength::Full));
+        }
+        state
+    }
+
+    #[test]
+    fn test_battle_resolves_once() {
+        let (map, units) = (test_map(3, 1, None), test_units());
+        let mut state = combat_state();
+        let declare = Action::DeclareBattle {
+            attacker_ids: vec!["XLVII".to_string()],
+            defender_id: "16A".to_string(),
+        };
+        apply_action(&declare, &mut state, &units, &map).unwrap();
+
+        let resolve = Action::ResolveBattle {
+            battle_index: 0,
+            loss: None,
+        };
+        apply_action(&resolve, &mut state, &units, &map).unwrap();
+        assert_eq!(state.dice.rolls, 1);
+
+        // A second resolution would be a free re-roll
+        let before = state.clone();
+        assert_eq!(
+            apply_action(&resolve, &mut state, &units, &map).unwrap_err(),
+            RuleError::BattleAlreadyResolved(0)
+        );
+        assert_eq!(state_hash(&state), state_hash(&before));
+    }
+
+    #[test]
+    fn test_illegal_battles_are_refused() {
+        let (map, units) = (test_map(4, 1, None), test_units());
+        let mut state = combat_state();
+        state.units.push(UnitState::new(
+            "V".to_string(),
+            Some(Hex::new(3, 0)),
+            UnitStrength::Full,
+        ));
+        let declare = |attackers: &[&str], defender: &str| Action::DeclareBattle {
+            attacker_ids: attackers.iter().map(|id| id.to_string()).collect(),
+            defender_id: defender.to_string(),
+        };
+
+        for (action, err) in [
+            (declare(&[], "16A"), RuleError::NoAttackers),
+            (
+                declare(&["16A"], "XLVII"),
+                RuleError::NotAnEnemy("XLVII".to_string()),
+            ),
+            (
+                declare(&["V"], "16A"),
+                RuleError::NotAdjacent("V".to_string()),
+            ),
+            (declare(&["XLVII", "16A"], "16A"), RuleError::NotYourTurn),
+            (
+                declare(&["XLVII", "XLVII"], "16A"),
+                RuleError::AlreadyInBattle("XLVII".to_string()),
+            ),
+        ] {
+            assert_eq!(
+                apply_action(&action, &mut state, &units, &map).unwrap_err(),
+                err
+            );
+        }
+        assert!(state.pending_battles.is_empty());
+
+        // Each defender is attacked once per phase
+        apply_action(&declare(&["XLVII"], "16A"), &mut state, &units, &map).unwrap();
+        assert_eq!(
+            apply_action(&declare(&["XLVII"], "16A"), &mut state, &units, &map).unwrap_err(),
+            RuleError::AlreadyInBattle("16A".to_string())
+        );
+        assert_eq!(state.pending_battles.len(), 1);
+    }
+
+    #[test]
+    fn test_only_driven_back_defenders_retreat() {
+        let (map, units) = (test_map(4, 1, None), test_units());
+        let mut state = combat_state();
+        let retreat = |to: i32| Action::Retreat {
+            unit_id: "16A".to_string(),
+            to: Hex::new(to, 0),
+        };
+        assert_eq!(
+            apply_action(&retreat(3), &mut state, &units, &map).unwrap_err(),
+            RuleError::NoRetreatPending("16A".to_string())
+        );
+
+        let battle = BattleDeclaration {
+            attackers: vec!["XLVII".to_string()],
+            defender: "16A".to_string(),
+            resolved: false,
+        };
+        apply_combat_result(&CombatResult::DR, &battle, None, &mut state, &units).unwrap();
+        assert_eq!(state.pending_retreats, vec!["16A".to_string()]);
+
+        apply_action(&retreat(3), &mut state, &units, &map).unwrap();
+        assert_eq!(state.get_unit("16A").unwrap().hex(), Some(Hex::new(3, 0)));
+        assert!(state.pending_retreats.is_empty());
+        assert_eq!(
+            apply_action(&retreat(1), &mut state, &units, &map).unwrap_err(),
+            RuleError::NoRetreatPending("16A".to_string())
+        );
+    }
+
+    #[test]
+    fn test_action_json() {
+        let json = serde_json::to_value(Action::AdvancePhase).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "advance_phase"}));
+
+        let action: Action = serde_json::from_value(serde_json::json!({
+            "type": "move", "unit_id": "XLVII", "to": {"q": 2, "r": 0}
+        }))
+        .unwrap();
+        assert_eq!(
+            action,
+            Action::Move {
+                unit_id: "XLVII".to_string(),
+                to: Hex::new(2, 0)
+            }
+        );
+    }
+}
@@ -0,0 +1,608 @@
+use crate::action::{apply_action, Action, ActionOutcome};
+use crate::analysis::plan_attacks;
+use crate::combat::{find_valid_attacks, CombatResult};
+use crate::error::RuleError;
+use crate::evaluate::evaluate_position;
+use crate::game_state::{BattleDeclaration, GameState, Phase};
+use crate::hex::Hex;
+use crate::map::Map;
+use crate::mcts::{MctsConfig, MctsPlayer};
+use crate::movement::find_move_options;
+use crate::replacement::{replacement_options, replacements_remaining};
+use crate::retreat::find_valid_retreat_hexes;
+use crate::unit::{Side, Units};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use utoipa::ToSchema;
+
+/// Actions a side may take before play_side gives up on its turn
+const MAX_ACTIONS_PER_TURN: usize = 1000;
+
+/// Something that chooses actions for one side
+pub trait Player: Send {
+    /// The next action for the side whose phase it is
+    fn choose(&mut self, state: &GameState, units: &Units, map: &Map) -> Action;
+
+    /// Where one of this side's units retreats after a DR or DRL, if it can
+    fn retreat(
+        &mut self,
+        unit_id: &str,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> Option<Hex> {
+        best_retreat(unit_id, state, units, map)
+    }
+
+    /// Which attacker takes the step if a battle this side resolves ends in an AL
+    fn choose_loss(
+        &mut self,
+        battle: &BattleDeclaration,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> Option<String> {
+        best_loss(battle, state, units, map)
+    }
+
+    /// Told the final position once the game is over
+    fn game_over(&mut self, _state: &GameState) {}
+}
+
+/// The computer players the server can seat
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlayerKind {
+    /// Greedy one-ply search over the position evaluation
+    #[default]
+    Heuristic,
+    /// Monte Carlo tree search
+    Mcts(MctsConfig),
+}
+
+impl PlayerKind {
+    pub fn player(&self) -> Box<dyn Player> {
+        match self {
+            PlayerKind::Heuristic => Box::new(HeuristicPlayer::new()),
+            PlayerKind::Mcts(config) => Box::new(MctsPlayer::new(config.clone())),
+        }
+    }
+}
+
+/// A side for the server to play itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ComputerOpponent {
+    pub side: Side,
+    #[serde(default)]
+    pub player: PlayerKind,
+}
+
+/// Let a player act for `side` until the phase passes to the other side or the game ends
+///
+/// Actions go through `perform`, so callers can record and publish them. A rejected
+/// action ends the player's phase. A battle resolved without naming its loss takes the
+/// player's `choose_loss`. When a battle forces an enemy unit back, `retreat_for` picks its
+/// hex. Returns the number of actions performed.
+pub fn play_side(
+    player: &mut dyn Player,
+    side: Side,
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+    mut perform: impl FnMut(&mut GameState, Action) -> Result<ActionOutcome, RuleError>,
+    mut retreat_for: impl FnMut(&str, &GameState) -> Option<Hex>,
+) -> Result<usize, RuleError> {
+    let mut performed = 0;
+    while !state.is_over() && state.active_player() == side {
+        if performed >= MAX_ACTIONS_PER_TURN {
+            perform(state, Action::AdvancePhase)?;
+            performed += 1;
+            continue;
+        }
+
+        let mut action = player.choose(state, units, map);
+        if let Action::ResolveBattle { battle_index, loss } = &mut action {
+            if let (None, Some(battle)) = (&loss, state.pending_battles.get(*battle_index)) {
+                *loss = player.choose_loss(battle, state, units, map);
+            }
+        }
+        let outcome = match perform(state, action.clone()) {
+            Ok(outcome) => outcome,
+            Err(e) if action == Action::AdvancePhase => return Err(e),
+            Err(_) => perform(state, Action::AdvancePhase)?,
+        };
+        performed += 1;
+
+        if let (Action::ResolveBattle { battle_index, .. }, Some(resolution)) =
+            (&action, &outcome.resolution)
+        {
+            if matches!(resolution.result, CombatResult::DR | CombatResult::DRL) {
+                let defender = state.pending_battles[*battle_index].defender.clone();
+                let on_map = state.get_unit(&defender).is_some_and(|u| u.hex().is_some());
+                if let Some(to) = on_map.then(|| retreat_for(&defender, state)).flatten() {
+                    perform(
+                        state,
+                        Action::Retreat {
+                            unit_id: defender,
+                            to,
+                        },
+                    )?;
+                    performed += 1;
+                }
+            }
+        }
+    }
+    Ok(performed)
+}
+
+/// Play a whole game between two players with the rules engine, returning the actions taken
+pub fn play_game<'a>(
+    german: &mut (dyn Player + 'a),
+    soviet: &mut (dyn Player + 'a),
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<usize, RuleError> {
+    play_game_with(german, soviet, state, units, map, |state, action| {
+        apply_action(&action, state, units, map)
+    })
+}
+
+/// Play a whole game between two players, carrying out each action with `perform`
+pub fn play_game_with<'a>(
+    german: &mut (dyn Player + 'a),
+    soviet: &mut (dyn Player + 'a),
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+    mut perform: impl FnMut(&mut GameState, Action) -> Result<ActionOutcome, RuleError>,
+) -> Result<usize, RuleError> {
+    let mut performed = 0;
+    while !state.is_over() {
+        let side = state.active_player();
+        let (player, opponent) = match side {
+            Side::German => (&mut *german, &mut *soviet),
+            Side::Soviet => (&mut *soviet, &mut *german),
+        };
+        performed += play_side(
+            player,
+            side,
+            state,
+            units,
+            map,
+            &mut perform,
+            |unit_id, state| opponent.retreat(unit_id, state, units, map),
+        )?;
+    }
+    german.game_over(state);
+    soviet.game_over(state);
+    Ok(performed)
+}
+
+/// The attacker whose step loss the evaluation minds least for its side
+pub fn best_loss(
+    battle: &BattleDeclaration,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Option<String> {
+    let side = units.get(battle.attackers.first()?)?.side;
+    battle
+        .attackers
+        .iter()
+        .filter_map(|id| {
+            let mut next = state.clone();
+            next.get_unit_mut(id)?.take_loss();
+            Some((id, evaluate_position(&next, units, map).score_for(side)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id.clone())
+}
+
+/// The retreat hex the evaluation favours for the unit's side, avoiding enemy ZOC if it can
+pub fn best_retreat(unit_id: &str, state: &GameState, units: &Units, map: &Map) -> Option<Hex> {
+    let side = units.get(unit_id)?.side;
+    let from = state.get_unit(unit_id)?.hex()?;
+    find_valid_retreat_hexes(unit_id, &from, state, units, map)
+        .into_iter()
+        .map(|to| {
+            let action = Action::Retreat {
+                unit_id: unit_id.to_string(),
+                to,
+            };
+            (to, score_after(&action, side, state, units, map))
+        })
+        .filter_map(|(to, score)| score.map(|s| (to, s)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(to, _)| to)
+}
+
+/// Every action the side to play may take now, other than retreats
+///
+/// That is each move of each unit still free to move, each resolution of a pending battle,
+/// each group of units that could attack each enemy unit, each replacement, and advancing
+/// the phase, which is always last.
+pub fn legal_actions(state: &GameState, units: &Units, map: &Map) -> Vec<Action> {
+    if state.is_over() {
+        return Vec::new();
+    }
+    let side = state.active_player();
+    let mut actions = Vec::new();
+
+    if state.phase.is_replacement_phase() {
+        actions = replacement_actions(side, state, units, map);
+    } else if state.phase.is_movement_phase() {
+        for unit_state in &state.units {
+            if unit_state.hex().is_none()
+                || state.has_moved(&unit_state.id)
+                || units.get(&unit_state.id).is_none_or(|def| def.side != side)
+            {
+                continue;
+            }
+            let options = find_move_options(&unit_state.id, state, units, map).unwrap_or_default();
+            actions.extend(options.into_iter().map(|option| Action::Move {
+                unit_id: unit_state.id.clone(),
+                to: option.hex,
+            }));
+        }
+    } else if state.phase.is_combat_phase() {
+        for (battle_index, battle) in state.pending_battles.iter().enumerate() {
+            if !battle.resolved {
+                actions.push(Action::ResolveBattle {
+                    battle_index,
+                    loss: None,
+                });
+            }
+        }
+        let options = find_valid_attacks(state, units, map).unwrap_or_default();
+        for option in options {
+            actions.extend(
+                option
+                    .groups
+                    .into_iter()
+                    .map(|group| Action::DeclareBattle {
+                        attacker_ids: group.attackers,
+                        defender_id: group.defender,
+                    }),
+            );
+        }
+    }
+    actions.push(Action::AdvancePhase);
+    actions
+}
+
+/// Every replacement `side` could make now: restoring a unit in place, or bringing an
+/// eliminated one back on each hex open to it
+pub(crate) fn replacement_actions(
+    side: Side,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Vec<Action> {
+    if replacements_remaining(side, state) == 0 {
+        return Vec::new();
+    }
+    replacement_options(side, state, units, map)
+        .into_iter()
+        .flat_map(|option| {
+            let hexes: Vec<Option<Hex>> = match option.hex {
+                Some(_) => vec![None],
+                None => option.placements.into_iter().map(Some).collect(),
+            };
+            let unit_id = option.unit_id;
+            hexes.into_iter().map(move |hex| Action::Replacement {
+                unit_id: unit_id.clone(),
+                hex,
+            })
+        })
+        .collect()
+}
+
+/// The evaluation for `side` after an action, or `None` if the rules reject it
+pub(crate) fn score_after(
+    action: &Action,
+    side: Side,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Option<f64> {
+    let mut next = state.clone();
+    apply_action(action, &mut next, units, map).ok()?;
+    Some(evaluate_position(&next, units, map).score_for(side))
+}
+
+/// Greedy player: each decision takes whatever most improves the position evaluation
+///
+/// Replacements and moves are tried one at a time against the evaluation. In combat it
+/// declares the best plan from the attack solver and resolves the battles in order.
+#[derive(Debug, Default)]
+pub struct HeuristicPlayer {
+    /// The phase the memory below belongs to
+    phase: Option<(i32, Phase)>,
+    /// Units already weighed this phase
+    considered: HashSet<String>,
+    /// Battles still to declare from this phase's plan
+    queued: VecDeque<Action>,
+    planned: bool,
+}
+
+impl HeuristicPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn start_phase(&mut self, state: &GameState) {
+        let phase = (state.turn, state.phase.clone());
+        if self.phase.as_ref() != Some(&phase) {
+            self.phase = Some(phase);
+            self.considered.clear();
+            self.queued.clear();
+            self.planned = false;
+        }
+    }
+
+    fn choose_replacement(&self, state: &GameState, units: &Units, map: &Map) -> Option<Action> {
+        let side = state.active_player();
+        let candidates = replacement_actions(side, state, units, map).into_iter();
+        best_action(candidates, side, state, units, map).map(|(action, _)| action)
+    }
+
+    fn choose_move(&mut self, state: &GameState, units: &Units, map: &Map) -> Option<Action> {
+        let side = state.active_player();
+        let standing = evaluate_position(state, units, map).score_for(side);
+        for unit_state in &state.units {
+            if unit_state.hex().is_none()
+                || state.has_moved(&unit_state.id)
+                || self.considered.contains(&unit_state.id)
+                || units.get(&unit_state.id).is_none_or(|def| def.side != side)
+            {
+                continue;
+            }
+            self.considered.insert(unit_state.id.clone());
+
+            let options = find_move_options(&unit_state.id, state, units, map).unwrap_or_default();
+            let moves = options.into_iter().map(|option| Action::Move {
+                unit_id: unit_state.id.clone(),
+                to: option.hex,
+            });
+            if let Some((action, score)) = best_action(moves, side, state, units, map) {
+                if score > standing {
+                    return Some(action);
+                }
+            }
+        }
+        None
+    }
+
+    fn choose_combat(&mut self, state: &GameState, units: &Units, map: &Map) -> Option<Action> {
+        if !self.planned {
+            self.planned = true;
+            if let Some(plan) = plan_attacks(state, units, map, 1)
+                .ok()
+                .and_then(|p| p.into_iter().next())
+            {
+                self.queued = plan
+                    .battles
+                    .into_iter()
+                    .map(|battle| Action::DeclareBattle {
+                        attacker_ids: battle.attackers,
+                        defender_id: battle.defender,
+                    })
+                    .collect();
+            }
+        }
+        if let Some(declare) = self.queued.pop_front() {
+            return Some(declare);
+        }
+        state
+            .pending_battles
+            .iter()
+            .position(|battle| !battle.resolved)
+            .map(|battle_index| Action::ResolveBattle {
+                battle_index,
+                loss: None,
+            })
+    }
+}
+
+impl Player for HeuristicPlayer {
+    fn choose(&mut self, state: &GameState, units: &Units, map: &Map) -> Action {
+        self.start_phase(state);
+        let action = if state.phase.is_replacement_phase() {
+            self.choose_replacement(state, units, map)
+        } else if state.phase.is_movement_phase() {
+            self.choose_move(state, units, map)
+        } else if state.phase.is_combat_phase() {
+            self.choose_combat(state, units, map)
+        } else {
+            None
+        };
+        action.unwrap_or(Action::AdvancePhase)
+    }
+}
+
+/// The legal action scoring best for `side`, with its score
+fn best_action(
+    actions: impl Iterator<Item = Action>,
+    side: Side,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Option<(Action, f64)> {
+    actions
+        .filter_map(|action| {
+            let score = score_after(&action, side, state, units, map)?;
+            Some((action, score))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::bundled;
+    use crate::unit::{UnitState, UnitStrength};
+
+    /// Always asks for a move the rules reject
+    struct Stubborn;
+
+    impl Player for Stubborn {
+        fn choose(&mut self, _: &GameState, _: &Units, _: &Map) -> Action {
+            Action::Move {
+                unit_id: "nobody".to_string(),
+                to: Hex::new(0, 0),
+            }
+        }
+    }
+
+    /// Resolves each battle, putting any loss on the last attacker declared
+    #[derive(Default)]
+    struct LastTakesLoss {
+        asked: usize,
+    }
+
+    impl Player for LastTakesLoss {
+        fn choose(&mut self, state: &GameState, _: &Units, _: &Map) -> Action {
+            match state.pending_battles.iter().position(|b| !b.resolved) {
+                Some(battle_index) => Action::ResolveBattle {
+                    battle_index,
+                    loss: None,
+                },
+                None => Action::AdvancePhase,
+            }
+        }
+
+        fn choose_loss(
+            &mut self,
+            battle: &BattleDeclaration,
+            _: &GameState,
+            _: &Units,
+            _: &Map,
+        ) -> Option<String> {
+            self.asked += 1;
+            battle.attackers.last().cloned()
+        }
+    }
+
+    #[test]
+    fn test_self_play() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("self-play");
+        state.place_initial_units(&units, &map);
+
+        let mut german = HeuristicPlayer::new();
+        let mut soviet = HeuristicPlayer::new();
+        let performed = play_game(&mut german, &mut soviet, &mut state, &units, &map).unwrap();
+        assert!(state.is_over());
+        assert!(performed > 0);
+
+        // The German infantry closed on Moscow
+        let v = state.get_unit("V").unwrap();
+        assert!(v.hex().is_none_or(|hex| hex.q > 0));
+    }
+
+    #[test]
+    fn test_heuristic_attacks() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("heuristic-attack");
+        state.phase = Phase::GermanCombat;
+        for (id, q) in [("XXIV", 1), ("3", 0)] {
+            state.units.push(UnitState::new(
+                id.to_string(),
+                Some(Hex::new(q, 0)),
+                UnitStrength::Full,
+            ));
+        }
+        state.get_unit_mut("3").unwrap().take_loss();
+
+        let mut player = HeuristicPlayer::new();
+        let declare = player.choose(&state, &units, &map);
+        assert!(matches!(
+            declare,
+            Action::DeclareBattle { ref defender_id, .. } if defender_id == "3"
+        ));
+        apply_action(&declare, &mut state, &units, &map).unwrap();
+        assert_eq!(
+            player.choose(&state, &units, &map),
+            Action::ResolveBattle {
+                battle_index: 0,
+                loss: None,
+            }
+        );
+        apply_action(
+            &Action::ResolveBattle {
+                battle_index: 0,
+                loss: None,
+            },
+            &mut state,
+            &units,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(player.choose(&state, &units, &map), Action::AdvancePhase);
+    }
+
+    #[test]
+    fn test_play_side_asks_for_losses() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("losses");
+        state.phase = Phase::GermanCombat;
+        for (id, hex) in [("3", (0, 0)), ("XXIV", (1, 0)), ("V", (0, 1))] {
+            state.units.push(UnitState::new(
+                id.to_string(),
+                Some(Hex::new(hex.0, hex.1)),
+                UnitStrength::Full,
+            ));
+        }
+        let declare = Action::DeclareBattle {
+            attacker_ids: vec!["XXIV".to_string(), "V".to_string()],
+            defender_id: "3".to_string(),
+        };
+        apply_action(&declare, &mut state, &units, &map).unwrap();
+
+        let mut player = LastTakesLoss::default();
+        let mut actions = Vec::new();
+        let perform = |state: &mut GameState, action: Action| {
+            actions.push(action.clone());
+            apply_action(&action, state, &units, &map)
+        };
+        play_side(
+            &mut player,
+            Side::German,
+            &mut state,
+            &units,
+            &map,
+            perform,
+            |_, _| None,
+        )
+        .unwrap();
+        assert_eq!(player.asked, 1);
+        assert_eq!(
+            actions[0],
+            Action::ResolveBattle {
+                battle_index: 0,
+                loss: Some("V".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejected_actions_end_the_phase() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("stubborn");
+        state.place_initial_units(&units, &map);
+
+        let performed = play_side(
+            &mut Stubborn,
+            Side::German,
+            &mut state,
+            &units,
+            &map,
+            |state, action| apply_action(&action, state, &units, &map),
+            |_, _| None,
+        )
+        .unwrap();
+        assert_eq!(state.active_player(), Side::Soviet);
+        assert_eq!(performed, 3);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::combat::{find_valid_attacks, AttackOption, BattlePreview, CombatResult};
+use crate::error::RuleError;
+use crate::game_state::GameState;
+use crate::map::Map;
+use crate::unit::Units;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Value of each step the defender is expected to lose
+const DEFENDER_STEP_VALUE: f64 = 1.0;
+/// Value of forcing the defender out of its hex
+const RETREAT_VALUE: f64 = 0.5;
+/// Cost of each step the attackers are expected to lose
+const ATTACKER_STEP_COST: f64 = 1.0;
+
+/// Plans returned when the caller does not ask for a number
+pub const DEFAULT_PLAN_LIMIT: usize = 5;
+
+/// A set of battles that can all be declared together, with what they are expected to gain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AttackPlan {
+    pub battles: Vec<BattlePreview>,
+    /// Defender steps destroyed, summed over the battles
+    pub expected_defender_losses: f64,
+    /// Retreats forced, summed over the battles
+    pub expected_retreats: f64,
+    /// Attacker steps lost, summed over the battles
+    pub expected_attacker_losses: f64,
+    /// Steps destroyed plus half a step per retreat, less steps lost
+    pub score: f64,
+}
+
+/// A battle worth considering, with its score
+struct Candidate<'a> {
+    preview: &'a BattlePreview,
+    retreats: f64,
+    score: f64,
+}
+
+impl<'a> Candidate<'a> {
+    fn new(preview: &'a BattlePreview) -> Self {
+        let retreats: f64 = preview
+            .outcomes
+            .iter()
+            .filter(|o| matches!(o.result, CombatResult::DR | CombatResult::DRL))
+            .map(|o| o.probability)
+            .sum();
+        let score = preview.expected_defender_losses * DEFENDER_STEP_VALUE
+            + retreats * RETREAT_VALUE
+            - preview.expected_attacker_losses * ATTACKER_STEP_COST;
+        Candidate {
+            preview,
+            retreats,
+            score,
+        }
+    }
+
+    fn shares_attackers(&self, attackers: &[&str]) -> bool {
+        self.preview
+            .attackers
+            .iter()
+            .any(|id| attackers.contains(&id.as_str()))
+    }
+}
+
+/// The best ways to split the active side's units among the enemy units they can attack
+///
+/// Every unit attacks at most once and every defender is attacked at most once. Battles
+/// expected to cost more than they gain are never proposed, and a group is dropped when
+/// fewer of its units would do as well. Plans come best score first.
+pub fn plan_attacks(
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+    limit: usize,
+) -> Result<Vec<AttackPlan>, RuleError> {
+    let options = find_valid_attacks(state, units, map)?;
+    let targets: Vec<Vec<Candidate>> = options
+        .iter()
+        .map(useful_candidates)
+        .filter(|candidates| !candidates.is_empty())
+        .collect();
+
+    // What the remaining targets could add at best, ignoring shared attackers
+    let mut bounds = vec![0.0; targets.len() + 1];
+    for i in (0..targets.len()).rev() {
+        bounds[i] = bounds[i + 1] + targets[i][0].score;
+    }
+
+    let mut search = Search {
+        targets: &targets,
+        bounds: &bounds,
+        limit,
+        chosen: Vec::new(),
+        plans: Vec::new(),
+    };
+    search.visit(0, 0.0);
+
+    Ok(search
+        .plans
+        .into_iter()
+        .map(|(_, chosen)| plan(&chosen))
+        .collect())
+}
+
+/// The groups attacking one defender that are worth declaring, best first
+fn useful_candidates(option: &AttackOption) -> Vec<Candidate<'_>> {
+    let mut candidates: Vec<Candidate> = option
+        .groups
+        .iter()
+        .map(Candidate::new)
+        .filter(|c| c.score > 0.0)
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.preview.attackers.len().cmp(&b.preview.attackers.len()))
+    });
+
+    // Drop a group when a smaller one made of its units scores as well
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        let dominated = kept.iter().any(|k| {
+            k.score >= candidate.score
+                && k.preview
+                    .attackers
+                    .iter()
+                    .all(|id| candidate.preview.attackers.contains(id))
+        });
+        if !dominated {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// Depth-first search over targets, keeping the best `limit` plans
+struct Search<'a> {
+    targets: &'a [Vec<Candidate<'a>>],
+    bounds: &'a [f64],
+    limit: usize,
+    chosen: Vec<&'a Candidate<'a>>,
+    plans: Vec<(f64, Vec<&'a Candidate<'a>>)>,
+}
+
+impl<'a> Search<'a> {
+    fn visit(&mut self, target: usize, score: f64) {
+        if self.limit == 0 {
+            return;
+        }
+        // Nothing below this point can beat the worst plan kept
+        if self.plans.len() == self.limit {
+            let worst = self.plans[self.limit - 1].0;
+            if score + self.bounds[target] <= worst {
+                return;
+            }
+        }
+
+        if target == self.targets.len() {
+            if !self.chosen.is_empty() {
+                self.keep(score);
+            }
+            return;
+        }
+
+        let committed: Vec<&str> = self
+            .chosen
+            .iter()
+            .flat_map(|c| c.preview.attackers.iter().map(String::as_str))
+            .collect();
+        for candidate in &self.targets[target] {
+            if candidate.shares_attackers(&committed) {
+                continue;
+            }
+            self.chosen.push(candidate);
+            self.visit(target + 1, score + candidate.score);
+            self.chosen.pop();
+        }
+        // Or leave this defender alone
+        self.visit(target + 1, score);
+    }
+
+    fn keep(&mut self, score: f64) {
+        let at = self.plans.partition_point(|(s, _)| *s >= score);
+        if at < self.limit {
+            self.plans.insert(at, (score, self.chosen.clone()));
+            self.plans.truncate(self.limit);
+        }
+    }
+}
+
+/// Total up the chosen battles
+fn plan(chosen: &[&Candidate]) -> AttackPlan {
+    AttackPlan {
+        battles: chosen.iter().map(|c| c.preview.clone()).collect(),
+        expected_defender_losses: chosen
+            .iter()
+            .map(|c| c.preview.expected_defender_losses)
+            .sum(),
+        expected_retreats: chosen.iter().map(|c| c.retreats).sum(),
+        expected_attacker_losses: chosen
+            .iter()
+            .map(|c| c.preview.expected_attacker_losses)
+            .sum(),
+        score: chosen.iter().map(|c| c.score).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::Phase;
+    use crate::hex::Hex;
+    use crate::test_support::{test_map, unit};
+    use crate::unit::{Side, UnitState, UnitStrength, UnitType};
+
+    /// Two German units between two Soviet ones: A touches both, B touches only the east one
+    fn setup() -> (GameState, Units, Map) {
+        let map = test_map(5, 1, None);
+        let units = Units {
+            units: vec![
+                unit("A", Side::German, UnitType::Infantry, 12, 4),
+                unit("B", Side::German, UnitType::Infantry, 12, 4),
+                unit("west", Side::Soviet, UnitType::Infantry, 4, 4),
+                unit("east", Side::Soviet, UnitType::Infantry, 4, 4),
+            ],
+        };
+        let mut state = GameState::with_seed("analysis-test");
+        state.phase = Phase::GermanCombat;
+        for (id, q) in [("west", 0), ("A", 1), ("east", 2), ("B", 3)] {
+            state.units.push(UnitState::new(
+                id.to_string(),
+                Some(Hex::new(q, 0)),
+                UnitStrength::Full,
+            ));
+        }
+        (state, units, map)
+    }
+
+    #[test]
+    fn test_plan_attacks() {
+        let (state, units, map) = setup();
+        let plans = plan_attacks(&state, &units, &map, 10).unwrap();
+
+        // Best: A and B together at 6:1 on east, which may eliminate it outright
+        let best = &plans[0];
+        assert_eq!(best.battles.len(), 1);
+        assert_eq!(best.battles[0].defender, "east");
+        assert_eq!(best.battles[0].modified_odds, "6:1");
+        assert!((best.expected_defender_losses - 1.0).abs() < 1e-9);
+        assert_eq!(best.expected_attacker_losses, 0.0);
+
+        // Splitting them for two 3:1 attacks is also on the list
+        assert!(plans
+            .iter()
+            .any(|p| p.battles.len() == 2 && p.battles.iter().all(|b| b.modified_odds == "3:1")));
+
+        // No unit attacks twice and no defender is attacked twice in any plan
+        for plan in &plans {
+            let mut attackers: Vec<&String> =
+                plan.battles.iter().flat_map(|b| &b.attackers).collect();
+            let total = attackers.len();
+            attackers.sort();
+            attackers.dedup();
+            assert_eq!(attackers.len(), total);
+            assert!(plan.battles.len() <= 2);
+        }
+        assert!(plans.windows(2).all(|w| w[0].score >= w[1].score));
+
+        assert_eq!(plan_attacks(&state, &units, &map, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_plan_attacks_outside_combat() {
+        let (mut state, units, map) = setup();
+        state.phase = Phase::GermanMovement;
+        assert!(matches!(
+            plan_attacks(&state, &units, &map, 5),
+            Err(RuleError::WrongPhase(_))
+        ));
+    }
+}
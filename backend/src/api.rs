@@ -1,20 +1,39 @@
-use crate::combat::{apply_combat_result, resolve_battle};
-use crate::game_state::{BattleDeclaration, GameState};
+use crate::action::{apply_action, Action, ActionOutcome};
+use crate::ai::{best_retreat, play_side, ComputerOpponent, Player};
+use crate::analysis::{plan_attacks, AttackPlan, DEFAULT_PLAN_LIMIT};
+use crate::combat::{
+    needs_die_roll, preview_battle, BattlePreview, BattleResolution, CombatResult,
+};
+use crate::dice::DiceAudit;
+use crate::error::RuleError;
+use crate::evaluate::{assess_position, Assessment};
+use crate::events::{EventLog, EventRecord, GameEvent};
+use crate::game_state::GameState;
 use crate::hex::Hex;
 use crate::map::Map;
-use crate::movement::{find_valid_moves, validate_move};
-use crate::replacement::{apply_replacement, get_valid_replacement_hexes, validate_replacement_placement};
-use crate::retreat::{execute_retreat, find_valid_retreat_hexes};
-use crate::unit::{Units, UnitState, UnitStrength};
+use crate::movement::find_valid_moves;
+use crate::openapi::ApiDoc;
+use crate::render::{render_svg, CoordinateLabels, RenderOptions};
+use crate::replacement::get_valid_replacement_hexes;
+use crate::retreat::{find_valid_retreat_hexes, RetreatResult};
+use crate::seats::{Seat, SeatSummary, Seats};
+use crate::threats::{analyze_threats, ThreatReport};
+use crate::turn_file::{replay_turn, state_hash, TurnFile, TurnRecorder};
+use crate::unit::{Side, Units};
 use axum::{
-    extract::State as AxumState,
-    http::StatusCode,
-    response::{IntoResponse, Json},
-    routing::{get, post},
+    extract::{Query, State as AxumState},
+    http::{header, HeaderMap, Method, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    routing::{on, MethodFilter, MethodRouter},
     Router,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 /// Shared application state
 #[derive(Clone)]
@@ -22,16 +41,176 @@ pub struct AppState {
     pub game: Arc<RwLock<GameState>>,
     pub units: Arc<Units>,
     pub map: Arc<Map>,
+    pub events: Arc<Mutex<EventLog>>,
+    pub seats: Arc<Mutex<Seats>>,
+    pub turns: Arc<Mutex<TurnRecorder>>,
+    /// Plays the side the seats give to the computer
+    pub computer: Arc<Mutex<Option<Box<dyn Player>>>>,
+}
+
+impl AppState {
+    /// State for a server running the given game
+    pub fn new(game: GameState, units: Units, map: Map) -> Self {
+        AppState {
+            game: Arc::new(RwLock::new(game)),
+            units: Arc::new(units),
+            map: Arc::new(map),
+            events: Arc::new(Mutex::new(EventLog::new())),
+            seats: Arc::new(Mutex::new(Seats::new())),
+            turns: Arc::new(Mutex::new(TurnRecorder::new())),
+            computer: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Record a game event and push it to subscribers
+    fn publish(&self, event: GameEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    /// Apply an action, recording it for turn export and publishing its events
+    fn perform(&self, game: &mut GameState, action: Action) -> Result<ActionOutcome, RuleError> {
+        let before = game.clone();
+        let outcome = apply_action(&action, game, &self.units, &self.map)?;
+        self.turns
+            .lock()
+            .unwrap()
+            .record(&action, &outcome, &before, game);
+        for event in &outcome.events {
+            self.publish(event.clone());
+        }
+        Ok(outcome)
+    }
+
+    /// Let the computer play while it is its side's phase
+    ///
+    /// The computer thinks on a copy of the game in a blocking task, so other requests are
+    /// served meanwhile. Its actions are then applied to the game under a short write lock;
+    /// if the game has moved on in the meantime, it thinks again from the new position.
+    async fn play_computer(&self) -> Result<(), RuleError> {
+        loop {
+            let Some(side) = self.seats.lock().unwrap().computer() else {
+                return Ok(());
+            };
+            let snapshot = self.game.read().unwrap().clone();
+            let start = state_hash(&snapshot);
+            let state = self.clone();
+            let actions = tokio::task::spawn_blocking(move || state.plan_computer(side, snapshot))
+                .await
+                .expect("The computer player panicked")?;
+
+            let mut game = self.game.write().unwrap();
+            if state_hash(&game) != start {
+                continue;
+            }
+            for action in actions {
+                self.perform(&mut game, action)?;
+            }
+            return Ok(());
+        }
+    }
+
+    /// The actions the computer plays for its side from a position
+    ///
+    /// Retreats its attacks force are carried out to the hex the evaluation favours for
+    /// the retreating side.
+    fn plan_computer(&self, side: Side, mut game: GameState) -> Result<Vec<Action>, RuleError> {
+        let mut computer = self.computer.lock().unwrap();
+        let Some(player) = computer.as_mut() else {
+            return Ok(Vec::new());
+        };
+        let mut actions = Vec::new();
+        play_side(
+            player.as_mut(),
+            side,
+            &mut game,
+            &self.units,
+            &self.map,
+            |game, action| {
+                let outcome = apply_action(&action, game, &self.units, &self.map)?;
+                actions.push(action);
+                Ok(outcome)
+            },
+            |unit_id, game| best_retreat(unit_id, game, &self.units, &self.map),
+        )?;
+        Ok(actions)
+    }
+
+    /// Have the computer retreat its defender when a battle result calls for it
+    fn computer_retreat(
+        &self,
+        game: &mut GameState,
+        battle_index: usize,
+        result: &CombatResult,
+    ) -> Result<(), RuleError> {
+        if !matches!(result, CombatResult::DR | CombatResult::DRL) {
+            return Ok(());
+        }
+        let defender = game.pending_battles[battle_index].defender.clone();
+        let side = self.seats.lock().unwrap().computer();
+        if side.is_none() || self.units.get(&defender).map(|def| def.side) != side {
+            return Ok(());
+        }
+        let mut computer = self.computer.lock().unwrap();
+        let Some(player) = computer.as_mut() else {
+            return Ok(());
+        };
+        if let Some(to) = player.retreat(&defender, game, &self.units, &self.map) {
+            let action = Action::Retreat {
+                unit_id: defender,
+                to,
+            };
+            self.perform(game, action)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the caller's seat token lets them act for a side
+    fn authorize(&self, headers: &HeaderMap, side: Side) -> Result<(), RuleError> {
+        self.seats.lock().unwrap().authorize(seat_token(headers), side)
+    }
+}
+
+/// Seat token sent as `Authorization: Bearer <token>`
+fn seat_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
 }
 
-/// API response wrapper
-#[derive(Serialize)]
+/// API response wrapper, sent with the HTTP status it carries
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    GameResponse = ApiResponse<GameState>,
+    HexesResponse = ApiResponse<Vec<Hex>>,
+    ResolutionResponse = ApiResponse<BattleResolution>,
+    PreviewResponse = ApiResponse<BattlePreview>,
+    AttackPlansResponse = ApiResponse<Vec<AttackPlan>>,
+    ThreatsResponse = ApiResponse<ThreatReport>,
+    AssessmentResponse = ApiResponse<Assessment>,
+    RetreatResponse = ApiResponse<RetreatResult>,
+    MapResponse = ApiResponse<Map>,
+    UnitsResponse = ApiResponse<Units>,
+    SeatsResponse = ApiResponse<SeatSummary>,
+    SeatClaimResponse = ApiResponse<SeatClaim>,
+    SeatResponse = ApiResponse<Seat>,
+    TurnFileResponse = ApiResponse<TurnFile>,
+    TurnCheckResponse = ApiResponse<TurnCheck>,
+    DiceAuditResponse = ApiResponse<DiceAudit>,
+)]
 pub struct ApiResponse<T> {
-    success: bool,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    data: Option<T>,
+    pub error: Option<String>,
+    /// Stable identifier of the error for clients to match on
     #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
+    pub code: Option<String>,
+    #[serde(skip)]
+    status: StatusCode,
 }
 
 impl<T> ApiResponse<T> {
@@ -40,192 +219,607 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
+            status: StatusCode::OK,
         }
     }
 
-    fn error(msg: String) -> Self {
+    fn error(err: impl Into<ApiError>) -> Self {
+        let err = err.into();
         ApiResponse {
             success: false,
             data: None,
-            error: Some(msg),
+            error: Some(err.message),
+            code: Some(err.code.to_string()),
+            status: err.status,
         }
     }
 }
 
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+/// Why a request failed: HTTP status, stable code and message
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// A malformed or incomplete request
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+}
+
+impl From<RuleError> for ApiError {
+    fn from(err: RuleError) -> Self {
+        Self::new(rule_status(&err), err.code(), err.to_string())
+    }
+}
+
+/// Request parsing errors are reported as bad requests
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::bad_request(message)
+    }
+}
+
+/// HTTP status for a rule error
+///
+/// Missing things are 404, acting out of turn or out of sequence is 409, missing or
+/// wrong seat tokens are 401 and 403, and moves the rules forbid are 422.
+pub fn rule_status(err: &RuleError) -> StatusCode {
+    match err {
+        RuleError::UnitNotFound(_) | RuleError::BattleNotFound(_) | RuleError::NoTurnRecorded => {
+            StatusCode::NOT_FOUND
+        }
+        RuleError::NotYourTurn
+        | RuleError::WrongPhase(_)
+        | RuleError::BattleAlreadyResolved(_)
+        | RuleError::NoRetreatPending(_)
+        | RuleError::DiceNotReady
+        | RuleError::NotCommitReveal
+        | RuleError::AlreadyCommitted(_)
+        | RuleError::NotAllCommitted
+        | RuleError::AlreadyRevealed(_)
+        | RuleError::RollPending(_)
+        | RuleError::RollNotNeeded(_)
+        | RuleError::GameNotOver
+        | RuleError::SeatTaken(_)
+        | RuleError::ComputerSeat(_) => StatusCode::CONFLICT,
+        RuleError::UnknownSeatToken | RuleError::SeatTokenRequired(_) => StatusCode::UNAUTHORIZED,
+        RuleError::WrongSeat(..) | RuleError::NotSeated => StatusCode::FORBIDDEN,
+        RuleError::InvalidCommitment => StatusCode::BAD_REQUEST,
+        _ => StatusCode::UNPROCESSABLE_ENTITY,
+    }
+}
+
 /// Request to create a new game
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct NewGameRequest {
-    // Could include options like scenario selection
+    /// Dice seed agreed between play-by-email opponents; random if absent
+    #[serde(default)]
+    pub seed: Option<String>,
+    /// Roll the dice from hash chains both seated players commit to
+    #[serde(default)]
+    pub commit_reveal: bool,
+    /// A side for the server to play itself
+    #[serde(default)]
+    pub computer: Option<ComputerOpponent>,
+    /// Let callers without a seat token act for sides nobody has claimed
+    #[serde(default)]
+    pub hotseat: bool,
 }
 
 /// Request to move a unit
-#[derive(Deserialize)]
+/// The destination is given either as `to_q`/`to_r` or as a printed hex number in `to_original`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MoveRequest {
     pub unit_id: String,
-    pub to_q: i32,
-    pub to_r: i32,
+    pub to_q: Option<i32>,
+    pub to_r: Option<i32>,
+    pub to_original: Option<String>,
 }
 
 /// Request to declare a battle
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DeclareBattleRequest {
     pub attacker_ids: Vec<String>,
     pub defender_id: String,
 }
 
 /// Request to resolve a battle
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ResolveBattleRequest {
     pub battle_index: usize,
+    /// The attacker that takes the step on an AL; the first one declared if absent
+    #[serde(default)]
+    pub loss: Option<String>,
 }
 
 /// Request to apply replacement
-#[derive(Deserialize)]
+/// The optional placement hex is given either as `hex_q`/`hex_r` or as `hex_original`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ReplacementRequest {
     pub unit_id: String,
     pub hex_q: Option<i32>,
     pub hex_r: Option<i32>,
+    pub hex_original: Option<String>,
 }
 
 /// Request to retreat a unit
-#[derive(Deserialize)]
+/// The destination is given either as `to_q`/`to_r` or as `to_original`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RetreatRequest {
     pub unit_id: String,
-    pub to_q: i32,
-    pub to_r: i32,
+    pub to_q: Option<i32>,
+    pub to_r: Option<i32>,
+    pub to_original: Option<String>,
+}
+
+/// Query options for the rendered map
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderQuery {
+    /// Hex radius in pixels
+    pub size: Option<f64>,
+    pub coords: Option<CoordinateLabels>,
+    /// Shade the ZOC exerted by this side's units
+    pub zoc: Option<Side>,
+    /// Hexes separated by ';', each "q,r" or a printed number "XXYY"
+    pub highlight: Option<String>,
+    /// Unit ID whose valid moves are highlighted
+    pub moves: Option<String>,
+    pub title: Option<bool>,
+}
+
+/// Request to claim a seat
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClaimSeatRequest {
+    pub seat: Seat,
+}
+
+/// A claimed seat and the token that proves ownership
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SeatClaim {
+    pub seat: Seat,
+    pub token: String,
+}
+
+/// A seated player's commitment to the end of their hash chain
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CommitSeedRequest {
+    /// The last link of the chain, as 64 hex digits
+    pub commitment: String,
+}
+
+/// A seated player's next hash chain link, for the roll of one battle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevealSeedRequest {
+    /// The link whose SHA-256 is the side's previous link
+    pub link: String,
+    /// Defender of the pending battle the roll is for
+    pub defender: String,
+}
+
+/// Query options for the event stream
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    /// Replay events after this ID before streaming new ones
+    pub since: Option<u64>,
+}
+
+/// Query options for attack plans
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AttackPlanQuery {
+    /// How many plans to return, best first (default 5)
+    pub limit: Option<usize>,
+}
+
+/// Query options for threat analysis
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ThreatQuery {
+    /// The side under threat (default the active side)
+    pub side: Option<Side>,
+}
+
+/// Query options for position evaluation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EvaluateQuery {
+    /// Random games to play out for the win chances, at most 100 (default 0, reading
+    /// them from the score)
+    pub playouts: Option<usize>,
+    /// Seeds the playouts (default 0)
+    pub seed: Option<u64>,
+}
+
+/// Resolve a request hex given as axial coordinates or as a printed map number
+fn request_hex(
+    q: Option<i32>,
+    r: Option<i32>,
+    original: Option<&str>,
+) -> Result<Option<Hex>, String> {
+    match (q.zip(r), original) {
+        (Some((q, r)), None) => Ok(Some(Hex::new(q, r))),
+        (None, Some(number)) => Hex::from_original(number)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid hex number {}", number)),
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => {
+            Err("Give either axial coordinates or a hex number, not both".to_string())
+        }
+    }
+}
+
+/// Parse a list of hexes separated by ';', each "q,r" or a printed number "XXYY"
+fn parse_hex_list(list: &str) -> Result<Vec<Hex>, String> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            if let Some(hex) = Hex::from_original(item) {
+                return Ok(hex);
+            }
+            let (q, r) = item
+                .split_once(',')
+                .ok_or_else(|| format!("Invalid hex {}", item))?;
+            match (q.trim().parse(), r.trim().parse()) {
+                (Ok(q), Ok(r)) => Ok(Hex::new(q, r)),
+                _ => Err(format!("Invalid hex {}", item)),
+            }
+        })
+        .collect()
 }
 
 /// Create the API router
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        .route("/api/game", get(get_game_state))
-        .route("/api/game/new", post(new_game))
-        .route("/api/game/advance-phase", post(advance_phase))
-        .route("/api/units/move", post(move_unit))
-        .route("/api/units/:unit_id/valid-moves", get(get_valid_moves))
-        .route("/api/battle/declare", post(declare_battle))
-        .route("/api/battle/resolve", post(resolve_battle_endpoint))
-        .route("/api/replacement/apply", post(apply_replacement_endpoint))
-        .route("/api/replacement/valid-hexes", get(get_replacement_hexes))
-        .route("/api/retreat/execute", post(retreat_unit))
-        .route("/api/retreat/:unit_id/valid-hexes", get(get_retreat_hexes))
-        .route("/api/map", get(get_map))
-        .route("/api/units", get(get_units))
+    Endpoint::ALL
+        .iter()
+        .fold(Router::new(), |router, endpoint| {
+            router.route(&endpoint.axum_path(), endpoint.method_router())
+        })
         .with_state(state)
 }
 
+/// Every route the API serves
+///
+/// The router, the OpenAPI document and the typed client are all built from this list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    GetGame,
+    NewGame,
+    AdvancePhase,
+    MoveUnit,
+    ValidMoves,
+    DeclareBattle,
+    ResolveBattle,
+    PreviewBattle,
+    AttackPlans,
+    Threats,
+    Evaluate,
+    ApplyReplacement,
+    ReplacementHexes,
+    ExecuteRetreat,
+    RetreatHexes,
+    GetMap,
+    RenderSvg,
+    GetUnits,
+    Events,
+    GetSeats,
+    ClaimSeat,
+    ReleaseSeat,
+    ExportTurn,
+    VerifyTurn,
+    ImportTurn,
+    CommitSeed,
+    RevealSeed,
+    AuditDice,
+    OpenApi,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 29] = [
+        Endpoint::GetGame,
+        Endpoint::NewGame,
+        Endpoint::AdvancePhase,
+        Endpoint::MoveUnit,
+        Endpoint::ValidMoves,
+        Endpoint::DeclareBattle,
+        Endpoint::ResolveBattle,
+        Endpoint::PreviewBattle,
+        Endpoint::AttackPlans,
+        Endpoint::Threats,
+        Endpoint::Evaluate,
+        Endpoint::ApplyReplacement,
+        Endpoint::ReplacementHexes,
+        Endpoint::ExecuteRetreat,
+        Endpoint::RetreatHexes,
+        Endpoint::GetMap,
+        Endpoint::RenderSvg,
+        Endpoint::GetUnits,
+        Endpoint::Events,
+        Endpoint::GetSeats,
+        Endpoint::ClaimSeat,
+        Endpoint::ReleaseSeat,
+        Endpoint::ExportTurn,
+        Endpoint::VerifyTurn,
+        Endpoint::ImportTurn,
+        Endpoint::CommitSeed,
+        Endpoint::RevealSeed,
+        Endpoint::AuditDice,
+        Endpoint::OpenApi,
+    ];
+
+    pub fn method(self) -> Method {
+        match self {
+            Endpoint::NewGame
+            | Endpoint::AdvancePhase
+            | Endpoint::MoveUnit
+            | Endpoint::DeclareBattle
+            | Endpoint::ResolveBattle
+            | Endpoint::PreviewBattle
+            | Endpoint::ApplyReplacement
+            | Endpoint::ExecuteRetreat
+            | Endpoint::ClaimSeat
+            | Endpoint::ReleaseSeat
+            | Endpoint::VerifyTurn
+            | Endpoint::ImportTurn
+            | Endpoint::CommitSeed
+            | Endpoint::RevealSeed => Method::POST,
+            _ => Method::GET,
+        }
+    }
+
+    /// Path in OpenAPI form, with parameters written as `{name}`
+    pub fn path(self) -> &'static str {
+        match self {
+            Endpoint::GetGame => "/api/game",
+            Endpoint::NewGame => "/api/game/new",
+            Endpoint::AdvancePhase => "/api/game/advance-phase",
+            Endpoint::MoveUnit => "/api/units/move",
+            Endpoint::ValidMoves => "/api/units/{unit_id}/valid-moves",
+            Endpoint::DeclareBattle => "/api/battle/declare",
+            Endpoint::ResolveBattle => "/api/battle/resolve",
+            Endpoint::PreviewBattle => "/api/battle/preview",
+            Endpoint::AttackPlans => "/api/analysis/attack-plans",
+            Endpoint::Threats => "/api/analysis/threats",
+            Endpoint::Evaluate => "/api/analysis/evaluate",
+            Endpoint::ApplyReplacement => "/api/replacement/apply",
+            Endpoint::ReplacementHexes => "/api/replacement/valid-hexes",
+            Endpoint::ExecuteRetreat => "/api/retreat/execute",
+            Endpoint::RetreatHexes => "/api/retreat/{unit_id}/valid-hexes",
+            Endpoint::GetMap => "/api/map",
+            Endpoint::RenderSvg => "/api/render.svg",
+            Endpoint::GetUnits => "/api/units",
+            Endpoint::Events => "/api/events",
+            Endpoint::GetSeats => "/api/seats",
+            Endpoint::ClaimSeat => "/api/seats/claim",
+            Endpoint::ReleaseSeat => "/api/seats/release",
+            Endpoint::ExportTurn => "/api/turn/export",
+            Endpoint::VerifyTurn => "/api/turn/verify",
+            Endpoint::ImportTurn => "/api/turn/import",
+            Endpoint::CommitSeed => "/api/dice/commit",
+            Endpoint::RevealSeed => "/api/dice/reveal",
+            Endpoint::AuditDice => "/api/dice/audit",
+            Endpoint::OpenApi => "/api/openapi.json",
+        }
+    }
+
+    /// Path in the router's form, with parameters written as `:name`
+    fn axum_path(self) -> String {
+        self.path().replace('{', ":").replace('}', "")
+    }
+
+    fn method_router(self) -> MethodRouter<AppState> {
+        let filter = MethodFilter::try_from(self.method()).expect("Endpoints use GET or POST");
+        match self {
+            Endpoint::GetGame => on(filter, get_game_state),
+            Endpoint::NewGame => on(filter, new_game),
+            Endpoint::AdvancePhase => on(filter, advance_phase),
+            Endpoint::MoveUnit => on(filter, move_unit),
+            Endpoint::ValidMoves => on(filter, get_valid_moves),
+            Endpoint::DeclareBattle => on(filter, declare_battle),
+            Endpoint::ResolveBattle => on(filter, resolve_battle_endpoint),
+            Endpoint::PreviewBattle => on(filter, preview_battle_endpoint),
+            Endpoint::AttackPlans => on(filter, get_attack_plans),
+            Endpoint::Threats => on(filter, get_threats),
+            Endpoint::Evaluate => on(filter, evaluate),
+            Endpoint::ApplyReplacement => on(filter, apply_replacement_endpoint),
+            Endpoint::ReplacementHexes => on(filter, get_replacement_hexes),
+            Endpoint::ExecuteRetreat => on(filter, retreat_unit),
+            Endpoint::RetreatHexes => on(filter, get_retreat_hexes),
+            Endpoint::GetMap => on(filter, get_map),
+            Endpoint::RenderSvg => on(filter, render_map),
+            Endpoint::GetUnits => on(filter, get_units),
+            Endpoint::Events => on(filter, game_events),
+            Endpoint::GetSeats => on(filter, get_seats),
+            Endpoint::ClaimSeat => on(filter, claim_seat),
+            Endpoint::ReleaseSeat => on(filter, release_seat),
+            Endpoint::ExportTurn => on(filter, export_turn),
+            Endpoint::VerifyTurn => on(filter, verify_turn),
+            Endpoint::ImportTurn => on(filter, import_turn),
+            Endpoint::CommitSeed => on(filter, commit_seed),
+            Endpoint::RevealSeed => on(filter, reveal_seed),
+            Endpoint::AuditDice => on(filter, audit_dice),
+            Endpoint::OpenApi => on(filter, openapi_document),
+        }
+    }
+}
+
+/// The OpenAPI document describing this API
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "meta",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json")),
+)]
+async fn openapi_document() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
 /// Get current game state
+#[utoipa::path(
+    get,
+    path = "/api/game",
+    tag = "game",
+    responses((status = 200, body = GameResponse)),
+)]
 async fn get_game_state(
     AxumState(state): AxumState<AppState>,
 ) -> impl IntoResponse {
     let game = state.game.read().unwrap();
-    Json(ApiResponse::ok(game.clone()))
+    ApiResponse::ok(game.clone())
 }
 
 /// Create a new game
+///
+/// Play-by-email opponents pass the same agreed `seed` so their engines roll the same dice.
+/// Online opponents pass `commit_reveal` to roll from hash chains they both commit to.
+/// Pass `computer` to have the server play one side; it moves whenever that side's phase
+/// comes round, so it cannot be combined with `commit_reveal`. Pass `hotseat` to play unclaimed sides without seat tokens on one screen.
+#[utoipa::path(
+    post,
+    path = "/api/game/new",
+    tag = "game",
+    request_body = NewGameRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn new_game(
     AxumState(state): AxumState<AppState>,
-    Json(_req): Json<NewGameRequest>,
+    headers: HeaderMap,
+    Json(req): Json<NewGameRequest>,
 ) -> impl IntoResponse {
-    let mut game = state.game.write().unwrap();
-    *game = GameState::new();
-
-    // Initialize unit positions from map setup markers
-    for map_hex in &state.map.hexes {
-        if let Some(ref setup) = map_hex.setup {
-            // Find a matching unit for this setup location
-            // This is simplified; in a real game we'd have a proper setup algorithm
-            for unit_def in &state.units.units {
-                let has_this_unit = game.units.iter().any(|u| u.id == unit_def.id);
-                if !has_this_unit {
-                    let unit_side_matches = match setup {
-                        crate::map::SetupMarker::German => {
-                            unit_def.side == crate::unit::Side::German
-                        }
-                        crate::map::SetupMarker::Soviet => {
-                            unit_def.side == crate::unit::Side::Soviet
-                        }
-                    };
-
-                    if unit_side_matches {
-                        game.units.push(UnitState::new(
-                            unit_def.id.clone(),
-                            Some(map_hex.hex()),
-                            UnitStrength::Full,
-                        ));
-                        break;
-                    }
-                }
-            }
-        }
+    if let Err(e) = state.seats.lock().unwrap().authorize_player(seat_token(&headers)) {
+        return ApiResponse::error(e);
     }
 
-    // Initialize city control based on unit positions
-    for map_hex in &state.map.hexes {
-        if let Some(ref city) = map_hex.city {
-            // Check which side has units here
-            for unit_state in &game.units {
-                if let Some(unit_hex) = unit_state.hex() {
-                    if unit_hex == map_hex.hex() {
-                        if let Some(unit_def) = state.units.get(&unit_state.id) {
-                            game.city_control.insert(city.name.clone(), unit_def.side);
-                            break;
-                        }
-                    }
-                }
-            }
+    if req.computer.is_some() && req.commit_reveal {
+        return ApiResponse::error(ApiError::bad_request(
+            "The computer cannot commit to a hash chain; give a seed or leave commit_reveal off",
+        ));
+    }
+        let next = match (req.seed, req.commit_reveal) {
+        (Some(_), true) => {
+            return ApiResponse::error(ApiError::bad_request(
+                "Give either a seed or commit_reveal, not both",
+            ))
         }
+        (Some(ref seed), false) => GameState::with_seed(seed),
+        (None, true) => GameState::with_commit_reveal(),
+        (None, false) => GameState::new(),
+    };
+    if let Err(e) = state
+        .seats
+        .lock()
+        .unwrap()
+        .set_computer(req.computer.as_ref().map(|c| c.side))
+    {
+        return ApiResponse::error(e);
+    }
+    state.seats.lock().unwrap().set_hotseat(req.hotseat);
+    *state.computer.lock().unwrap() = req.computer.map(|c| c.player.player());
+    {
+        let mut game = state.game.write().unwrap();
+        *game = next;
+        game.place_initial_units(&state.units, &state.map);
     }
 
-    Json(ApiResponse::ok(game.clone()))
+    state.publish(GameEvent::GameStarted);
+    match state.play_computer().await {
+        Ok(()) => ApiResponse::ok(state.game.read().unwrap().clone()),
+        Err(e) => ApiResponse::error(e),
+    }
 }
 
 /// Advance to next phase
+#[utoipa::path(
+    post,
+    path = "/api/game/advance-phase",
+    tag = "game",
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn advance_phase(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    let mut game = state.game.write().unwrap();
-    game.advance_phase();
-    Json(ApiResponse::ok(game.clone()))
+    {
+        let mut game = state.game.write().unwrap();
+        if let Err(e) = state.authorize(&headers, game.active_player()) {
+            return ApiResponse::error(e);
+        }
+        if let Err(e) = state.perform(&mut game, Action::AdvancePhase) {
+            return ApiResponse::error(e);
+        }
+    }
+
+    match state.play_computer().await {
+        Ok(()) => ApiResponse::ok(state.game.read().unwrap().clone()),
+        Err(e) => ApiResponse::error(e),
+    }
 }
 
 /// Move a unit
+#[utoipa::path(
+    post,
+    path = "/api/units/move",
+    tag = "movement",
+    request_body = MoveRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn move_unit(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
     Json(req): Json<MoveRequest>,
 ) -> impl IntoResponse {
     let mut game = state.game.write().unwrap();
-    let destination = Hex::new(req.to_q, req.to_r);
-
-    // Validate the move
-    match validate_move(&req.unit_id, &destination, &game, &state.units, &state.map) {
-        Ok(_) => {
-            // Execute the move
-            if let Some(unit) = game.get_unit_mut(&req.unit_id) {
-                unit.move_to(destination);
-                game.mark_moved(&req.unit_id);
-
-                // Update city control if moved into a city
-                for map_hex in &state.map.hexes {
-                    if map_hex.hex() == destination {
-                        if let Some(ref city) = map_hex.city {
-                            if let Some(unit_def) = state.units.get(&req.unit_id) {
-                                game.update_city_control(&city.name, unit_def.side);
-                            }
-                        }
-                    }
-                }
+    if let Err(e) = state.authorize(&headers, game.active_player()) {
+        return ApiResponse::error(e);
+    }
+    let destination = match request_hex(req.to_q, req.to_r, req.to_original.as_deref()) {
+        Ok(Some(hex)) => hex,
+        Ok(None) => return ApiResponse::error("Missing destination hex".to_string()),
+        Err(e) => return ApiResponse::error(e),
+    };
 
-                Json(ApiResponse::ok(game.clone()))
-            } else {
-                Json(ApiResponse::error(format!("Unit {} not found", req.unit_id)))
-            }
-        }
-        Err(e) => Json(ApiResponse::error(e)),
+    let action = Action::Move {
+        unit_id: req.unit_id,
+        to: destination,
+    };
+    match state.perform(&mut game, action) {
+        Ok(_) => ApiResponse::ok(game.clone()),
+        Err(e) => ApiResponse::error(e),
     }
 }
 
 /// Get valid moves for a unit
+#[utoipa::path(
+    get,
+    path = "/api/units/{unit_id}/valid-moves",
+    tag = "movement",
+    params(("unit_id" = String, Path, description = "Unit ID")),
+    responses((status = 200, body = HexesResponse)),
+)]
 async fn get_valid_moves(
     AxumState(state): AxumState<AppState>,
     axum::extract::Path(unit_id): axum::extract::Path<String>,
@@ -233,104 +827,291 @@ async fn get_valid_moves(
     let game = state.game.read().unwrap();
 
     match find_valid_moves(&unit_id, &game, &state.units, &state.map) {
-        Ok(hexes) => Json(ApiResponse::ok(hexes)),
-        Err(e) => Json(ApiResponse::error(e)),
+        Ok(hexes) => ApiResponse::ok(hexes),
+        Err(e) => ApiResponse::error(e),
     }
 }
 
 /// Declare a battle
+#[utoipa::path(
+    post,
+    path = "/api/battle/declare",
+    tag = "combat",
+    request_body = DeclareBattleRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn declare_battle(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
     Json(req): Json<DeclareBattleRequest>,
 ) -> impl IntoResponse {
     let mut game = state.game.write().unwrap();
+    if let Err(e) = state.authorize(&headers, game.active_player()) {
+        return ApiResponse::error(e);
+    }
 
-    let battle = BattleDeclaration {
-        attackers: req.attacker_ids,
-        defender: req.defender_id,
-        resolved: false,
+    let action = Action::DeclareBattle {
+        attacker_ids: req.attacker_ids,
+        defender_id: req.defender_id,
     };
-
-    game.pending_battles.push(battle);
-    Json(ApiResponse::ok(game.clone()))
+    match state.perform(&mut game, action) {
+        Ok(_) => ApiResponse::ok(game.clone()),
+        Err(e) => ApiResponse::error(e),
+    }
 }
 
 /// Resolve a pending battle
+#[utoipa::path(
+    post,
+    path = "/api/battle/resolve",
+    tag = "combat",
+    request_body = ResolveBattleRequest,
+    responses((status = 200, body = ResolutionResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn resolve_battle_endpoint(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
     Json(req): Json<ResolveBattleRequest>,
 ) -> impl IntoResponse {
     let mut game = state.game.write().unwrap();
+    if let Err(e) = state.authorize(&headers, game.active_player()) {
+        return ApiResponse::error(e);
+    }
 
-    if req.battle_index >= game.pending_battles.len() {
-        return Json(ApiResponse::error("Invalid battle index".to_string()));
+    let action = Action::ResolveBattle {
+        battle_index: req.battle_index,
+        loss: req.loss,
+    };
+    match state.perform(&mut game, action) {
+        Ok(outcome) => match outcome.resolution {
+            Some(resolution) => {
+                match state.computer_retreat(&mut game, req.battle_index, &resolution.result) {
+                    Ok(()) => ApiResponse::ok(resolution),
+                    Err(e) => ApiResponse::error(e),
+                }
+            }
+            None => ApiResponse::error(ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "Battle was not resolved",
+            )),
+        },
+        Err(e) => ApiResponse::error(e),
     }
+}
 
-    let battle = game.pending_battles[req.battle_index].clone();
+/// Preview a battle without declaring it or rolling the die
+///
+/// Returns the odds and terrain shifts `resolve` would use, the chance of each combat
+/// result and the expected step losses on both sides.
+#[utoipa::path(
+    post,
+    path = "/api/battle/preview",
+    tag = "combat",
+    request_body = DeclareBattleRequest,
+    responses((status = 200, body = PreviewResponse)),
+)]
+async fn preview_battle_endpoint(
+    AxumState(state): AxumState<AppState>,
+    Json(req): Json<DeclareBattleRequest>,
+) -> impl IntoResponse {
+    let game = state.game.read().unwrap();
 
-    match resolve_battle(&battle, &mut game, &state.units, &state.map) {
-        Ok(resolution) => {
-            // Apply the result
-            if let Err(e) = apply_combat_result(&resolution.result, &battle, &mut game, &state.units) {
-                return Json(ApiResponse::error(e));
-            }
+    match preview_battle(
+        &req.attacker_ids,
+        &req.defender_id,
+        &game,
+        &state.units,
+        &state.map,
+    ) {
+        Ok(preview) => ApiResponse::ok(preview),
+        Err(e) => ApiResponse::error(e),
+    }
+}
 
-            // Mark battle as resolved
-            game.pending_battles[req.battle_index].resolved = true;
+/// Rank the ways the active side could split its units among the battles open to it
+///
+/// Plans are scored by defender steps destroyed and retreats forced, less attacker steps
+/// lost, all weighted by the Combat Results Table odds.
+#[utoipa::path(
+    get,
+    path = "/api/analysis/attack-plans",
+    tag = "analysis",
+    params(AttackPlanQuery),
+    responses((status = 200, body = AttackPlansResponse)),
+)]
+async fn get_attack_plans(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<AttackPlanQuery>,
+) -> impl IntoResponse {
+    let game = state.game.read().unwrap();
+    let limit = query.limit.unwrap_or(DEFAULT_PLAN_LIMIT);
 
-            Json(ApiResponse::ok(resolution))
-        }
-        Err(e) => Json(ApiResponse::error(e)),
+    match plan_attacks(&game, &state.units, &state.map, limit) {
+        Ok(plans) => ApiResponse::ok(plans),
+        Err(e) => ApiResponse::error(e),
     }
 }
 
+/// What the enemy could do to a side in its next phase
+///
+/// Lists the units it could attack with the best odds it could reach, units that could be
+/// cut off from their map edge, cities and Moscow approaches likely to fall, and empty
+/// hexes behind the line it could slip into.
+#[utoipa::path(
+    get,
+    path = "/api/analysis/threats",
+    tag = "analysis",
+    params(ThreatQuery),
+    responses((status = 200, body = ThreatsResponse)),
+)]
+async fn get_threats(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<ThreatQuery>,
+) -> impl IntoResponse {
+    let game = state.game.read().unwrap();
+    let side = query.side.unwrap_or_else(|| game.active_player());
+
+    ApiResponse::ok(analyze_threats(side, &game, &state.units, &state.map))
+}
+
+/// Who is winning: the position's score, each side's chance of winning and what the
+/// score is made of
+///
+/// The chances are read from the score unless `playouts` asks for random games to be
+/// played out from the position, which leaves the game's own dice untouched. At most
+/// `MAX_PLAYOUTS` (100) are played; larger requests are cut down to that.
+#[utoipa::path(
+    get,
+    path = "/api/analysis/evaluate",
+    tag = "analysis",
+    params(EvaluateQuery),
+    responses((status = 200, body = AssessmentResponse)),
+)]
+async fn evaluate(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<EvaluateQuery>,
+) -> impl IntoResponse {
+    let game = state.game.read().unwrap().clone();
+    let playouts = query.playouts.unwrap_or(0);
+    let seed = query.seed.unwrap_or(0);
+
+    // Playouts are plain computation, so keep them off the async workers
+    let assessment = tokio::task::spawn_blocking(move || {
+        assess_position(&game, &state.units, &state.map, playouts, seed)
+    })
+    .await
+    .expect("The position assessment panicked");
+    ApiResponse::ok(assessment)
+}
+
 /// Apply a replacement
+#[utoipa::path(
+    post,
+    path = "/api/replacement/apply",
+    tag = "replacements",
+    request_body = ReplacementRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn apply_replacement_endpoint(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
     Json(req): Json<ReplacementRequest>,
 ) -> impl IntoResponse {
     let mut game = state.game.write().unwrap();
-
-    let hex = req.hex_q.zip(req.hex_r).map(|(q, r)| Hex::new(q, r));
-
-    // Validate if hex is provided
-    if let Some(ref h) = hex {
-        if let Err(e) = validate_replacement_placement(&req.unit_id, h, &game, &state.units, &state.map) {
-            return Json(ApiResponse::error(e));
-        }
+    if let Err(e) = state.authorize(&headers, game.active_player()) {
+        return ApiResponse::error(e);
     }
 
-    match apply_replacement(&req.unit_id, hex.as_ref(), &mut game, &state.units) {
-        Ok(_) => Json(ApiResponse::ok(game.clone())),
-        Err(e) => Json(ApiResponse::error(e)),
+    let hex = match request_hex(req.hex_q, req.hex_r, req.hex_original.as_deref()) {
+        Ok(hex) => hex,
+        Err(e) => return ApiResponse::error(e),
+    };
+
+    let action = Action::Replacement {
+        unit_id: req.unit_id,
+        hex,
+    };
+    match state.perform(&mut game, action) {
+        Ok(_) => ApiResponse::ok(game.clone()),
+        Err(e) => ApiResponse::error(e),
     }
 }
 
 /// Get valid replacement hexes
+#[utoipa::path(
+    get,
+    path = "/api/replacement/valid-hexes",
+    tag = "replacements",
+    responses((status = 200, body = HexesResponse)),
+)]
 async fn get_replacement_hexes(
     AxumState(state): AxumState<AppState>,
 ) -> impl IntoResponse {
     let game = state.game.read().unwrap();
     let side = game.active_player();
     let hexes = get_valid_replacement_hexes(side, &game, &state.units, &state.map);
-    Json(ApiResponse::ok(hexes))
+    ApiResponse::ok(hexes)
 }
 
 /// Execute a retreat
+#[utoipa::path(
+    post,
+    path = "/api/retreat/execute",
+    tag = "retreats",
+    request_body = RetreatRequest,
+    responses((status = 200, body = RetreatResponse)),
+    security(("seat_token" = []), ()),
+)]
 async fn retreat_unit(
     AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
     Json(req): Json<RetreatRequest>,
 ) -> impl IntoResponse {
     let mut game = state.game.write().unwrap();
-    let to_hex = Hex::new(req.to_q, req.to_r);
 
-    match execute_retreat(&req.unit_id, &to_hex, &mut game, &state.units, &state.map) {
-        Ok(result) => Json(ApiResponse::ok(result)),
-        Err(e) => Json(ApiResponse::error(e)),
+    // Retreats are carried out by the owner of the retreating unit
+    let side = match state.units.get(&req.unit_id) {
+        Some(def) => def.side,
+        None => return ApiResponse::error(RuleError::UnitNotFound(req.unit_id)),
+    };
+    if let Err(e) = state.authorize(&headers, side) {
+        return ApiResponse::error(e);
+    }
+    let to_hex = match request_hex(req.to_q, req.to_r, req.to_original.as_deref()) {
+        Ok(Some(hex)) => hex,
+        Ok(None) => return ApiResponse::error("Missing retreat hex".to_string()),
+        Err(e) => return ApiResponse::error(e),
+    };
+
+    let action = Action::Retreat {
+        unit_id: req.unit_id,
+        to: to_hex,
+    };
+    match state.perform(&mut game, action) {
+        Ok(outcome) => match outcome.retreat {
+            Some(result) => ApiResponse::ok(result),
+            None => ApiResponse::error(ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "Retreat was not carried out",
+            )),
+        },
+        Err(e) => ApiResponse::error(e),
     }
 }
 
 /// Get valid retreat hexes for a unit
+#[utoipa::path(
+    get,
+    path = "/api/retreat/{unit_id}/valid-hexes",
+    tag = "retreats",
+    params(("unit_id" = String, Path, description = "Unit ID")),
+    responses((status = 200, body = HexesResponse)),
+)]
 async fn get_retreat_hexes(
     AxumState(state): AxumState<AppState>,
     axum::extract::Path(unit_id): axum::extract::Path<String>,
@@ -340,23 +1121,422 @@ async fn get_retreat_hexes(
     // Get unit position
     let from_hex = match game.get_unit(&unit_id).and_then(|u| u.hex()) {
         Some(hex) => hex,
-        None => return Json(ApiResponse::error(format!("Unit {} has no position", unit_id))),
+        None => return ApiResponse::error(RuleError::NotOnMap(unit_id)),
     };
 
     let hexes = find_valid_retreat_hexes(&unit_id, &from_hex, &game, &state.units, &state.map);
-    Json(ApiResponse::ok(hexes))
+    ApiResponse::ok(hexes)
 }
 
 /// Get map data
+#[utoipa::path(
+    get,
+    path = "/api/map",
+    tag = "data",
+    responses((status = 200, body = MapResponse)),
+)]
 async fn get_map(
     AxumState(state): AxumState<AppState>,
 ) -> impl IntoResponse {
-    Json(ApiResponse::ok(state.map.as_ref().clone()))
+    ApiResponse::ok(state.map.as_ref().clone())
+}
+
+/// Render the map and game state as SVG
+#[utoipa::path(
+    get,
+    path = "/api/render.svg",
+    tag = "data",
+    params(RenderQuery),
+    responses((status = 200, content_type = "image/svg+xml", body = String)),
+)]
+async fn render_map(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<RenderQuery>,
+) -> Response {
+    let game = state.game.read().unwrap();
+    let defaults = RenderOptions::default();
+
+    let hex_size = query.size.unwrap_or(defaults.hex_size);
+    if !(5.0..=200.0).contains(&hex_size) {
+        return ApiResponse::<()>::error("Size must be between 5 and 200".to_string())
+            .into_response();
+    }
+
+    let mut highlight = match query.highlight.as_deref().map(parse_hex_list) {
+        Some(Ok(hexes)) => hexes,
+        Some(Err(e)) => return ApiResponse::<()>::error(e).into_response(),
+        None => Vec::new(),
+    };
+
+    if let Some(ref unit_id) = query.moves {
+        match find_valid_moves(unit_id, &game, &state.units, &state.map) {
+            Ok(hexes) => highlight.extend(hexes),
+            Err(e) => return ApiResponse::<()>::error(e).into_response(),
+        }
+    }
+
+    let options = RenderOptions {
+        hex_size,
+        labels: query.coords.unwrap_or(defaults.labels),
+        zoc: query.zoc,
+        highlight,
+        title: query.title.unwrap_or(defaults.title),
+    };
+
+    let svg = render_svg(&state.map, &state.units, &game, &options);
+    ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
 }
 
 /// Get unit definitions
+#[utoipa::path(
+    get,
+    path = "/api/units",
+    tag = "data",
+    responses((status = 200, body = UnitsResponse)),
+)]
 async fn get_units(
     AxumState(state): AxumState<AppState>,
 ) -> impl IntoResponse {
-    Json(ApiResponse::ok(state.units.as_ref().clone()))
+    ApiResponse::ok(state.units.as_ref().clone())
+}
+
+/// Export the turn file of the side that acted most recently
+#[utoipa::path(
+    get,
+    path = "/api/turn/export",
+    tag = "turns",
+    responses((status = 200, body = TurnFileResponse)),
+)]
+async fn export_turn(
+    AxumState(state): AxumState<AppState>,
+) -> ApiResponse<TurnFile> {
+    match state.turns.lock().unwrap().current() {
+        Some(turn) => ApiResponse::ok(turn.clone()),
+        None => ApiResponse::error(RuleError::NoTurnRecorded),
+    }
+}
+
+/// Summary of a turn file that replays cleanly
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TurnCheck {
+    pub side: Side,
+    pub actions: usize,
+    pub die_rolls: Vec<i32>,
+}
+
+/// Check an opponent's turn file against the engine without applying it
+#[utoipa::path(
+    post,
+    path = "/api/turn/verify",
+    tag = "turns",
+    request_body = TurnFile,
+    responses((status = 200, body = TurnCheckResponse)),
+)]
+async fn verify_turn(
+    AxumState(state): AxumState<AppState>,
+    Json(turn): Json<TurnFile>,
+) -> ApiResponse<TurnCheck> {
+    let game = state.game.read().unwrap();
+    match replay_turn(&turn, &game, &state.units, &state.map) {
+        Ok(_) => ApiResponse::ok(TurnCheck {
+            side: turn.side,
+            actions: turn.actions.len(),
+            die_rolls: turn.actions.iter().filter_map(|a| a.die_roll).collect(),
+        }),
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Check an opponent's turn file and apply it to the game
+///
+/// Once another player holds the seat of the side that played the turn, only that seat's
+/// token may import it.
+#[utoipa::path(
+    post,
+    path = "/api/turn/import",
+    tag = "turns",
+    request_body = TurnFile,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = []), ()),
+)]
+async fn import_turn(
+    AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
+    Json(turn): Json<TurnFile>,
+) -> ApiResponse<GameState> {
+    let authorized = state
+        .seats
+        .lock()
+        .unwrap()
+        .authorize_import(seat_token(&headers), turn.side);
+    if let Err(e) = authorized {
+        return ApiResponse::error(e);
+    }
+
+    let mut game = state.game.write().unwrap();
+    match replay_turn(&turn, &game, &state.units, &state.map) {
+        Ok(replayed) => {
+            *game = replayed.state;
+            for event in replayed.events {
+                state.publish(event);
+            }
+            ApiResponse::ok(game.clone())
+        }
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Side of the seat held by the caller's token
+fn seated_side(state: &AppState, headers: &HeaderMap) -> Result<Side, RuleError> {
+    match seat_token(headers).and_then(|t| state.seats.lock().unwrap().seat_for(t)) {
+        Some(Seat::German) => Ok(Side::German),
+        Some(Seat::Soviet) => Ok(Side::Soviet),
+        _ => Err(RuleError::NotSeated),
+    }
+}
+
+/// Commit the caller's side to the end of its hash chain
+#[utoipa::path(
+    post,
+    path = "/api/dice/commit",
+    tag = "dice",
+    request_body = CommitSeedRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = [])),
+)]
+async fn commit_seed(
+    AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CommitSeedRequest>,
+) -> ApiResponse<GameState> {
+    let side = match seated_side(&state, &headers) {
+        Ok(side) => side,
+        Err(e) => return ApiResponse::error(e),
+    };
+
+    let mut game = state.game.write().unwrap();
+    match game.dice.commit(side, &req.commitment) {
+        Ok(()) => ApiResponse::ok(game.clone()),
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Reveal the caller's next hash chain link for the roll of a pending battle
+#[utoipa::path(
+    post,
+    path = "/api/dice/reveal",
+    tag = "dice",
+    request_body = RevealSeedRequest,
+    responses((status = 200, body = GameResponse)),
+    security(("seat_token" = [])),
+)]
+async fn reveal_seed(
+    AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RevealSeedRequest>,
+) -> ApiResponse<GameState> {
+    let side = match seated_side(&state, &headers) {
+        Ok(side) => side,
+        Err(e) => return ApiResponse::error(e),
+    };
+
+    let mut game = state.game.write().unwrap();
+    let needs_roll = game
+        .pending_battles
+        .iter()
+        .filter(|b| !b.resolved && b.defender == req.defender)
+        .any(|b| needs_die_roll(b, &game, &state.units, &state.map).unwrap_or(false));
+    if !needs_roll {
+        return ApiResponse::error(RuleError::RollNotNeeded(req.defender));
+    }
+    match game.dice.reveal(side, &req.link, &req.defender) {
+        Ok(()) => ApiResponse::ok(game.clone()),
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Disclose the dice seeds and every roll once the game is over
+#[utoipa::path(
+    get,
+    path = "/api/dice/audit",
+    tag = "dice",
+    responses((status = 200, body = DiceAuditResponse)),
+)]
+async fn audit_dice(
+    AxumState(state): AxumState<AppState>,
+) -> ApiResponse<DiceAudit> {
+    let game = state.game.read().unwrap();
+    if !game.is_over() {
+        return ApiResponse::error(RuleError::GameNotOver);
+    }
+    ApiResponse::ok(game.dice.audit())
+}
+
+/// Show which seats are taken
+#[utoipa::path(
+    get,
+    path = "/api/seats",
+    tag = "seats",
+    responses((status = 200, body = SeatsResponse)),
+)]
+async fn get_seats(
+    AxumState(state): AxumState<AppState>,
+) -> ApiResponse<SeatSummary> {
+    ApiResponse::ok(state.seats.lock().unwrap().summary())
+}
+
+/// Claim a seat and receive its token
+#[utoipa::path(
+    post,
+    path = "/api/seats/claim",
+    tag = "seats",
+    request_body = ClaimSeatRequest,
+    responses((status = 200, body = SeatClaimResponse)),
+)]
+async fn claim_seat(
+    AxumState(state): AxumState<AppState>,
+    Json(req): Json<ClaimSeatRequest>,
+) -> ApiResponse<SeatClaim> {
+    match state.seats.lock().unwrap().claim(req.seat) {
+        Ok(token) => ApiResponse::ok(SeatClaim {
+            seat: req.seat,
+            token,
+        }),
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Give up the seat held by the caller's token
+#[utoipa::path(
+    post,
+    path = "/api/seats/release",
+    tag = "seats",
+    responses((status = 200, body = SeatResponse)),
+    security(("seat_token" = [])),
+)]
+async fn release_seat(
+    AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
+) -> ApiResponse<Seat> {
+    let token = match seat_token(&headers) {
+        Some(token) => token,
+        None => {
+            return ApiResponse::error(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "missing_seat_token",
+                "Missing seat token",
+            ))
+        }
+    };
+    match state.seats.lock().unwrap().release(token) {
+        Ok(seat) => ApiResponse::ok(seat),
+        Err(e) => ApiResponse::error(e),
+    }
+}
+
+/// Stream game events as Server-Sent Events
+///
+/// Clients catch up from `?since=N` or the `Last-Event-ID` header sent on reconnect.
+/// A subscriber that falls too far behind is disconnected and catches up on reconnect.
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    params(EventsQuery),
+    responses((status = 200, content_type = "text/event-stream", body = EventRecord)),
+)]
+async fn game_events(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let since = query
+        .since
+        .or_else(|| {
+            headers
+                .get("last-event-id")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+        })
+        .unwrap_or(0);
+
+    let (backlog, receiver) = {
+        let events = state.events.lock().unwrap();
+        (events.since(since), events.subscribe())
+    };
+    let last_sent = backlog.last().map_or(since, |record| record.id);
+
+    let live = BroadcastStream::new(receiver)
+        .take_while(Result::is_ok)
+        .filter_map(Result::ok)
+        .filter(move |record| record.id > last_sent);
+
+    let stream = tokio_stream::iter(backlog)
+        .chain(live)
+        .map(|record| sse_event(&record));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Encode an event record with its ID and type so clients can resume and dispatch
+fn sse_event(record: &EventRecord) -> Result<Event, axum::Error> {
+    Event::default()
+        .id(record.id.to_string())
+        .event(record.event.name())
+        .json_data(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hex() {
+        assert_eq!(request_hex(Some(5), Some(3), None), Ok(Some(Hex::new(5, 3))));
+        assert_eq!(request_hex(None, None, Some("0505")), Ok(Some(Hex::new(5, 3))));
+        assert_eq!(request_hex(None, None, None), Ok(None));
+        assert!(request_hex(None, None, Some("55")).is_err());
+        assert!(request_hex(Some(5), Some(3), Some("0505")).is_err());
+    }
+
+    #[test]
+    fn test_seat_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(seat_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer abc123".parse().unwrap());
+        assert_eq!(seat_token(&headers), Some("abc123"));
+
+        headers.insert(header::AUTHORIZATION, "Basic abc123".parse().unwrap());
+        assert_eq!(seat_token(&headers), None);
+    }
+
+    #[test]
+    fn test_parse_hex_list() {
+        assert_eq!(
+            parse_hex_list("5,3; 0505;;-1, 2"),
+            Ok(vec![Hex::new(5, 3), Hex::new(5, 3), Hex::new(-1, 2)])
+        );
+        assert!(parse_hex_list("5").is_err());
+        assert!(parse_hex_list("a,b").is_err());
+    }
+
+    #[test]
+    fn test_error_response() {
+        let response = ApiResponse::<()>::error(RuleError::NotYourTurn);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["code"], "not_your_turn");
+        assert_eq!(json["error"], "Not your turn");
+        assert_eq!(response.into_response().status(), StatusCode::CONFLICT);
+
+        let response = ApiResponse::<()>::error(RuleError::Unreachable(Hex::new(1, 2)));
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+        let response = ApiResponse::<()>::error("Invalid hex 55".to_string());
+        assert_eq!(response.code.as_deref(), Some("bad_request"));
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+        let json = serde_json::to_value(ApiResponse::ok(1)).unwrap();
+        assert!(json.get("code").is_none());
+    }
 }
@@ -0,0 +1,478 @@
+use crate::action::{apply_action, Action};
+use crate::ai::{play_game_with, Player, PlayerKind};
+use crate::bot::ExternalBot;
+use crate::error::RuleError;
+use crate::events::GameEvent;
+use crate::game_state::{GameState, Phase};
+use crate::map::Map;
+use crate::mcts::MctsConfig;
+use crate::unit::{Side, UnitStrength, Units};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// z for a 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// A player as named on the `bfm-arena` command line
+///
+/// `heuristic`, `mcts` with optional settings such as
+/// `mcts:iterations=500,time=100,depth=20,seed=7,exploration=0.7`, or `bot:COMMAND` for
+/// an external program speaking the bot protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerSpec {
+    Computer(PlayerKind),
+    Bot(String),
+}
+
+impl PlayerSpec {
+    /// A fresh player for one game; search seeds are offset by the game number and bots
+    /// are started anew
+    pub fn player(
+        &self,
+        game: usize,
+        side: Side,
+        units: &Units,
+        map: &Map,
+    ) -> Result<Box<dyn Player>, ArenaError> {
+        Ok(match self {
+            PlayerSpec::Computer(PlayerKind::Mcts(config)) => PlayerKind::Mcts(MctsConfig {
+                seed: config.seed.wrapping_add(game as u64),
+                ..config.clone()
+            })
+            .player(),
+            PlayerSpec::Computer(kind) => kind.player(),
+            PlayerSpec::Bot(command) => Box::new(
+                ExternalBot::spawn(command, side, units, map)
+                    .map_err(|e| ArenaError::Bot(command.clone(), e))?,
+            ),
+        })
+    }
+}
+
+/// Why a tournament stopped early
+#[derive(Debug)]
+pub enum ArenaError {
+    /// A game could not be played to the end
+    Rules(RuleError),
+    /// A bot could not be started
+    Bot(String, io::Error),
+}
+
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArenaError::Rules(e) => write!(f, "Game aborted: {}", e),
+            ArenaError::Bot(command, e) => write!(f, "Bot \"{}\" failed to start: {}", command, e),
+        }
+    }
+}
+
+impl std::error::Error for ArenaError {}
+
+impl From<RuleError> for ArenaError {
+    fn from(err: RuleError) -> Self {
+        ArenaError::Rules(err)
+    }
+}
+
+impl FromStr for PlayerSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (name, settings) = spec.split_once(':').unwrap_or((spec, ""));
+        match name {
+            "bot" if !settings.trim().is_empty() => Ok(PlayerSpec::Bot(settings.to_string())),
+            "heuristic" if settings.is_empty() => Ok(PlayerSpec::Computer(PlayerKind::Heuristic)),
+            "mcts" => {
+                let mut config = MctsConfig::default();
+                for setting in settings.split(',').filter(|s| !s.is_empty()) {
+                    let (key, value) = setting
+                        .split_once('=')
+                        .ok_or_else(|| format!("Expected key=value, got \"{}\"", setting))?;
+                    let bad = || format!("Bad value for {}: \"{}\"", key, value);
+                    match key {
+                        "iterations" => config.iterations = value.parse().map_err(|_| bad())?,
+                        "time" => config.time_limit_ms = Some(value.parse().map_err(|_| bad())?),
+                        "depth" => config.rollout_depth = value.parse().map_err(|_| bad())?,
+                        "seed" => config.seed = value.parse().map_err(|_| bad())?,
+                        "exploration" => config.exploration = value.parse().map_err(|_| bad())?,
+                        _ => return Err(format!("Unknown mcts setting \"{}\"", key)),
+                    }
+                }
+                Ok(PlayerSpec::Computer(PlayerKind::Mcts(config)))
+            }
+            _ => Err(format!("Unknown player \"{}\"", spec)),
+        }
+    }
+}
+
+/// A player taking part in a tournament, under the name it is reported by
+#[derive(Debug, Clone)]
+pub struct Contestant {
+    pub name: String,
+    pub spec: PlayerSpec,
+}
+
+/// One action of a game log, with the events it produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub turn: i32,
+    pub phase: Phase,
+    #[serde(flatten)]
+    pub action: Action,
+    pub events: Vec<GameEvent>,
+}
+
+/// How one game went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub game: usize,
+    /// Dice seed the game was played with
+    pub seed: String,
+    pub german: String,
+    pub soviet: String,
+    /// `None` if the game stopped before the last turn
+    pub winner: Option<Side>,
+    /// Turn the Germans first took Moscow, if they did
+    pub moscow_captured_turn: Option<i32>,
+    pub german_steps_lost: i32,
+    pub soviet_steps_lost: i32,
+    pub actions: usize,
+}
+
+/// Play one game to the end, logging every action
+///
+/// The record's `game`, `seed` and player names are left for the caller to fill in.
+pub fn play_logged_game<'a>(
+    german: &mut (dyn Player + 'a),
+    soviet: &mut (dyn Player + 'a),
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<(GameRecord, Vec<LogEntry>), RuleError> {
+    let mut log = Vec::new();
+    let mut record = GameRecord {
+        game: 0,
+        seed: String::new(),
+        german: String::new(),
+        soviet: String::new(),
+        winner: None,
+        moscow_captured_turn: None,
+        german_steps_lost: 0,
+        soviet_steps_lost: 0,
+        actions: 0,
+    };
+
+    let perform = |state: &mut GameState, action: Action| {
+        let (turn, phase) = (state.turn, state.phase.clone());
+        let before = [
+            steps(state, units, Side::German),
+            steps(state, units, Side::Soviet),
+        ];
+        let outcome = apply_action(&action, state, units, map)?;
+
+        record.german_steps_lost += (before[0] - steps(state, units, Side::German)).max(0);
+        record.soviet_steps_lost += (before[1] - steps(state, units, Side::Soviet)).max(0);
+        if record.moscow_captured_turn.is_none()
+            && state.get_city_control("Moscow") == Some(Side::German)
+        {
+            record.moscow_captured_turn = Some(turn);
+        }
+        log.push(LogEntry {
+            turn,
+            phase,
+            action,
+            events: outcome.events.clone(),
+        });
+        Ok(outcome)
+    };
+    record.actions = play_game_with(german, soviet, state, units, map, perform)?;
+    record.winner = state.check_victory();
+    Ok((record, log))
+}
+
+/// Steps a side has on the map or in the replacement pool: two per full unit, one per half
+fn steps(state: &GameState, units: &Units, side: Side) -> i32 {
+    state
+        .units
+        .iter()
+        .filter(|u| units.get(&u.id).is_some_and(|def| def.side == side))
+        .map(|u| match u.strength {
+            UnitStrength::Full => 2,
+            UnitStrength::Half => 1,
+            UnitStrength::Eliminated => 0,
+        })
+        .sum()
+}
+
+/// Play `games` games between two contestants, swapping sides every game
+///
+/// The first contestant takes the Germans in even-numbered games. Game `n` rolls its
+/// dice from the seed `"{seed}-{n}"`. `on_game` sees each game as it finishes.
+pub fn run_arena(
+    contestants: &[Contestant; 2],
+    games: usize,
+    seed: &str,
+    units: &Units,
+    map: &Map,
+    mut on_game: impl FnMut(&GameRecord, &[LogEntry]),
+) -> Result<Vec<GameRecord>, ArenaError> {
+    let mut records = Vec::with_capacity(games);
+    for game in 0..games {
+        let (german, soviet) = if game % 2 == 0 {
+            (&contestants[0], &contestants[1])
+        } else {
+            (&contestants[1], &contestants[0])
+        };
+        let game_seed = format!("{}-{}", seed, game);
+        let mut state = GameState::with_seed(&game_seed);
+        state.place_initial_units(units, map);
+
+        let mut german_player = german.spec.player(game, Side::German, units, map)?;
+        let mut soviet_player = soviet.spec.player(game, Side::Soviet, units, map)?;
+        let (mut record, log) = play_logged_game(
+            german_player.as_mut(),
+            soviet_player.as_mut(),
+            &mut state,
+            units,
+            map,
+        )?;
+        record.game = game;
+        record.seed = game_seed;
+        record.german = german.name.clone();
+        record.soviet = soviet.name.clone();
+
+        on_game(&record, &log);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// A proportion with its 95% Wilson score interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rate {
+    pub rate: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Rate {
+    pub fn wilson(successes: usize, trials: usize) -> Self {
+        if trials == 0 {
+            return Rate {
+                rate: 0.0,
+                low: 0.0,
+                high: 1.0,
+            };
+        }
+        let n = trials as f64;
+        let p = successes as f64 / n;
+        let z2 = Z_95 * Z_95;
+        let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        Rate {
+            rate: p,
+            low: (centre - margin).max(0.0),
+            high: (centre + margin).min(1.0),
+        }
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}% (95% CI {:.1}%–{:.1}%)",
+            self.rate * 100.0,
+            self.low * 100.0,
+            self.high * 100.0
+        )
+    }
+}
+
+/// How one contestant fared over the tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    pub win_rate: Rate,
+    pub wins_as_german: usize,
+    pub wins_as_soviet: usize,
+}
+
+/// Results of a tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArenaSummary {
+    pub games: usize,
+    pub german_wins: usize,
+    pub soviet_wins: usize,
+    /// Games that stopped without a winner
+    pub undecided: usize,
+    pub german_win_rate: Rate,
+    pub soviet_win_rate: Rate,
+    pub standings: Vec<Standing>,
+    /// Games in which the Germans took Moscow at some point
+    pub moscow_captures: usize,
+    pub average_moscow_capture_turn: Option<f64>,
+    pub average_german_steps_lost: f64,
+    pub average_soviet_steps_lost: f64,
+}
+
+/// Total up a tournament's records
+pub fn summarize(contestants: &[Contestant; 2], records: &[GameRecord]) -> ArenaSummary {
+    let games = records.len();
+    let wins = |side: Side| records.iter().filter(|r| r.winner == Some(side)).count();
+    let (german_wins, soviet_wins) = (wins(Side::German), wins(Side::Soviet));
+    let average = |total: f64, count: usize| {
+        if count == 0 {
+            0.0
+        } else {
+            total / count as f64
+        }
+    };
+
+    let standings = contestants
+        .iter()
+        .map(|contestant| {
+            let name = &contestant.name;
+            let played = records
+                .iter()
+                .filter(|r| &r.german == name || &r.soviet == name)
+                .count();
+            let wins_as = |side: Side| {
+                records
+                    .iter()
+                    .filter(|r| r.winner == Some(side))
+                    .filter(|r| match side {
+                        Side::German => &r.german == name,
+                        Side::Soviet => &r.soviet == name,
+                    })
+                    .count()
+            };
+            let (as_german, as_soviet) = (wins_as(Side::German), wins_as(Side::Soviet));
+            Standing {
+                name: name.clone(),
+                games: played,
+                wins: as_german + as_soviet,
+                win_rate: Rate::wilson(as_german + as_soviet, played),
+                wins_as_german: as_german,
+                wins_as_soviet: as_soviet,
+            }
+        })
+        .collect();
+
+    let capture_turns: Vec<i32> = records
+        .iter()
+        .filter_map(|r| r.moscow_captured_turn)
+        .collect();
+
+    ArenaSummary {
+        games,
+        german_wins,
+        soviet_wins,
+        undecided: games - german_wins - soviet_wins,
+        german_win_rate: Rate::wilson(german_wins, games),
+        soviet_win_rate: Rate::wilson(soviet_wins, games),
+        standings,
+        moscow_captures: capture_turns.len(),
+        average_moscow_capture_turn: (!capture_turns.is_empty()).then(|| {
+            capture_turns.iter().map(|&t| t as f64).sum::<f64>() / capture_turns.len() as f64
+        }),
+        average_german_steps_lost: average(
+            records.iter().map(|r| r.german_steps_lost as f64).sum(),
+            games,
+        ),
+        average_soviet_steps_lost: average(
+            records.iter().map(|r| r.soviet_steps_lost as f64).sum(),
+            games,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::bundled;
+
+    #[test]
+    fn test_player_spec() {
+        assert_eq!(
+            "heuristic".parse::<PlayerSpec>(),
+            Ok(PlayerSpec::Computer(PlayerKind::Heuristic))
+        );
+        let Ok(PlayerSpec::Computer(PlayerKind::Mcts(config))) =
+            "mcts:iterations=50,time=20,seed=3".parse::<PlayerSpec>()
+        else {
+            panic!("Expected an MCTS player");
+        };
+        assert_eq!(
+            (config.iterations, config.time_limit_ms, config.seed),
+            (50, Some(20), 3)
+        );
+        assert_eq!(config.rollout_depth, MctsConfig::default().rollout_depth);
+
+        assert!("mcts:iterations=lots".parse::<PlayerSpec>().is_err());
+        assert!("mcts:speed=3".parse::<PlayerSpec>().is_err());
+        assert!("random".parse::<PlayerSpec>().is_err());
+
+        assert_eq!(
+            "bot:python3 bot.py --fast".parse::<PlayerSpec>(),
+            Ok(PlayerSpec::Bot("python3 bot.py --fast".to_string()))
+        );
+        assert!("bot:".parse::<PlayerSpec>().is_err());
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let rate = Rate::wilson(5, 10);
+        assert_eq!(rate.rate, 0.5);
+        assert!((rate.low - 0.2366).abs() < 1e-3);
+        assert!((rate.high - 0.7634).abs() < 1e-3);
+
+        let none = Rate::wilson(0, 20);
+        assert_eq!(none.low, 0.0);
+        assert!(none.high > 0.0 && none.high < 0.2);
+        assert_eq!(Rate::wilson(0, 0).high, 1.0);
+    }
+
+    #[test]
+    fn test_run_arena() {
+        let (units, map) = bundled();
+        let contestants = [
+            Contestant {
+                name: "first".to_string(),
+                spec: PlayerSpec::Computer(PlayerKind::Heuristic),
+            },
+            Contestant {
+                name: "second".to_string(),
+                spec: PlayerSpec::Computer(PlayerKind::Heuristic),
+            },
+        ];
+
+        let mut logged = 0;
+        let records = run_arena(&contestants, 2, "arena", &units, &map, |record, log| {
+            assert_eq!(record.actions, log.len());
+            logged += 1;
+        })
+        .unwrap();
+        assert_eq!(logged, 2);
+        assert_eq!(
+            (records[0].german.as_str(), records[1].german.as_str()),
+            ("first", "second")
+        );
+        assert_eq!(records[1].seed, "arena-1");
+
+        let summary = summarize(&contestants, &records);
+        assert_eq!(summary.games, 2);
+        assert_eq!(
+            summary.german_wins + summary.soviet_wins + summary.undecided,
+            2
+        );
+        let total_wins: usize = summary.standings.iter().map(|s| s.wins).sum();
+        assert_eq!(total_wins, summary.german_wins + summary.soviet_wins);
+        assert!(summary.standings.iter().all(|s| s.games == 2));
+    }
+}
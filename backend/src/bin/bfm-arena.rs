@@ -0,0 +1,177 @@
+use backend::arena::{run_arena, summarize, ArenaSummary, Contestant, PlayerSpec};
+use backend::map::Map;
+use backend::unit::Units;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: bfm-arena [--games N] [--seed SEED] [--log-dir DIR] \
+[--map map.json] [--units units.json] PLAYER_A PLAYER_B";
+
+/// Command-line settings
+struct Options {
+    games: usize,
+    seed: String,
+    log_dir: Option<PathBuf>,
+    map_path: String,
+    units_path: String,
+    players: Vec<String>,
+}
+
+/// Play AI players against each other, printing one JSON record per game
+///
+/// Players are `heuristic`, `mcts[:iterations=N,time=MS,depth=D,seed=S,exploration=C]` or
+/// `bot:COMMAND` for an external bot program, which is started afresh for every game.
+/// They swap sides every game. With `--log-dir`, every game's actions and events are
+/// written to `game-N.jsonl` and the summary to `summary.json`.
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        games: 10,
+        seed: "arena".to_string(),
+        log_dir: None,
+        map_path: "data/map.json".to_string(),
+        units_path: "data/units.json".to_string(),
+        players: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--games" => {
+                options.games = value()?
+                    .parse()
+                    .map_err(|_| "--games must be a number".to_string())?
+            }
+            "--seed" => options.seed = value()?,
+            "--log-dir" => options.log_dir = Some(PathBuf::from(value()?)),
+            "--map" => options.map_path = value()?,
+            "--units" => options.units_path = value()?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => options.players.push(arg),
+        }
+    }
+    if options.players.len() != 2 {
+        return Err("Give exactly two players".to_string());
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let map = load(&options.map_path, Map::load_from_json)?;
+    let units = load(&options.units_path, Units::load_from_json)?;
+
+    let mut contestants = Vec::new();
+    for (i, name) in options.players.iter().enumerate() {
+        let spec: PlayerSpec = name.parse()?;
+        // Tell identical players apart in the results
+        let name = if options.players[0] == options.players[1] {
+            format!("{}#{}", name, i + 1)
+        } else {
+            name.clone()
+        };
+        contestants.push(Contestant { name, spec });
+    }
+    let contestants: [Contestant; 2] = contestants.try_into().unwrap();
+
+    if let Some(ref dir) = options.log_dir {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+
+    let mut write_error = None;
+    let records = run_arena(
+        &contestants,
+        options.games,
+        &options.seed,
+        &units,
+        &map,
+        |record, log| {
+            println!(
+                "{}",
+                serde_json::to_string(record).expect("Failed to serialize game record")
+            );
+            if let Some(ref dir) = options.log_dir {
+                let lines: Vec<String> = log
+                    .iter()
+                    .map(|entry| serde_json::to_string(entry).expect("Failed to serialize log"))
+                    .collect();
+                let path = dir.join(format!("game-{}.jsonl", record.game));
+                if let Err(e) = fs::write(&path, lines.join("\n") + "\n") {
+                    write_error.get_or_insert(format!("{}: {}", path.display(), e));
+                }
+            }
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(message) = write_error {
+        return Err(message);
+    }
+
+    let summary = summarize(&contestants, &records);
+    if let Some(ref dir) = options.log_dir {
+        let path = dir.join("summary.json");
+        let json = serde_json::to_string_pretty(&summary).expect("Failed to serialize summary");
+        fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    report(&summary);
+    Ok(())
+}
+
+/// Print the summary for people
+fn report(summary: &ArenaSummary) {
+    eprintln!("{} games", summary.games);
+    eprintln!(
+        "German wins: {}, {}",
+        summary.german_wins, summary.german_win_rate
+    );
+    eprintln!(
+        "Soviet wins: {}, {}",
+        summary.soviet_wins, summary.soviet_win_rate
+    );
+    if summary.undecided > 0 {
+        eprintln!("Undecided: {}", summary.undecided);
+    }
+    for standing in &summary.standings {
+        eprintln!(
+            "{}: {} of {} won, {} ({} as German, {} as Soviet)",
+            standing.name,
+            standing.wins,
+            standing.games,
+            standing.win_rate,
+            standing.wins_as_german,
+            standing.wins_as_soviet
+        );
+    }
+    match summary.average_moscow_capture_turn {
+        Some(turn) => eprintln!(
+            "Moscow taken in {} games, on average on turn {:.1}",
+            summary.moscow_captures, turn
+        ),
+        None => eprintln!("Moscow never taken"),
+    }
+    eprintln!(
+        "Average steps lost: German {:.1}, Soviet {:.1}",
+        summary.average_german_steps_lost, summary.average_soviet_steps_lost
+    );
+}
+
+/// Read and parse a data file
+fn load<T>(path: &str, parse: fn(&str) -> Result<T, serde_json::Error>) -> Result<T, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    parse(&json).map_err(|e| format!("{}: {}", path, e))
+}
@@ -0,0 +1,50 @@
+use backend::map::Map;
+use backend::unit::Units;
+use backend::validate::{validate, Diagnostic};
+use std::process::ExitCode;
+
+/// Check map and unit data files, printing one JSON diagnostic per line
+///
+/// Usage: bfm-validate [map.json] [units.json]
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let map_path = args.first().map(String::as_str).unwrap_or("data/map.json");
+    let units_path = args.get(1).map(String::as_str).unwrap_or("data/units.json");
+
+    let map = load(map_path, Map::load_from_json);
+    let units = load(units_path, Units::load_from_json);
+
+    let diagnostics = match (map, units) {
+        (Ok(map), Ok(units)) => validate(&map, &units),
+        (map, units) => map.err().into_iter().chain(units.err()).collect(),
+    };
+
+    for diagnostic in &diagnostics {
+        println!(
+            "{}",
+            serde_json::to_string(diagnostic).expect("Failed to serialize diagnostic")
+        );
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    eprintln!(
+        "{} {}: {} errors, {} warnings",
+        map_path, units_path, errors, warnings
+    );
+
+    if errors > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Read and parse a data file, turning any failure into a diagnostic
+fn load<T>(
+    path: &str,
+    parse: fn(&str) -> Result<T, serde_json::Error>,
+) -> Result<T, Diagnostic> {
+    let json = std::fs::read_to_string(path).map_err(|e| Diagnostic::read_error(path, &e))?;
+    parse(&json).map_err(|e| Diagnostic::parse_error(path, &e))
+}
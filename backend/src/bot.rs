@@ -0,0 +1,346 @@
+use crate::action::Action;
+use crate::ai::{best_loss, legal_actions, Player};
+use crate::game_state::{BattleDeclaration, GameState};
+use crate::hex::Hex;
+use crate::jsonrpc::{JsonRpcRequest, JsonRpcResponse};
+use crate::map::Map;
+use crate::retreat::find_valid_retreat_hexes;
+use crate::unit::{Side, Units};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// Version of the protocol sent in `initialize`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long a bot may think before its answer is given up on
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A bot's answer to `choose_action`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ActionChoice {
+    Index { index: usize },
+    Action { action: Action },
+}
+
+/// A bot's answer to `choose_retreat`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RetreatChoice {
+    Index { index: usize },
+    Hex { hex: Option<Hex> },
+}
+
+/// A bot's answer to `choose_loss`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LossChoice {
+    Index { index: usize },
+    Unit { unit_id: String },
+}
+
+/// A bot program playing one side over line-delimited JSON-RPC on its stdin and stdout
+///
+/// The bot is asked to `initialize`, then to `choose_action` from the legal actions,
+/// `choose_loss` before resolving a battle it attacks with several units and
+/// `choose_retreat` after losing a battle, and is told `game_over` at the end. A bot that
+/// fails to answer, answers garbage or exits has its phase ended for it, its losses
+/// chosen for it and retreats nowhere; the failure is reported on stderr.
+pub struct ExternalBot {
+    name: String,
+    child: Child,
+    input: ChildStdin,
+    lines: Receiver<io::Result<String>>,
+    next_id: u64,
+}
+
+impl ExternalBot {
+    /// Start `sh -c command` and introduce the game to it
+    pub fn spawn(command: &str, side: Side, units: &Units, map: &Map) -> io::Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = child.stdin.take().expect("stdin is piped");
+        let output = child.stdout.take().expect("stdout is piped");
+
+        // Read on a thread so a silent bot can be timed out
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = ExternalBot {
+            name: command.to_string(),
+            child,
+            input,
+            lines,
+            next_id: 1,
+        };
+        let hello = bot.call(
+            "initialize",
+            json!({
+                "protocol_version": PROTOCOL_VERSION,
+                "side": side,
+                "units": units,
+                "map": map,
+            }),
+        )?;
+        if let Some(name) = hello.get("name").and_then(Value::as_str) {
+            bot.name = name.to_string();
+        }
+        Ok(bot)
+    }
+
+    /// The name the bot gave, or its command if it gave none
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&mut self, message: &JsonRpcRequest) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.input.write_all(line.as_bytes())?;
+        self.input.flush()
+    }
+
+    /// Send a request and wait for the response with its id, skipping anything else
+    fn call(&mut self, method: &str, params: Value) -> io::Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&JsonRpcRequest::new(id, method, params))?;
+
+        loop {
+            let line = match self.lines.recv_timeout(RESPONSE_TIMEOUT) {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("No answer to {} within {:?}", method, RESPONSE_TIMEOUT),
+                    ))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "The bot closed its output",
+                    ))
+                }
+            };
+            let Ok(response) = serde_json::from_str::<JsonRpcResponse>(&line) else {
+                eprintln!("{}: ignoring \"{}\"", self.name, line);
+                continue;
+            };
+            if response.id != Some(Value::from(id)) {
+                continue;
+            }
+            if let Some(error) = response.error {
+                return Err(io::Error::other(format!("{} failed: {}", method, error)));
+            }
+            return Ok(response.result.unwrap_or(Value::Null));
+        }
+    }
+
+    fn choose_action(&mut self, state: &GameState, legal: &[Action]) -> io::Result<Action> {
+        let answer = self.call(
+            "choose_action",
+            json!({ "state": state, "legal_actions": legal }),
+        )?;
+        match serde_json::from_value(answer)? {
+            ActionChoice::Index { index } => legal.get(index).cloned().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Action index out of range")
+            }),
+            ActionChoice::Action { action } if legal.contains(&action) => Ok(action),
+            ActionChoice::Action { .. } => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Action is not among the legal actions",
+            )),
+        }
+    }
+
+    fn ask_loss(&mut self, state: &GameState, battle: &BattleDeclaration) -> io::Result<String> {
+        let answer = self.call("choose_loss", json!({ "state": state, "battle": battle }))?;
+        match serde_json::from_value(answer)? {
+            LossChoice::Index { index } => battle.attackers.get(index).cloned().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Attacker index out of range")
+            }),
+            LossChoice::Unit { unit_id } => Ok(unit_id),
+        }
+    }
+
+    fn choose_retreat(
+        &mut self,
+        state: &GameState,
+        unit_id: &str,
+        hexes: &[Hex],
+    ) -> io::Result<Option<Hex>> {
+        let answer = self.call(
+            "choose_retreat",
+            json!({ "state": state, "unit_id": unit_id, "hexes": hexes }),
+        )?;
+        match serde_json::from_value(answer)? {
+            RetreatChoice::Index { index } => {
+                hexes.get(index).map(|&hex| Some(hex)).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Retreat index out of range")
+                })
+            }
+            RetreatChoice::Hex { hex } => Ok(hex),
+        }
+    }
+}
+
+impl Player for ExternalBot {
+    fn choose(&mut self, state: &GameState, units: &Units, map: &Map) -> Action {
+        let legal = legal_actions(state, units, map);
+        self.choose_action(state, &legal).unwrap_or_else(|e| {
+            eprintln!("{}: {}", self.name, e);
+            Action::AdvancePhase
+        })
+    }
+
+    fn retreat(
+        &mut self,
+        unit_id: &str,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> Option<Hex> {
+        let from = state.get_unit(unit_id)?.hex()?;
+        let hexes = find_valid_retreat_hexes(unit_id, &from, state, units, map);
+        if hexes.is_empty() {
+            return None;
+        }
+        self.choose_retreat(state, unit_id, &hexes)
+            .unwrap_or_else(|e| {
+                eprintln!("{}: {}", self.name, e);
+                None
+            })
+    }
+
+    fn choose_loss(
+        &mut self,
+        battle: &BattleDeclaration,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> Option<String> {
+        if battle.attackers.len() < 2 {
+            return battle.attackers.first().cloned();
+        }
+        self.ask_loss(state, battle)
+            .map_err(|e| eprintln!("{}: {}", self.name, e))
+            .ok()
+            .or_else(|| best_loss(battle, state, units, map))
+    }
+
+    fn game_over(&mut self, state: &GameState) {
+        let notification = JsonRpcRequest::notification(
+            "game_over",
+            json!({ "state": state, "winner": state.check_victory() }),
+        );
+        if let Err(e) = self.send(&notification) {
+            eprintln!("{}: {}", self.name, e);
+        }
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::bundled;
+    use crate::ai::play_game;
+    use crate::game_state::Phase;
+
+    /// Answers every request with `result`, echoing its id
+    fn echo_bot(result: &str) -> String {
+        format!(
+            r#"while read -r line; do
+                 id=$(printf '%s' "$line" | sed -n 's/^{{"jsonrpc":"2.0","id":\([0-9]*\),.*/\1/p')
+                 [ -n "$id" ] && printf '{{"jsonrpc":"2.0","id":%s,"result":{}}}\n' "$id"
+               done"#,
+            result
+        )
+    }
+
+    #[test]
+    fn test_bot_plays_a_game() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("bots");
+        state.place_initial_units(&units, &map);
+
+        // Both bots always take the first legal action
+        let first = echo_bot(r#"{"index":0,"name":"first"}"#);
+        let mut german = ExternalBot::spawn(&first, Side::German, &units, &map).unwrap();
+        let mut soviet = ExternalBot::spawn(&first, Side::Soviet, &units, &map).unwrap();
+        assert_eq!(german.name(), "first");
+
+        let mut moving = state.clone();
+        moving.phase = Phase::GermanMovement;
+        let first_legal = legal_actions(&moving, &units, &map)[0].clone();
+        assert!(matches!(first_legal, Action::Move { .. }));
+        assert_eq!(german.choose(&moving, &units, &map), first_legal);
+        let battle = BattleDeclaration {
+            attackers: vec!["XXIV".to_string(), "V".to_string()],
+            defender: "3".to_string(),
+            resolved: false,
+        };
+        assert_eq!(
+            german.choose_loss(&battle, &state, &units, &map),
+            Some("XXIV".to_string())
+        );
+
+        play_game(&mut german, &mut soviet, &mut state, &units, &map).unwrap();
+        assert!(state.is_over());
+    }
+
+    #[test]
+    fn test_bot_failures() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("bad-bot");
+        state.place_initial_units(&units, &map);
+
+        let mut wild =
+            ExternalBot::spawn(&echo_bot(r#"{"index":99}"#), Side::German, &units, &map).unwrap();
+        assert_eq!(wild.choose(&state, &units, &map), Action::AdvancePhase);
+
+        // An action named outright must still be one of those offered
+        let mut combat = state.clone();
+        combat.phase = Phase::GermanCombat;
+        let declare =
+            r#"{"action":{"type":"declare_battle","attacker_ids":["XXIV"],"defender_id":"3"}}"#;
+        let mut sneaky =
+            ExternalBot::spawn(&echo_bot(declare), Side::German, &units, &map).unwrap();
+        assert_eq!(sneaky.choose(&combat, &units, &map), Action::AdvancePhase);
+
+        let mut errors = ExternalBot::spawn(
+            r#"read -r line; echo '{"jsonrpc":"2.0","id":1,"result":{}}'; read -r line;
+               echo '{"jsonrpc":"2.0","id":2,"error":{"code":-1,"message":"no"}}'"#,
+            Side::German,
+            &units,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(errors.choose(&state, &units, &map), Action::AdvancePhase);
+        // Nothing more comes once the bot has exited
+        assert_eq!(errors.choose(&state, &units, &map), Action::AdvancePhase);
+
+        assert!(ExternalBot::spawn("exit 0", Side::German, &units, &map).is_err());
+    }
+}
@@ -0,0 +1,503 @@
+use crate::analysis::AttackPlan;
+use crate::api::{
+    ApiResponse, AttackPlanQuery, ClaimSeatRequest, CommitSeedRequest, DeclareBattleRequest,
+    Endpoint, EvaluateQuery, MoveRequest, NewGameRequest, RenderQuery, ReplacementRequest,
+    ResolveBattleRequest, RetreatRequest, RevealSeedRequest, SeatClaim, ThreatQuery, TurnCheck,
+};
+use crate::combat::{BattlePreview, BattleResolution};
+use crate::dice::DiceAudit;
+use crate::evaluate::Assessment;
+use crate::game_state::GameState;
+use crate::hex::Hex;
+use crate::map::Map;
+use crate::retreat::RetreatResult;
+use crate::seats::{Seat, SeatSummary};
+use crate::threats::ThreatReport;
+use crate::turn_file::TurnFile;
+use crate::unit::{Side, Units};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+
+/// Why a call to the game API failed
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server could not be reached or its reply could not be read
+    Transport(reqwest::Error),
+    /// The server refused the request
+    Api {
+        status: u16,
+        code: String,
+        message: String,
+    },
+}
+
+impl ClientError {
+    /// The stable error code, if the server sent one
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => Some(code),
+            ClientError::Transport(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Transport(e) => write!(f, "{}", e),
+            ClientError::Api { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Transport(err)
+    }
+}
+
+/// Typed client for the game API
+///
+/// Routes come from the same `Endpoint` table the server is built from, and requests and
+/// replies use the server's own types, so the two cannot drift apart.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    /// Client for a server such as `http://127.0.0.1:3000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        ApiClient {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Send this seat token with every request
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Full URL of an endpoint, filling its path parameters in order
+    pub fn url(&self, endpoint: Endpoint, params: &[&str]) -> String {
+        let mut path = endpoint.path().to_string();
+        for param in params {
+            if let (Some(start), Some(end)) = (path.find('{'), path.find('}')) {
+                path.replace_range(start..=end, param);
+            }
+        }
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn request(&self, endpoint: Endpoint, params: &[&str]) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(endpoint.method(), self.url(endpoint, params));
+        match self.token {
+            Some(ref token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Send a request and unwrap the `ApiResponse` envelope
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ClientError> {
+        let response = request.send().await?;
+        let status = response.status().as_u16();
+        let envelope: ApiResponse<T> = response.json().await?;
+
+        match envelope.data {
+            Some(data) if envelope.success => Ok(data),
+            _ => Err(ClientError::Api {
+                status,
+                code: envelope.code.unwrap_or_else(|| "internal".to_string()),
+                message: envelope
+                    .error
+                    .unwrap_or_else(|| "Response carried no data".to_string()),
+            }),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        params: &[&str],
+    ) -> Result<T, ClientError> {
+        self.send(self.request(endpoint, params)).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        self.send(self.request(endpoint, &[]).json(body)).await
+    }
+
+    pub async fn game_state(&self) -> Result<GameState, ClientError> {
+        self.get(Endpoint::GetGame, &[]).await
+    }
+
+    pub async fn new_game(&self, request: &NewGameRequest) -> Result<GameState, ClientError> {
+        self.post(Endpoint::NewGame, request).await
+    }
+
+    pub async fn advance_phase(&self) -> Result<GameState, ClientError> {
+        self.send(self.request(Endpoint::AdvancePhase, &[])).await
+    }
+
+    pub async fn move_unit(&self, request: &MoveRequest) -> Result<GameState, ClientError> {
+        self.post(Endpoint::MoveUnit, request).await
+    }
+
+    pub async fn valid_moves(&self, unit_id: &str) -> Result<Vec<Hex>, ClientError> {
+        self.get(Endpoint::ValidMoves, &[unit_id]).await
+    }
+
+    pub async fn declare_battle(
+        &self,
+        request: &DeclareBattleRequest,
+    ) -> Result<GameState, ClientError> {
+        self.post(Endpoint::DeclareBattle, request).await
+    }
+
+    pub async fn resolve_battle(
+        &self,
+        request: &ResolveBattleRequest,
+    ) -> Result<BattleResolution, ClientError> {
+        self.post(Endpoint::ResolveBattle, request).await
+    }
+
+    pub async fn preview_battle(
+        &self,
+        request: &DeclareBattleRequest,
+    ) -> Result<BattlePreview, ClientError> {
+        self.post(Endpoint::PreviewBattle, request).await
+    }
+
+    pub async fn attack_plans(&self, limit: Option<usize>) -> Result<Vec<AttackPlan>, ClientError> {
+        let query = AttackPlanQuery { limit };
+        self.send(self.request(Endpoint::AttackPlans, &[]).query(&query))
+            .await
+    }
+
+    pub async fn threats(&self, side: Option<Side>) -> Result<ThreatReport, ClientError> {
+        let query = ThreatQuery { side };
+        self.send(self.request(Endpoint::Threats, &[]).query(&query))
+            .await
+    }
+
+    pub async fn evaluate(
+        &self,
+        playouts: Option<usize>,
+        seed: Option<u64>,
+    ) -> Result<Assessment, ClientError> {
+        let query = EvaluateQuery { playouts, seed };
+        self.send(self.request(Endpoint::Evaluate, &[]).query(&query))
+            .await
+    }
+
+    pub async fn apply_replacement(
+        &self,
+        request: &ReplacementRequest,
+    ) -> Result<GameState, ClientError> {
+        self.post(Endpoint::ApplyReplacement, request).await
+    }
+
+    pub async fn replacement_hexes(&self) -> Result<Vec<Hex>, ClientError> {
+        self.get(Endpoint::ReplacementHexes, &[]).await
+    }
+
+    pub async fn retreat(&self, request: &RetreatRequest) -> Result<RetreatResult, ClientError> {
+        self.post(Endpoint::ExecuteRetreat, request).await
+    }
+
+    pub async fn retreat_hexes(&self, unit_id: &str) -> Result<Vec<Hex>, ClientError> {
+        self.get(Endpoint::RetreatHexes, &[unit_id]).await
+    }
+
+    pub async fn map(&self) -> Result<Map, ClientError> {
+        self.get(Endpoint::GetMap, &[]).await
+    }
+
+    pub async fn units(&self) -> Result<Units, ClientError> {
+        self.get(Endpoint::GetUnits, &[]).await
+    }
+
+    /// The map as SVG; not wrapped in the envelope
+    pub async fn render_svg(&self, query: &RenderQuery) -> Result<String, ClientError> {
+        let response = self
+            .request(Endpoint::RenderSvg, &[])
+            .query(query)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status.is_success() {
+            return Ok(body);
+        }
+
+        let (code, message) = match serde_json::from_str::<ApiResponse<()>>(&body) {
+            Ok(envelope) => (envelope.code, envelope.error.unwrap_or_default()),
+            Err(_) => (None, body),
+        };
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            code: code.unwrap_or_else(|| "internal".to_string()),
+            message,
+        })
+    }
+
+    pub async fn seats(&self) -> Result<SeatSummary, ClientError> {
+        self.get(Endpoint::GetSeats, &[]).await
+    }
+
+    /// Claim a seat; the returned token is not stored, see `set_token`
+    pub async fn claim_seat(&self, seat: Seat) -> Result<SeatClaim, ClientError> {
+        self.post(Endpoint::ClaimSeat, &ClaimSeatRequest { seat })
+            .await
+    }
+
+    pub async fn release_seat(&self) -> Result<Seat, ClientError> {
+        self.send(self.request(Endpoint::ReleaseSeat, &[])).await
+    }
+
+    pub async fn export_turn(&self) -> Result<TurnFile, ClientError> {
+        self.get(Endpoint::ExportTurn, &[]).await
+    }
+
+    pub async fn verify_turn(&self, turn: &TurnFile) -> Result<TurnCheck, ClientError> {
+        self.post(Endpoint::VerifyTurn, turn).await
+    }
+
+    pub async fn import_turn(&self, turn: &TurnFile) -> Result<GameState, ClientError> {
+        self.post(Endpoint::ImportTurn, turn).await
+    }
+
+    pub async fn commit_seed(&self, commitment: &str) -> Result<GameState, ClientError> {
+        let request = CommitSeedRequest {
+            commitment: commitment.to_string(),
+        };
+        self.post(Endpoint::CommitSeed, &request).await
+    }
+
+    pub async fn reveal_seed(&self, link: &str, defender: &str) -> Result<GameState, ClientError> {
+        let request = RevealSeedRequest {
+            link: link.to_string(),
+            defender: defender.to_string(),
+        };
+        self.post(Endpoint::RevealSeed, &request).await
+    }
+
+    pub async fn dice_audit(&self) -> Result<DiceAudit, ClientError> {
+        self.get(Endpoint::AuditDice, &[]).await
+    }
+
+    /// The server's OpenAPI document
+    pub async fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        let response = self.request(Endpoint::OpenApi, &[]).send().await?;
+        Ok(response.error_for_status()?.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{ComputerOpponent, PlayerKind};
+    use crate::api::{create_router, AppState};
+    use crate::dice::chain_link;
+    use crate::game_state::Phase;
+    use crate::openapi::ApiDoc;
+    use crate::test_support::bundled;
+    use crate::unit::Side;
+    use utoipa::OpenApi;
+
+    /// Serve a fresh game on an ephemeral port
+    async fn spawn_server() -> ApiClient {
+        let (units, map) = bundled();
+        let mut game = GameState::new();
+        game.place_initial_units(&units, &map);
+        let app = create_router(AppState::new(game, units, map));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        ApiClient::new(format!("http://{}", addr))
+    }
+
+    #[test]
+    fn test_url() {
+        let client = ApiClient::new("http://localhost:3000/");
+        assert_eq!(
+            client.url(Endpoint::ValidMoves, &["XLVII"]),
+            "http://localhost:3000/api/units/XLVII/valid-moves"
+        );
+        assert_eq!(
+            client.url(Endpoint::GetGame, &[]),
+            "http://localhost:3000/api/game"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_matches_server() {
+        let mut client = spawn_server().await;
+
+        // The served document is the one the client was built against
+        let served = client.openapi().await.unwrap();
+        assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+
+        let state = client.game_state().await.unwrap();
+        let unit_id = state.units[0].id.clone();
+        assert!(!client.map().await.unwrap().hexes.is_empty());
+        assert!(!client.units().await.unwrap().units.is_empty());
+        client.valid_moves(&unit_id).await.unwrap();
+        client.replacement_hexes().await.unwrap();
+        let preview = DeclareBattleRequest {
+            attacker_ids: vec!["3".to_string()],
+            defender_id: "V".to_string(),
+        };
+        let preview = client.preview_battle(&preview).await.unwrap();
+        let total: f64 = preview.outcomes.iter().map(|o| o.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(
+            client.attack_plans(None).await.unwrap_err().code(),
+            Some("wrong_phase")
+        );
+        let threats = client.threats(None).await.unwrap();
+        assert_eq!(threats.side, Side::German);
+        assert_eq!(
+            client.threats(Some(Side::Soviet)).await.unwrap().side,
+            Side::Soviet
+        );
+        let assessment = client.evaluate(Some(5), None).await.unwrap();
+        assert_eq!(assessment.playouts, 5);
+        assert_eq!(client.evaluate(Some(5), None).await.unwrap(), assessment);
+        assert_eq!(client.evaluate(None, None).await.unwrap().playouts, 0);
+        assert!(client
+            .render_svg(&RenderQuery::default())
+            .await
+            .unwrap()
+            .starts_with("<svg"));
+
+        // Rule errors come back typed, with the server's code and status
+        match client.valid_moves("no-such-unit").await {
+            Err(ClientError::Api { status, code, .. }) => {
+                assert_eq!((status, code.as_str()), (404, "unit_not_found"));
+            }
+            other => panic!("Expected an API error, got {:?}", other),
+        }
+
+        let claim = client.claim_seat(Seat::German).await.unwrap();
+        assert_eq!(
+            client.claim_seat(Seat::German).await.unwrap_err().code(),
+            Some("seat_taken")
+        );
+        client.set_token(Some(claim.token));
+        assert!(client.seats().await.unwrap().german);
+
+        let state = client.advance_phase().await.unwrap();
+        assert_eq!(state.phase, crate::game_state::Phase::GermanCombat);
+        assert!(client.attack_plans(Some(3)).await.unwrap().len() <= 3);
+        let turn = client.export_turn().await.unwrap();
+        assert_eq!(turn.actions.len(), 1);
+        assert!(client.verify_turn(&turn).await.is_err());
+
+        assert_eq!(client.release_seat().await.unwrap(), Seat::German);
+        assert_eq!(
+            client.new_game(&NewGameRequest::default()).await.unwrap_err().code(),
+            Some("unknown_seat_token")
+        );
+        client.set_token(None);
+        let state = client.new_game(&NewGameRequest::default()).await.unwrap();
+        assert_eq!(
+            client.advance_phase().await.unwrap_err().code(),
+            Some("seat_token_required")
+        );
+        assert_eq!(state.turn, 1);
+        assert_eq!(
+            client.dice_audit().await.unwrap_err().code(),
+            Some("game_not_over")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_computer_opponent() {
+        let client = spawn_server().await;
+        let request = NewGameRequest {
+            seed: Some("computer".to_string()),
+            computer: Some(ComputerOpponent {
+                side: Side::German,
+                player: PlayerKind::Heuristic,
+            }),
+            hotseat: true,
+            ..NewGameRequest::default()
+        };
+
+        // The computer holds no hash chain, so it only plays with server-rolled dice
+        let with_chains = NewGameRequest {
+            commit_reveal: true,
+            ..request.clone()
+        };
+        assert_eq!(
+            client.new_game(&with_chains).await.unwrap_err().code(),
+            Some("bad_request")
+        );
+
+        // The computer takes the German turn at once and hands over to the Soviets
+        let state = client.new_game(&request).await.unwrap();
+        assert_eq!(state.phase, Phase::SovietReplacement);
+        assert_eq!(client.seats().await.unwrap().computer, Some(Side::German));
+        assert_eq!(
+            client.claim_seat(Seat::German).await.unwrap_err().code(),
+            Some("seat_taken")
+        );
+
+        // Ending the Soviet turn lets the computer play its next one
+        for _ in 0..4 {
+            client.advance_phase().await.unwrap();
+        }
+        let state = client.game_state().await.unwrap();
+        assert_eq!((state.turn, state.phase), (2, Phase::SovietReplacement));
+    }
+
+    #[tokio::test]
+    async fn test_commit_reveal_dice() {
+        let mut client = spawn_server().await;
+        let request = NewGameRequest {
+            commit_reveal: true,
+            ..NewGameRequest::default()
+        };
+        client.new_game(&request).await.unwrap();
+        let german = client.claim_seat(Seat::German).await.unwrap().token;
+        let soviet = client.claim_seat(Seat::Soviet).await.unwrap().token;
+
+        client.set_token(Some(german));
+        client.commit_seed(&chain_link("g", 5)).await.unwrap();
+        client.set_token(Some(soviet));
+        let state = client.commit_seed(&chain_link("s", 5)).await.unwrap();
+        assert!(state.dice.is_ready());
+
+        // Links go only to battles that will roll, so none is stranded
+        assert_eq!(
+            client
+                .reveal_seed(&chain_link("s", 4), "V")
+                .await
+                .unwrap_err()
+                .code(),
+            Some("roll_not_needed")
+        );
+    }
+}
@@ -1,12 +1,12 @@
+use crate::error::RuleError;
 use crate::game_state::{BattleDeclaration, GameState};
-use crate::hex::{Direction, Hex};
+use crate::hex::Hex;
 use crate::map::{Map, Terrain};
 use crate::unit::{Side, UnitStrength, Units};
-use crate::zoc::is_in_enemy_zoc;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CombatResult {
     NE,  // No Effect
@@ -17,7 +17,7 @@ pub enum CombatResult {
     EX,  // Exchange
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BattleResolution {
     pub die_roll: i32,
     pub raw_odds: String,
@@ -102,51 +102,54 @@ fn level_to_odds_string(level: i32) -> String {
     }
 }
 
-/// Resolve a single battle
-pub fn resolve_battle(
-    battle: &BattleDeclaration,
-    state: &mut GameState,
+/// Strengths and odds column of a battle, before the die is rolled
+struct Odds {
+    attack_strength: i32,
+    defense_strength: i32,
+    raw_level: i32,
+    level: i32,
+    modifiers: Vec<String>,
+}
+
+/// Work out the odds of `attackers` against `defender`, terrain included
+fn calculate_odds(
+    attackers: &[String],
+    defender: &str,
+    state: &GameState,
     units: &Units,
     map: &Map,
-) -> Result<BattleResolution, String> {
+) -> Result<Odds, RuleError> {
     // Get defender
     let defender_state = state
-        .get_unit(&battle.defender)
-        .ok_or("Defender not found")?
-        .clone();
+        .get_unit(defender)
+        .ok_or_else(|| RuleError::UnitNotFound(defender.to_string()))?;
 
     let defender_def = units
-        .get(&battle.defender)
-        .ok_or("Defender definition not found")?;
+        .get(defender)
+        .ok_or_else(|| RuleError::UnitNotFound(defender.to_string()))?;
 
     let defender_hex = defender_state
         .hex()
-        .ok_or("Defender is eliminated")?;
+        .ok_or_else(|| RuleError::NotOnMap(defender.to_string()))?;
 
     // Calculate total attack strength
     let mut total_attack = 0;
-    let mut attacker_side = None;
     let mut attacker_hexes = Vec::new();
 
-    for attacker_id in &battle.attackers {
+    for attacker_id in attackers {
         let attacker_state = state
             .get_unit(attacker_id)
-            .ok_or(format!("Attacker {} not found", attacker_id))?
-            .clone();
+            .ok_or_else(|| RuleError::UnitNotFound(attacker_id.clone()))?;
 
         let attacker_def = units
             .get(attacker_id)
-            .ok_or(format!("Attacker definition for {} not found", attacker_id))?;
-
-        if attacker_side.is_none() {
-            attacker_side = Some(attacker_def.side.clone());
-        }
+            .ok_or_else(|| RuleError::UnitNotFound(attacker_id.clone()))?;
 
         let mut strength = attacker_def.get_combat_strength(&attacker_state.strength);
 
         // Halve attack strength in mud turns
         if state.is_mud() {
-            strength = strength / 2;
+            strength /= 2;
         }
 
         total_attack += strength;
@@ -161,7 +164,8 @@ pub fn resolve_battle(
 
     // Calculate raw odds
     let raw_odds_ratio = total_attack as f32 / defender_strength as f32;
-    let mut odds_level = odds_to_level(raw_odds_ratio);
+    let raw_level = odds_to_level(raw_odds_ratio);
+    let mut odds_level = raw_level;
 
     // Apply terrain modifiers
     let mut modifiers = Vec::new();
@@ -204,31 +208,59 @@ pub fn resolve_battle(
         }
     }
 
+    Ok(Odds {
+        attack_strength: total_attack,
+        defense_strength: defender_strength,
+        raw_level,
+        level: odds_level,
+        modifiers,
+    })
+}
+
+/// Whether resolving a battle rolls the die, which it does unless the odds are below 1:1
+pub fn needs_die_roll(
+    battle: &BattleDeclaration,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<bool, RuleError> {
+    let odds = calculate_odds(&battle.attackers, &battle.defender, state, units, map)?;
+    Ok(odds.level >= 0)
+}
+
+/// Resolve a single battle
+pub fn resolve_battle(
+    battle: &BattleDeclaration,
+    state: &mut GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<BattleResolution, RuleError> {
+    let odds = calculate_odds(&battle.attackers, &battle.defender, state, units, map)?;
+
     // Below 1:1 after modifiers = no effect
-    if odds_level < 0 {
+    if odds.level < 0 {
         return Ok(BattleResolution {
             die_roll: 0,
-            raw_odds: level_to_odds_string(odds_to_level(raw_odds_ratio)),
+            raw_odds: level_to_odds_string(odds.raw_level),
             modified_odds: "Below 1:1".to_string(),
-            terrain_modifiers: modifiers,
+            terrain_modifiers: odds.modifiers,
             result: CombatResult::NE,
             result_description: "Odds too low - No Effect".to_string(),
         });
     }
 
     // Roll die
-    let mut rng = rand::thread_rng();
-    let die_roll = rng.gen_range(1..=6);
+    let die_roll = state.dice.roll_for(&battle.defender)?;
 
     // Look up result
-    let result = combat_results_table(die_roll, odds_level);
+    let result = combat_results_table(die_roll, odds.level);
 
     // Create resolution
     let resolution = BattleResolution {
         die_roll,
-        raw_odds: level_to_odds_string(odds_to_level(raw_odds_ratio)),
-        modified_odds: level_to_odds_string(odds_level),
-        terrain_modifiers: modifiers,
+        raw_odds: level_to_odds_string(odds.raw_level),
+        modified_odds: level_to_odds_string(odds.level),
+        terrain_modifiers: odds.modifiers,
         result: result.clone(),
         result_description: format!("{:?}", result),
     };
@@ -239,13 +271,282 @@ pub fn resolve_battle(
     Ok(resolution)
 }
 
+/// One combat result and the die rolls that produce it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct OutcomeChance {
+    pub result: CombatResult,
+    /// Empty when the odds are too low to roll
+    pub die_rolls: Vec<i32>,
+    pub probability: f64,
+}
+
+/// What a battle would look like if declared now, without rolling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BattlePreview {
+    pub attackers: Vec<String>,
+    pub defender: String,
+    /// Total attack strength, after halving for mud
+    pub attack_strength: i32,
+    pub defense_strength: i32,
+    pub mud: bool,
+    pub raw_odds: String,
+    pub modified_odds: String,
+    pub terrain_modifiers: Vec<String>,
+    /// Every possible result with its chance, in die roll order; unlisted results cannot occur
+    pub outcomes: Vec<OutcomeChance>,
+    /// Average steps the attackers lose
+    pub expected_attacker_losses: f64,
+    /// Average steps the defender loses, counting elimination as all its remaining steps
+    pub expected_defender_losses: f64,
+}
+
+/// Preview a battle: the odds `resolve_battle` would use and the CRT column they select
+pub fn preview_battle(
+    attackers: &[String],
+    defender: &str,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<BattlePreview, RuleError> {
+    Ok(preview_with_level(attackers, defender, state, units, map)?.1)
+}
+
+/// A preview together with its odds column, for ranking
+fn preview_with_level(
+    attackers: &[String],
+    defender: &str,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<(i32, BattlePreview), RuleError> {
+    let odds = calculate_odds(attackers, defender, state, units, map)?;
+
+    let outcomes = if odds.level < 0 {
+        vec![OutcomeChance {
+            result: CombatResult::NE,
+            die_rolls: Vec::new(),
+            probability: 1.0,
+        }]
+    } else {
+        let mut outcomes: Vec<OutcomeChance> = Vec::new();
+        for die_roll in 1..=6 {
+            let result = combat_results_table(die_roll, odds.level);
+            match outcomes.iter_mut().find(|o| o.result == result) {
+                Some(outcome) => outcome.die_rolls.push(die_roll),
+                None => outcomes.push(OutcomeChance {
+                    result,
+                    die_rolls: vec![die_roll],
+                    probability: 0.0,
+                }),
+            }
+        }
+        for outcome in &mut outcomes {
+            outcome.probability = outcome.die_rolls.len() as f64 / 6.0;
+        }
+        outcomes
+    };
+
+    let defender_steps = match state.get_unit(defender).map(|u| &u.strength) {
+        Some(UnitStrength::Full) => 2.0,
+        _ => 1.0,
+    };
+    let (expected_attacker_losses, expected_defender_losses) =
+        outcomes.iter().fold((0.0, 0.0), |(attacker, defender), o| {
+            let (a, d) = steps_lost(&o.result, defender_steps);
+            (attacker + a * o.probability, defender + d * o.probability)
+        });
+
+    let preview = BattlePreview {
+        attackers: attackers.to_vec(),
+        defender: defender.to_string(),
+        attack_strength: odds.attack_strength,
+        defense_strength: odds.defense_strength,
+        mud: state.is_mud(),
+        raw_odds: level_to_odds_string(odds.raw_level),
+        modified_odds: if odds.level < 0 {
+            "Below 1:1".to_string()
+        } else {
+            level_to_odds_string(odds.level)
+        },
+        terrain_modifiers: odds.modifiers,
+        outcomes,
+        expected_attacker_losses,
+        expected_defender_losses,
+    };
+    Ok((odds.level.max(-1), preview))
+}
+
+/// Steps lost by (attackers, defender) for a result, given the defender's remaining steps
+fn steps_lost(result: &CombatResult, defender_steps: f64) -> (f64, f64) {
+    match result {
+        CombatResult::NE | CombatResult::DR => (0.0, 0.0),
+        CombatResult::AL => (1.0, 0.0),
+        CombatResult::DRL => (0.0, 1.0),
+        CombatResult::DE => (0.0, defender_steps),
+        CombatResult::EX => (1.0, 1.0),
+    }
+}
+
+/// An enemy unit the active side can attack, and with what
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AttackOption {
+    pub defender: String,
+    pub defender_hex: Hex,
+    /// Adjacent friendly units not yet committed to a battle
+    pub eligible_attackers: Vec<String>,
+    /// Every non-empty group of eligible attackers, best odds first
+    pub groups: Vec<BattlePreview>,
+}
+
+/// Every battle the active side could still declare this combat phase
+///
+/// Each unit attacks at most once and each enemy unit is attacked at most once per phase,
+/// so units and defenders in already declared battles are left out.
+pub fn find_valid_attacks(
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<Vec<AttackOption>, RuleError> {
+    if !state.phase.is_combat_phase() {
+        return Err(RuleError::WrongPhase("combat"));
+    }
+    let side = state.active_player();
+    let committed = committed_units(state);
+
+    let mut options = Vec::new();
+    for defender_state in &state.units {
+        let (Some(defender_hex), Some(defender_def)) =
+            (defender_state.hex(), units.get(&defender_state.id))
+        else {
+            continue;
+        };
+        if defender_def.side == side || committed.contains(&&defender_state.id) {
+            continue;
+        }
+
+        let eligible: Vec<String> = state
+            .units
+            .iter()
+            .filter(|u| u.hex().is_some_and(|hex| hex.is_adjacent(&defender_hex)))
+            .filter(|u| units.get(&u.id).is_some_and(|def| def.side == side))
+            .filter(|u| !committed.contains(&&u.id))
+            .map(|u| u.id.clone())
+            .collect();
+        if eligible.is_empty() {
+            continue;
+        }
+
+        let mut groups = Vec::new();
+        for mask in 1..(1u32 << eligible.len()) {
+            let attackers: Vec<String> = eligible
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, id)| id.clone())
+                .collect();
+            groups.push(preview_with_level(
+                &attackers,
+                &defender_state.id,
+                state,
+                units,
+                map,
+            )?);
+        }
+        // Best odds first; among equals, the fewest units committed
+        groups.sort_by_key(|(level, g)| (-level, g.attackers.len()));
+
+        options.push(AttackOption {
+            defender: defender_state.id.clone(),
+            defender_hex,
+            eligible_attackers: eligible,
+            groups: groups.into_iter().map(|(_, g)| g).collect(),
+        });
+    }
+    Ok(options)
+}
+
+/// Units attacking or attacked in the battles already declared this phase
+fn committed_units(state: &GameState) -> Vec<&String> {
+    state
+        .pending_battles
+        .iter()
+        .flat_map(|b| b.attackers.iter().chain([&b.defender]))
+        .collect()
+}
+
+/// Check a battle the active side wants to declare
+///
+/// The rules are the ones `find_valid_attacks` offers battles by: the defender is an
+/// enemy unit on the map, every attacker is a unit of the active side next to it, and no
+/// unit attacks or is attacked more than once per phase.
+pub fn validate_battle(
+    attackers: &[String],
+    defender: &str,
+    state: &GameState,
+    units: &Units,
+) -> Result<(), RuleError> {
+    if !state.phase.is_combat_phase() {
+        return Err(RuleError::WrongPhase("combat"));
+    }
+    let side = state.active_player();
+    let committed = committed_units(state);
+
+    let defender_def = units
+        .get(defender)
+        .ok_or_else(|| RuleError::UnitNotFound(defender.to_string()))?;
+    let defender_hex = state
+        .get_unit(defender)
+        .ok_or_else(|| RuleError::UnitNotFound(defender.to_string()))?
+        .hex()
+        .ok_or_else(|| RuleError::NotOnMap(defender.to_string()))?;
+    if defender_def.side == side {
+        return Err(RuleError::NotAnEnemy(defender.to_string()));
+    }
+    if committed.iter().any(|id| *id == defender) {
+        return Err(RuleError::AlreadyInBattle(defender.to_string()));
+    }
+    if attackers.is_empty() {
+        return Err(RuleError::NoAttackers);
+    }
+
+    for (i, attacker) in attackers.iter().enumerate() {
+        let attacker_def = units
+            .get(attacker)
+            .ok_or_else(|| RuleError::UnitNotFound(attacker.clone()))?;
+        if attacker_def.side != side {
+            return Err(RuleError::NotYourTurn);
+        }
+        let hex = state
+            .get_unit(attacker)
+            .ok_or_else(|| RuleError::UnitNotFound(attacker.clone()))?
+            .hex()
+            .ok_or_else(|| RuleError::NotOnMap(attacker.clone()))?;
+        if !hex.is_adjacent(&defender_hex) {
+            return Err(RuleError::NotAdjacent(attacker.clone()));
+        }
+        if committed.contains(&attacker) || attackers[..i].contains(attacker) {
+            return Err(RuleError::AlreadyInBattle(attacker.clone()));
+        }
+    }
+    Ok(())
+}
+
 /// Apply combat result to game state
+///
+/// `loss` names the attacker that takes the step on an AL; the first one declared if absent.
 pub fn apply_combat_result(
     result: &CombatResult,
     battle: &BattleDeclaration,
+    loss: Option<&str>,
     state: &mut GameState,
-    units: &Units,
-) -> Result<(), String> {
+    _units: &Units,
+) -> Result<(), RuleError> {
+    if let Some(id) = loss {
+        if !battle.attackers.iter().any(|a| a == id) {
+            return Err(RuleError::NotAnAttacker(id.to_string()));
+        }
+    }
+
     match result {
         CombatResult::NE => {
             // Nothing happens
@@ -253,8 +554,7 @@ pub fn apply_combat_result(
         }
         CombatResult::AL => {
             // Attacker chooses one unit to take a loss
-            // For now, just take the first attacker
-            if let Some(attacker_id) = battle.attackers.first() {
+            if let Some(attacker_id) = loss.or(battle.attackers.first().map(String::as_str)) {
                 if let Some(unit) = state.get_unit_mut(attacker_id) {
                     unit.take_loss();
                 }
@@ -263,15 +563,17 @@ pub fn apply_combat_result(
         }
         CombatResult::DR => {
             // Defender retreats (path must be chosen)
-            // This requires additional logic for retreat path selection
+            state.pending_retreats.push(battle.defender.clone());
             Ok(())
         }
         CombatResult::DRL => {
             // Defender takes loss, then retreats
             if let Some(unit) = state.get_unit_mut(&battle.defender) {
                 unit.take_loss();
+                if unit.hex().is_some() {
+                    state.pending_retreats.push(battle.defender.clone());
+                }
             }
-            // Then retreat (requires path selection)
             Ok(())
         }
         CombatResult::DE => {
@@ -313,4 +615,146 @@ mod tests {
         assert_eq!(odds_to_level(6.0), 5);  // 6:1
         assert_eq!(odds_to_level(10.0), 5); // Capped at 6:1
     }
+
+    /// A forest hex at (1, 0) held by a Soviet army, with two Germans to its west
+    fn battle_setup() -> (GameState, Units, Map) {
+        use crate::game_state::Phase;
+        use crate::test_support::{test_map, unit};
+        use crate::unit::{UnitState, UnitType};
+
+        let mut map = test_map(3, 2, None);
+        map.hexes.retain(|hex| (hex.q, hex.r) != (2, 1));
+        for hex in map.hexes.iter_mut().filter(|hex| (hex.q, hex.r) == (1, 0)) {
+            hex.terrain = Terrain::Forest;
+        }
+
+        let units = Units {
+            units: vec![
+                unit("XLVII", Side::German, UnitType::Panzer, 9, 4),
+                unit("V", Side::German, UnitType::Infantry, 6, 4),
+                unit("16", Side::Soviet, UnitType::Infantry, 8, 4),
+            ],
+        };
+
+        let mut state = GameState::with_seed("combat-test");
+        state.phase = Phase::GermanCombat;
+        for (id, hex) in [("XLVII", (0, 0)), ("V", (0, 1)), ("16", (1, 0))] {
+            let hex = Some(Hex::new(hex.0, hex.1));
+            state
+                .units
+                .push(UnitState::new(id.to_string(), hex, UnitStrength::Half));
+        }
+        (state, units, map)
+    }
+
+    #[test]
+    fn test_preview_matches_resolution() {
+        let (state, units, map) = battle_setup();
+        let attackers = vec!["XLVII".to_string(), "V".to_string()];
+        let preview = preview_battle(&attackers, "16", &state, &units, &map).unwrap();
+
+        // 4 + 3 against 4 is 1:1, shifted below 1:1 by the forest
+        assert_eq!((preview.attack_strength, preview.defense_strength), (7, 4));
+        assert_eq!(preview.raw_odds, "1:1");
+        assert_eq!(preview.modified_odds, "Below 1:1");
+        assert_eq!(preview.terrain_modifiers, vec!["Forest".to_string()]);
+        assert_eq!(preview.outcomes.len(), 1);
+        assert!(preview.outcomes[0].die_rolls.is_empty());
+
+        let battle = BattleDeclaration {
+            attackers,
+            defender: "16".to_string(),
+            resolved: false,
+        };
+        let resolution = resolve_battle(&battle, &mut state.clone(), &units, &map).unwrap();
+        assert_eq!(resolution.raw_odds, preview.raw_odds);
+        assert_eq!(resolution.modified_odds, preview.modified_odds);
+        assert_eq!(resolution.result, preview.outcomes[0].result);
+    }
+
+    #[test]
+    fn test_preview_outcome_distribution() {
+        let (mut state, units, map) = battle_setup();
+        state.get_unit_mut("XLVII").unwrap().strength = UnitStrength::Full;
+        state.get_unit_mut("V").unwrap().strength = UnitStrength::Full;
+
+        // 9 + 6 against 4 is 3:1, shifted to 2:1
+        let attackers = vec!["XLVII".to_string(), "V".to_string()];
+        let preview = preview_battle(&attackers, "16", &state, &units, &map).unwrap();
+        assert_eq!(preview.modified_odds, "2:1");
+        let total: f64 = preview.outcomes.iter().map(|o| o.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Every die roll appears once, under the result the CRT gives it
+        let mut rolls: Vec<i32> = preview
+            .outcomes
+            .iter()
+            .flat_map(|o| o.die_rolls.clone())
+            .collect();
+        rolls.sort();
+        assert_eq!(rolls, vec![1, 2, 3, 4, 5, 6]);
+        for outcome in &preview.outcomes {
+            for &roll in &outcome.die_rolls {
+                assert_eq!(combat_results_table(roll, 1), outcome.result);
+            }
+        }
+
+        // AL on 1-2 costs the attackers a step, DRL on 6 costs the defender one
+        assert!((preview.expected_attacker_losses - 2.0 / 6.0).abs() < 1e-9);
+        assert!((preview.expected_defender_losses - 1.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_attacker_chooses_loss() {
+        let (mut state, units, _) = battle_setup();
+        state.get_unit_mut("XLVII").unwrap().strength = UnitStrength::Full;
+        let battle = BattleDeclaration {
+            attackers: vec!["XLVII".to_string(), "V".to_string()],
+            defender: "16".to_string(),
+            resolved: false,
+        };
+        let al = CombatResult::AL;
+
+        assert_eq!(
+            apply_combat_result(&al, &battle, Some("16"), &mut state, &units).unwrap_err(),
+            RuleError::NotAnAttacker("16".to_string())
+        );
+        apply_combat_result(&al, &battle, Some("V"), &mut state, &units).unwrap();
+        assert_eq!(state.get_unit("V").unwrap().strength, UnitStrength::Eliminated);
+        assert_eq!(state.get_unit("XLVII").unwrap().strength, UnitStrength::Full);
+
+        // Without a choice the first attacker declared takes the step
+        apply_combat_result(&al, &battle, None, &mut state, &units).unwrap();
+        assert_eq!(state.get_unit("XLVII").unwrap().strength, UnitStrength::Half);
+    }
+
+    #[test]
+    fn test_find_valid_attacks() {
+        let (mut state, units, map) = battle_setup();
+        state.get_unit_mut("XLVII").unwrap().strength = UnitStrength::Full;
+        state.get_unit_mut("V").unwrap().strength = UnitStrength::Full;
+        let options = find_valid_attacks(&state, &units, &map).unwrap();
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].defender, "16");
+        assert_eq!(options[0].eligible_attackers.len(), 2);
+        // Both units alone and together, the combined 2:1 attack first
+        assert_eq!(options[0].groups.len(), 3);
+        assert_eq!(options[0].groups[0].attackers.len(), 2);
+        assert_eq!(options[0].groups[0].modified_odds, "2:1");
+        assert_eq!(options[0].groups[2].modified_odds, "Below 1:1");
+
+        // A declared battle uses up its attackers and its defender
+        state.pending_battles.push(BattleDeclaration {
+            attackers: vec!["V".to_string()],
+            defender: "16".to_string(),
+            resolved: false,
+        });
+        assert!(find_valid_attacks(&state, &units, &map).unwrap().is_empty());
+
+        state.phase = crate::game_state::Phase::GermanMovement;
+        assert_eq!(
+            find_valid_attacks(&state, &units, &map).unwrap_err(),
+            RuleError::WrongPhase("combat")
+        );
+    }
 }
@@ -0,0 +1,623 @@
+use crate::error::RuleError;
+use crate::unit::Side;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sha2::{Digest, Sha256};
+
+/// Deterministic die rolls derived from a seed
+///
+/// Roll `n` (counting from 0) is the first eight bytes of SHA-256("{seed}:{n}") as a
+/// big-endian integer, modulo 6, plus 1. Anyone who knows the seed can recompute every
+/// roll, and the commitment (SHA-256 of the seed) pins the seed down without revealing it.
+///
+/// A commit–reveal game has no seed fixed in advance. Each player commits to the end of
+/// a hash chain (see [`chain_link`]) and, before every roll, reveals the link before the
+/// one they revealed last, naming the battle the roll is for. The roll uses the seed
+/// `"{german_link}:{soviet_link}:{defender}"`, so nobody, the server included, knows a
+/// roll until both players have sent their links for that battle, and neither player can
+/// choose theirs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Dice {
+    /// Kept off the wire so players cannot read upcoming rolls from the game state
+    #[serde(skip_serializing, default)]
+    seed: String,
+    /// SHA-256 of the seed, as hex
+    pub commitment: String,
+    /// Number of rolls made so far
+    pub rolls: u32,
+    /// Every roll made so far, in order
+    #[serde(default)]
+    pub history: Vec<i32>,
+    /// Hash chains of a commit–reveal game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange: Option<SeedExchange>,
+}
+
+/// The hash chains committed by each side, and the links spent so far
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SeedExchange {
+    pub german: Option<SeedShare>,
+    pub soviet: Option<SeedShare>,
+    /// Defender of the battle the next roll is for, once either side has revealed for it
+    #[serde(default)]
+    pub battle: Option<String>,
+    /// The links each roll used, in order
+    #[serde(default)]
+    pub links: Vec<RollLinks>,
+}
+
+impl SeedExchange {
+    fn share_mut(&mut self, side: Side) -> &mut Option<SeedShare> {
+        match side {
+            Side::German => &mut self.german,
+            Side::Soviet => &mut self.soviet,
+        }
+    }
+
+    fn is_committed(&self) -> bool {
+        self.german.is_some() && self.soviet.is_some()
+    }
+
+    /// The hash the side's next link must have: its last link, or the end of its chain
+    fn head(&self, side: Side) -> Option<String> {
+        let share = match side {
+            Side::German => self.german.as_ref()?,
+            Side::Soviet => self.soviet.as_ref()?,
+        };
+        Some(match self.links.last() {
+            Some(links) => links.link(side).to_string(),
+            None => share.commitment.clone(),
+        })
+    }
+
+    /// Spend both sides' revealed links on the roll for a battle
+    fn take_links(&mut self, defender: &str) -> Option<RollLinks> {
+        if self.battle.as_deref() != Some(defender) {
+            return None;
+        }
+        match (&mut self.german, &mut self.soviet) {
+            (Some(german), Some(soviet)) if german.revealed && soviet.revealed => {
+                let links = RollLinks {
+                    battle: defender.to_string(),
+                    german: std::mem::take(&mut german.link),
+                    soviet: std::mem::take(&mut soviet.link),
+                };
+                german.revealed = false;
+                soviet.revealed = false;
+                self.battle = None;
+                self.links.push(links.clone());
+                Some(links)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One player's hash chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SeedShare {
+    /// The end of the chain, as hex
+    pub commitment: String,
+    /// Whether the player has sent the link for the next roll
+    pub revealed: bool,
+    /// The link sent for the next roll, kept secret until the roll
+    #[serde(skip_serializing, default)]
+    link: String,
+}
+
+/// The links both sides spent on one roll
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RollLinks {
+    /// Defender of the battle rolled for
+    pub battle: String,
+    pub german: String,
+    pub soviet: String,
+}
+
+impl RollLinks {
+    fn link(&self, side: Side) -> &str {
+        match side {
+            Side::German => &self.german,
+            Side::Soviet => &self.soviet,
+        }
+    }
+
+    /// Seed the roll is derived from
+    pub fn seed(&self) -> String {
+        format!("{}:{}:{}", self.german, self.soviet, self.battle)
+    }
+}
+
+/// Everything needed to check a finished game's rolls, seeds included
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DiceAudit {
+    pub seed: String,
+    pub commitment: String,
+    /// Chain ends of a commit–reveal game, empty otherwise
+    pub shares: Vec<RevealedShare>,
+    pub rolls: Vec<AuditedRoll>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RevealedShare {
+    pub side: Side,
+    /// The end of the side's hash chain
+    pub commitment: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AuditedRoll {
+    pub index: u32,
+    pub die_roll: i32,
+    /// Links the roll used, in a commit–reveal game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<RollLinks>,
+}
+
+impl Dice {
+    pub fn from_seed(seed: &str) -> Self {
+        Dice {
+            seed: seed.to_string(),
+            commitment: commitment(seed),
+            rolls: 0,
+            history: Vec::new(),
+            exchange: None,
+        }
+    }
+
+    /// Dice rolled from hash chains both players commit to before the first roll
+    pub fn commit_reveal() -> Self {
+        Dice {
+            seed: String::new(),
+            commitment: String::new(),
+            rolls: 0,
+            history: Vec::new(),
+            exchange: Some(SeedExchange::default()),
+        }
+    }
+
+    /// Dice with a fresh random seed
+    pub fn random() -> Self {
+        let bytes: [u8; 16] = rand::thread_rng().gen();
+        Self::from_seed(&to_hex(&bytes))
+    }
+
+    /// Whether the dice can roll once the links for a battle are in
+    pub fn is_ready(&self) -> bool {
+        match self.exchange {
+            Some(ref exchange) => exchange.is_committed(),
+            None => true,
+        }
+    }
+
+    /// Roll the next die
+    pub fn roll(&mut self) -> Result<i32, RuleError> {
+        self.roll_for("")
+    }
+
+    /// Roll the die for the battle against `defender`
+    ///
+    /// A commit–reveal game rolls only once both sides have revealed their links for it.
+    pub fn roll_for(&mut self, defender: &str) -> Result<i32, RuleError> {
+        let value = match self.exchange {
+            Some(ref mut exchange) => {
+                let links = exchange
+                    .take_links(defender)
+                    .ok_or(RuleError::DiceNotReady)?;
+                roll_at(&links.seed(), self.rolls)
+            }
+            None => roll_at(&self.seed, self.rolls),
+        };
+        self.rolls += 1;
+        self.history.push(value);
+        Ok(value)
+    }
+
+    /// Commit a side to the end of its hash chain, given in hex
+    ///
+    /// Once both sides have committed, the two chain ends make up the game's seed.
+    pub fn commit(&mut self, side: Side, share_commitment: &str) -> Result<(), RuleError> {
+        let exchange = self.exchange.as_mut().ok_or(RuleError::NotCommitReveal)?;
+        let share_commitment = share_commitment.trim().to_ascii_lowercase();
+        if share_commitment.len() != 64 || !share_commitment.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(RuleError::InvalidCommitment);
+        }
+
+        let share = exchange.share_mut(side);
+        if share.is_some() {
+            return Err(RuleError::AlreadyCommitted(side));
+        }
+        *share = Some(SeedShare {
+            commitment: share_commitment,
+            revealed: false,
+            link: String::new(),
+        });
+
+        if let (Some(german), Some(soviet)) = (&exchange.german, &exchange.soviet) {
+            self.seed = combine_seeds(&german.commitment, &soviet.commitment);
+            self.commitment = commitment(&self.seed);
+        }
+        Ok(())
+    }
+
+    /// Reveal a side's next link for the roll of the battle against `defender`
+    ///
+    /// The link must hash to the side's previous link, or to its chain end before the
+    /// first roll. Both sides must name the same battle.
+    pub fn reveal(&mut self, side: Side, link: &str, defender: &str) -> Result<(), RuleError> {
+        let exchange = self.exchange.as_mut().ok_or(RuleError::NotCommitReveal)?;
+        if !exchange.is_committed() {
+            return Err(RuleError::NotAllCommitted);
+        }
+        if let Some(ref battle) = exchange.battle {
+            if battle != defender {
+                return Err(RuleError::RollPending(battle.clone()));
+            }
+        }
+
+        let head = exchange.head(side).expect("Both sides have committed");
+        let share = exchange
+            .share_mut(side)
+            .as_mut()
+            .expect("Both sides have committed");
+        if share.revealed {
+            return Err(RuleError::AlreadyRevealed(side));
+        }
+        if commitment(link) != head {
+            return Err(RuleError::SeedMismatch(side));
+        }
+        share.link = link.to_string();
+        share.revealed = true;
+        exchange.battle = Some(defender.to_string());
+        Ok(())
+    }
+
+    /// Disclose the seed and every roll so far, for checking after the game
+    pub fn audit(&self) -> DiceAudit {
+        let (shares, links) = match self.exchange {
+            Some(ref exchange) => {
+                let shares = [
+                    (Side::German, &exchange.german),
+                    (Side::Soviet, &exchange.soviet),
+                ]
+                .into_iter()
+                .filter_map(|(side, share)| {
+                    share.as_ref().map(|share| RevealedShare {
+                        side,
+                        commitment: share.commitment.clone(),
+                    })
+                })
+                .collect();
+                (shares, exchange.links.as_slice())
+            }
+            None => (Vec::new(), &[][..]),
+        };
+
+        DiceAudit {
+            seed: self.seed.clone(),
+            commitment: self.commitment.clone(),
+            shares,
+            rolls: self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(index, &die_roll)| AuditedRoll {
+                    index: index as u32,
+                    die_roll,
+                    links: links.get(index).cloned(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Check that a seed matches this game's commitment
+    pub fn verify_seed(&self, seed: &str) -> bool {
+        commitment(seed) == self.commitment
+    }
+}
+
+impl Default for Dice {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+/// Check an audit without trusting the server that produced it
+///
+/// The seed must match the game's commitment and, in a commit–reveal game, be built
+/// from both chain ends. Each roll's links must continue both chains, and every roll
+/// must follow from its seed.
+pub fn verify_audit(audit: &DiceAudit) -> Result<(), String> {
+    if commitment(&audit.seed) != audit.commitment {
+        return Err("The dice seed does not match the game's commitment".to_string());
+    }
+
+    let mut heads = match audit.shares.as_slice() {
+        [] => None,
+        [german, soviet] => {
+            if audit.seed != combine_seeds(&german.commitment, &soviet.commitment) {
+                return Err("The dice seed is not built from both players' chains".to_string());
+            }
+            Some([german.commitment.clone(), soviet.commitment.clone()])
+        }
+        _ => return Err("Only one side committed to a hash chain".to_string()),
+    };
+
+    for roll in &audit.rolls {
+        let seed = match (heads.as_mut(), &roll.links) {
+            (None, _) => audit.seed.clone(),
+            (Some([german_head, soviet_head]), Some(links)) => {
+                for (side, head, link) in [
+                    (Side::German, german_head, &links.german),
+                    (Side::Soviet, soviet_head, &links.soviet),
+                ] {
+                    if commitment(link) != *head {
+                        return Err(format!(
+                            "The {:?} link of roll {} does not continue its chain",
+                            side,
+                            roll.index + 1
+                        ));
+                    }
+                    *head = link.clone();
+                }
+                links.seed()
+            }
+            (Some(_), None) => return Err(format!("Roll {} has no links", roll.index + 1)),
+        };
+
+        let expected = roll_at(&seed, roll.index);
+        if roll.die_roll != expected {
+            return Err(format!(
+                "Roll {} was {} but the seed gives {}",
+                roll.index + 1,
+                roll.die_roll,
+                expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Link `n` of the hash chain grown from a secret
+///
+/// Link 0 is the secret and each later link is the SHA-256 (as hex) of the one before.
+/// A player commits to link `N` and reveals links `N - 1`, `N - 2`, ... for successive
+/// rolls, so a chain of `N` links lasts `N` rolls.
+pub fn chain_link(secret: &str, n: u32) -> String {
+    (0..n).fold(secret.to_string(), |link, _| commitment(&link))
+}
+
+/// Seed of a commit–reveal game, built from both sides' chain ends
+pub fn combine_seeds(german: &str, soviet: &str) -> String {
+    format!("{}:{}", german, soviet)
+}
+
+/// Commitment to a seed: SHA-256 of the seed, as hex
+pub fn commitment(seed: &str) -> String {
+    sha256_hex(seed.as_bytes())
+}
+
+/// The die roll with the given index for a seed
+pub fn roll_at(seed: &str, index: u32) -> i32 {
+    let digest = Sha256::digest(format!("{}:{}", seed, index).as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) % 6) as i32 + 1
+}
+
+/// SHA-256 of some bytes, as lowercase hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolls_are_deterministic() {
+        let mut a = Dice::from_seed("moscow-1941");
+        let mut b = Dice::from_seed("moscow-1941");
+        let rolls_a: Vec<i32> = (0..20).map(|_| a.roll().unwrap()).collect();
+        let rolls_b: Vec<i32> = (0..20).map(|_| b.roll().unwrap()).collect();
+
+        assert_eq!(rolls_a, rolls_b);
+        assert!(rolls_a.iter().all(|r| (1..=6).contains(r)));
+        assert_eq!(a.rolls, 20);
+        assert_eq!(rolls_a[3], roll_at("moscow-1941", 3));
+
+        let mut c = Dice::from_seed("kalinin-1941");
+        let rolls_c: Vec<i32> = (0..20).map(|_| c.roll().unwrap()).collect();
+        assert_ne!(rolls_a, rolls_c);
+    }
+
+    #[test]
+    fn test_rolls_cover_all_faces() {
+        let mut counts = [0; 6];
+        for i in 0..600 {
+            counts[(roll_at("faces", i) - 1) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&n| n > 60), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_commitment() {
+        let dice = Dice::from_seed("abc");
+        assert_eq!(
+            dice.commitment,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(dice.verify_seed("abc"));
+        assert!(!dice.verify_seed("abd"));
+
+        // The seed never leaves the server in serialized state
+        let json = serde_json::to_value(&dice).unwrap();
+        assert!(json.get("seed").is_none());
+    }
+
+    /// Commit-reveal dice with both chains committed, each `length` links long
+    fn committed(length: u32) -> Dice {
+        let mut dice = Dice::commit_reveal();
+        dice.commit(Side::German, &chain_link("g", length)).unwrap();
+        dice.commit(Side::Soviet, &chain_link("s", length)).unwrap();
+        dice
+    }
+
+    #[test]
+    fn test_commit_reveal() {
+        let mut dice = Dice::commit_reveal();
+        assert!(dice.roll_for("3").is_err());
+
+        // Revealing before both sides commit would let the second side choose its chain
+        dice.commit(Side::German, &chain_link("german-secret", 5))
+            .unwrap();
+        assert_eq!(
+            dice.reveal(Side::German, &chain_link("german-secret", 4), "3"),
+            Err(RuleError::NotAllCommitted)
+        );
+        assert!(dice.commit(Side::German, &commitment("another")).is_err());
+        assert!(dice.commit(Side::Soviet, "not-a-hash").is_err());
+        dice.commit(Side::Soviet, &chain_link("soviet-secret", 5))
+            .unwrap();
+        assert!(dice.is_ready());
+        assert_eq!(
+            dice.commitment,
+            commitment(&combine_seeds(
+                &chain_link("german-secret", 5),
+                &chain_link("soviet-secret", 5)
+            ))
+        );
+
+        assert_eq!(
+            dice.reveal(Side::Soviet, &chain_link("soviet-secret", 3), "3"),
+            Err(RuleError::SeedMismatch(Side::Soviet))
+        );
+        dice.reveal(Side::Soviet, &chain_link("soviet-secret", 4), "3")
+            .unwrap();
+        assert_eq!(dice.roll_for("3"), Err(RuleError::DiceNotReady));
+        assert_eq!(
+            dice.reveal(Side::German, &chain_link("german-secret", 4), "5"),
+            Err(RuleError::RollPending("3".to_string()))
+        );
+        dice.reveal(Side::German, &chain_link("german-secret", 4), "3")
+            .unwrap();
+
+        // Links stay secret in serialized state until the roll
+        let json = serde_json::to_value(&dice).unwrap();
+        assert!(json["exchange"]["german"]["revealed"].as_bool().unwrap());
+        assert!(json["exchange"]["german"].get("link").is_none());
+
+        // The links are spent on the battle they were revealed for
+        assert_eq!(dice.roll_for("5"), Err(RuleError::DiceNotReady));
+        let seed = format!(
+            "{}:{}:3",
+            chain_link("german-secret", 4),
+            chain_link("soviet-secret", 4)
+        );
+        assert_eq!(dice.roll_for("3"), Ok(roll_at(&seed, 0)));
+        assert_eq!(dice.roll_for("3"), Err(RuleError::DiceNotReady));
+
+        // The next roll needs the next links down each chain
+        assert_eq!(
+            dice.reveal(Side::German, &chain_link("german-secret", 4), "5"),
+            Err(RuleError::SeedMismatch(Side::German))
+        );
+        dice.reveal(Side::German, &chain_link("german-secret", 3), "5")
+            .unwrap();
+        assert_eq!(
+            dice.reveal(Side::German, &chain_link("german-secret", 3), "5"),
+            Err(RuleError::AlreadyRevealed(Side::German))
+        );
+        dice.reveal(Side::Soviet, &chain_link("soviet-secret", 3), "5")
+            .unwrap();
+        let seed = format!(
+            "{}:{}:5",
+            chain_link("german-secret", 3),
+            chain_link("soviet-secret", 3)
+        );
+        assert_eq!(dice.roll_for("5"), Ok(roll_at(&seed, 1)));
+        assert_eq!(dice.history.len(), 2);
+    }
+
+    #[test]
+    fn test_server_cannot_foresee_rolls() {
+        // Each roll depends on links neither side has sent when the chains are committed,
+        // so knowing both chain ends tells the server nothing about the rolls
+        let mut played = committed(3);
+        let mut actual = Vec::new();
+        for n in (0..3).rev() {
+            let defender = format!("{}", n + 1);
+            played
+                .reveal(Side::German, &chain_link("g", n), &defender)
+                .unwrap();
+            played
+                .reveal(Side::Soviet, &chain_link("s", n), &defender)
+                .unwrap();
+            actual.push(played.roll_for(&defender).unwrap());
+        }
+        assert_eq!(
+            actual,
+            vec![
+                roll_at(
+                    &format!("{}:{}:3", chain_link("g", 2), chain_link("s", 2)),
+                    0
+                ),
+                roll_at(
+                    &format!("{}:{}:2", chain_link("g", 1), chain_link("s", 1)),
+                    1
+                ),
+                roll_at("g:s:1", 2),
+            ]
+        );
+
+        // A chain of three links lasts three rolls
+        assert_eq!(
+            played.reveal(Side::German, "anything", "1"),
+            Err(RuleError::SeedMismatch(Side::German))
+        );
+    }
+
+    #[test]
+    fn test_verify_audit() {
+        let mut dice = committed(10);
+        for n in (0..10).rev() {
+            let defender = format!("{}", n % 3 + 1);
+            dice.reveal(Side::German, &chain_link("g", n), &defender)
+                .unwrap();
+            dice.reveal(Side::Soviet, &chain_link("s", n), &defender)
+                .unwrap();
+            dice.roll_for(&defender).unwrap();
+        }
+
+        let audit = dice.audit();
+        assert_eq!(audit.shares.len(), 2);
+        assert_eq!(audit.rolls.len(), 10);
+        assert_eq!(audit.rolls[9].links.as_ref().unwrap().german, "g");
+        assert_eq!(verify_audit(&audit), Ok(()));
+
+        let mut altered = audit.clone();
+        altered.rolls[4].die_roll = altered.rolls[4].die_roll % 6 + 1;
+        assert!(verify_audit(&altered).unwrap_err().starts_with("Roll 5"));
+
+        let mut relinked = audit.clone();
+        relinked.rolls[4].links.as_mut().unwrap().soviet = "other".to_string();
+        assert!(verify_audit(&relinked)
+            .unwrap_err()
+            .contains("Soviet link of roll 5"));
+
+        let mut swapped = audit.clone();
+        swapped.shares[1].commitment = commitment("other");
+        assert!(verify_audit(&swapped).is_err());
+
+        let mut reseeded = audit;
+        reseeded.seed = "g:other".to_string();
+        reseeded.commitment = commitment("g:other");
+        assert!(verify_audit(&reseeded).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::hex::Hex;
+use crate::seats::Seat;
+use crate::unit::Side;
+use std::fmt;
+
+/// Why the engine refused a request
+///
+/// Every variant has a stable `code` that clients can match on; the `Display` text is
+/// for people and may change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    // Lookups
+    UnitNotFound(String),
+    NotOnMap(String),
+    BattleNotFound(usize),
+
+    // Turn and phase
+    NotYourTurn,
+    WrongPhase(&'static str),
+
+    // Movement
+    PanzersOnly,
+    NotOnRail,
+    AlreadyMoved,
+    Unreachable(Hex),
+
+    // Replacements
+    FullStrength,
+    ReplacementUsed,
+    NoReplacementsLeft,
+    InvalidReplacementHex,
+    HexOccupied,
+    NotInReplacementHex,
+
+    // Combat
+    NoAttackers,
+    NotAdjacent(String),
+    NotAnEnemy(String),
+    AlreadyInBattle(String),
+    NotAnAttacker(String),
+    BattleAlreadyResolved(usize),
+
+    // Retreats
+    InvalidRetreatHex(Hex),
+    NoRetreatPending(String),
+
+    // Dice
+    DiceNotReady,
+    NotCommitReveal,
+    InvalidCommitment,
+    AlreadyCommitted(Side),
+    NotAllCommitted,
+    AlreadyRevealed(Side),
+    SeedMismatch(Side),
+    RollPending(String),
+    RollNotNeeded(String),
+    GameNotOver,
+
+    // Seats
+    SeatTaken(Seat),
+    UnknownSeatToken,
+    SeatTokenRequired(Side),
+    WrongSeat(Seat, Side),
+    NotSeated,
+    ComputerSeat(Side),
+
+    // Turn files
+    InvalidTurnFile(String),
+    NoTurnRecorded,
+}
+
+impl RuleError {
+    /// Stable identifier for the error, in snake_case
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuleError::UnitNotFound(_) => "unit_not_found",
+            RuleError::NotOnMap(_) => "not_on_map",
+            RuleError::BattleNotFound(_) => "battle_not_found",
+            RuleError::NotYourTurn => "not_your_turn",
+            RuleError::WrongPhase(_) => "wrong_phase",
+            RuleError::PanzersOnly => "panzers_only",
+            RuleError::NotOnRail => "not_on_rail",
+            RuleError::AlreadyMoved => "already_moved",
+            RuleError::Unreachable(_) => "unreachable",
+            RuleError::FullStrength => "full_strength",
+            RuleError::ReplacementUsed => "replacement_used",
+            RuleError::NoReplacementsLeft => "no_replacements_left",
+            RuleError::InvalidReplacementHex => "invalid_replacement_hex",
+            RuleError::HexOccupied => "hex_occupied",
+            RuleError::NotInReplacementHex => "not_in_replacement_hex",
+            RuleError::NoAttackers => "no_attackers",
+            RuleError::NotAdjacent(_) => "not_adjacent",
+            RuleError::NotAnEnemy(_) => "not_an_enemy",
+            RuleError::AlreadyInBattle(_) => "already_in_battle",
+            RuleError::NotAnAttacker(_) => "not_an_attacker",
+            RuleError::BattleAlreadyResolved(_) => "battle_already_resolved",
+            RuleError::InvalidRetreatHex(_) => "invalid_retreat_hex",
+            RuleError::NoRetreatPending(_) => "no_retreat_pending",
+            RuleError::DiceNotReady => "dice_not_ready",
+            RuleError::NotCommitReveal => "not_commit_reveal",
+            RuleError::InvalidCommitment => "invalid_commitment",
+            RuleError::AlreadyCommitted(_) => "already_committed",
+            RuleError::NotAllCommitted => "not_all_committed",
+            RuleError::AlreadyRevealed(_) => "already_revealed",
+            RuleError::SeedMismatch(_) => "seed_mismatch",
+            RuleError::RollPending(_) => "roll_pending",
+            RuleError::RollNotNeeded(_) => "roll_not_needed",
+            RuleError::GameNotOver => "game_not_over",
+            RuleError::SeatTaken(_) => "seat_taken",
+            RuleError::UnknownSeatToken => "unknown_seat_token",
+            RuleError::SeatTokenRequired(_) => "seat_token_required",
+            RuleError::WrongSeat(..) => "wrong_seat",
+            RuleError::NotSeated => "not_seated",
+            RuleError::ComputerSeat(_) => "computer_seat",
+            RuleError::InvalidTurnFile(_) => "invalid_turn_file",
+            RuleError::NoTurnRecorded => "no_turn_recorded",
+        }
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::UnitNotFound(id) => write!(f, "Unit {} not found", id),
+            RuleError::NotOnMap(id) => write!(f, "Unit {} is not on the map", id),
+            RuleError::BattleNotFound(index) => write!(f, "Invalid battle index {}", index),
+            RuleError::NotYourTurn => write!(f, "Not your turn"),
+            RuleError::WrongPhase(phase) => write!(f, "Not a {} phase", phase),
+            RuleError::PanzersOnly => write!(f, "Only panzers can move in panzer phase"),
+            RuleError::NotOnRail => write!(f, "Unit not on rail line"),
+            RuleError::AlreadyMoved => write!(f, "Unit has already moved this phase"),
+            RuleError::Unreachable(hex) => write!(f, "Cannot reach hex ({}, {})", hex.q, hex.r),
+            RuleError::FullStrength => write!(
+                f,
+                "Unit is at full strength and cannot receive replacements"
+            ),
+            RuleError::ReplacementUsed => write!(f, "German replacement already used this turn"),
+            RuleError::NoReplacementsLeft => write!(f, "No Soviet replacements remaining"),
+            RuleError::InvalidReplacementHex => write!(
+                f,
+                "Invalid replacement hex - must be a controlled city with communication"
+            ),
+            RuleError::HexOccupied => write!(f, "Hex is occupied by another unit"),
+            RuleError::NotInReplacementHex => write!(
+                f,
+                "Unit must be in a valid replacement hex or be placed in one"
+            ),
+            RuleError::NoAttackers => write!(f, "A battle needs at least one attacker"),
+            RuleError::NotAdjacent(id) => write!(f, "Unit {} is not next to the defender", id),
+            RuleError::NotAnEnemy(id) => write!(f, "Unit {} is not an enemy unit", id),
+            RuleError::AlreadyInBattle(id) => {
+                write!(f, "Unit {} is already in a battle this phase", id)
+            }
+            RuleError::NotAnAttacker(id) => {
+                write!(f, "Unit {} is not attacking in this battle", id)
+            }
+            RuleError::BattleAlreadyResolved(index) => {
+                write!(f, "Battle {} has already been resolved", index)
+            }
+            RuleError::InvalidRetreatHex(hex) => {
+                write!(f, "Invalid retreat hex ({}, {})", hex.q, hex.r)
+            }
+            RuleError::NoRetreatPending(id) => {
+                write!(f, "Unit {} has no combat result to retreat from", id)
+            }
+            RuleError::DiceNotReady => write!(
+                f,
+                "Both sides must reveal their links for this battle before the die is rolled"
+            ),
+            RuleError::NotCommitReveal => write!(f, "This game does not use commit-reveal dice"),
+            RuleError::InvalidCommitment => {
+                write!(f, "A commitment is a SHA-256 hash written as 64 hex digits")
+            }
+            RuleError::AlreadyCommitted(side) => {
+                write!(f, "The {:?} side has already committed a seed", side)
+            }
+            RuleError::NotAllCommitted => write!(f, "Both sides must commit before either reveals"),
+            RuleError::AlreadyRevealed(side) => {
+                write!(
+                    f,
+                    "The {:?} side has already revealed its link for this roll",
+                    side
+                )
+            }
+            RuleError::SeedMismatch(side) => {
+                write!(f, "Link does not continue the {:?} hash chain", side)
+            }
+            RuleError::RollPending(defender) => {
+                write!(
+                    f,
+                    "Links are already being revealed for the battle against {}",
+                    defender
+                )
+            }
+            RuleError::RollNotNeeded(defender) => {
+                write!(f, "No pending battle against {} needs a die roll", defender)
+            }
+            RuleError::GameNotOver => {
+                write!(f, "Dice seeds are disclosed only once the game is over")
+            }
+            RuleError::SeatTaken(seat) => write!(f, "The {:?} seat is already taken", seat),
+            RuleError::UnknownSeatToken => write!(f, "Unknown seat token"),
+            RuleError::SeatTokenRequired(side) => {
+                write!(f, "The {:?} side requires its seat token", side)
+            }
+            RuleError::WrongSeat(seat, side) => {
+                write!(f, "Seat {:?} cannot act for the {:?} side", seat, side)
+            }
+            RuleError::NotSeated => write!(f, "Only a seated player can do this"),
+            RuleError::ComputerSeat(side) => {
+                write!(f, "The {:?} side is played by the computer", side)
+            }
+            RuleError::InvalidTurnFile(reason) => write!(f, "{}", reason),
+            RuleError::NoTurnRecorded => write!(f, "No actions recorded yet"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_and_message() {
+        let err = RuleError::Unreachable(Hex::new(3, 4));
+        assert_eq!(err.code(), "unreachable");
+        assert_eq!(err.to_string(), "Cannot reach hex (3, 4)");
+
+        let err = RuleError::WrongPhase("movement");
+        assert_eq!(err.code(), "wrong_phase");
+        assert_eq!(err.to_string(), "Not a movement phase");
+    }
+}
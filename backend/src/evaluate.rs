@@ -0,0 +1,281 @@
+use crate::game_state::GameState;
+use crate::hex::Hex;
+use crate::map::Map;
+use crate::mcts::playout_chances;
+use crate::replacement::can_trace_communication;
+use crate::unit::{Side, Units};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Weight of holding Moscow
+const MOSCOW_CONTROL_WEIGHT: f64 = 3.0;
+/// Weight of the German spearhead's closeness to Moscow
+const MOSCOW_DISTANCE_WEIGHT: f64 = 1.0;
+/// Weight of the German share of combat strength above or below half
+const STRENGTH_WEIGHT: f64 = 2.0;
+/// Weight of the difference in units with communication to their map edge
+const SUPPLY_WEIGHT: f64 = 0.5;
+/// Weight of the difference in units standing next to a friendly unit
+const FRONT_WEIGHT: f64 = 0.25;
+/// Score difference that multiplies the odds of winning by e
+const WIN_PROBABILITY_SCALE: f64 = 1.5;
+
+/// Most playouts an assessment may ask for, keeping one request's work bounded
+pub const MAX_PLAYOUTS: usize = 100;
+
+/// One contribution to a position's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Factor {
+    pub name: String,
+    /// The measurement, from the German point of view
+    pub value: f64,
+    /// What it adds to the German score
+    pub contribution: f64,
+}
+
+/// How a position stands, scored from the German point of view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PositionEvaluation {
+    /// Positive when the Germans are ahead, negative when the Soviets are
+    pub score: f64,
+    pub moscow_controller: Option<Side>,
+    /// Hexes from the nearest German unit to Moscow
+    pub moscow_distance: Option<i32>,
+    pub factors: Vec<Factor>,
+}
+
+impl PositionEvaluation {
+    /// The score as seen by one side
+    pub fn score_for(&self, side: Side) -> f64 {
+        match side {
+            Side::German => self.score,
+            Side::Soviet => -self.score,
+        }
+    }
+
+    /// Chance of `side` winning, read from the score through a logistic curve
+    pub fn win_probability(&self, side: Side) -> f64 {
+        1.0 / (1.0 + (-self.score_for(side) / WIN_PROBABILITY_SCALE).exp())
+    }
+}
+
+/// Who is winning: the evaluation with each side's score and chance of winning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Assessment {
+    pub evaluation: PositionEvaluation,
+    pub german_score: f64,
+    pub soviet_score: f64,
+    pub german_win_probability: f64,
+    pub soviet_win_probability: f64,
+    /// Games played out to estimate the chances; 0 when they are read from the score
+    pub playouts: usize,
+}
+
+/// Assess a position from the evaluation alone, or from `playouts` random games
+///
+/// Playouts are capped at `MAX_PLAYOUTS` and seeded with `seed`, so the same position
+/// and seed always give the same chances.
+pub fn assess_position(
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+    playouts: usize,
+    seed: u64,
+) -> Assessment {
+    let evaluation = evaluate_position(state, units, map);
+    let playouts = playouts.min(MAX_PLAYOUTS);
+    let (german_win_probability, soviet_win_probability) = if playouts == 0 {
+        (
+            evaluation.win_probability(Side::German),
+            evaluation.win_probability(Side::Soviet),
+        )
+    } else {
+        playout_chances(state, units, map, playouts, seed)
+    };
+
+    Assessment {
+        german_score: evaluation.score_for(Side::German),
+        soviet_score: evaluation.score_for(Side::Soviet),
+        german_win_probability,
+        soviet_win_probability,
+        playouts,
+        evaluation,
+    }
+}
+
+/// Per-side tallies of the units on the map
+#[derive(Default)]
+struct SideTally {
+    strength: i32,
+    on_map: usize,
+    supplied: usize,
+    in_line: usize,
+}
+
+impl SideTally {
+    fn share(count: usize, of: usize) -> f64 {
+        if of == 0 {
+            0.0
+        } else {
+            count as f64 / of as f64
+        }
+    }
+}
+
+/// Score a position by Moscow control, the German distance to Moscow, the strength ratio,
+/// communication and how well each side's units hold together
+pub fn evaluate_position(state: &GameState, units: &Units, map: &Map) -> PositionEvaluation {
+    let mut german = SideTally::default();
+    let mut soviet = SideTally::default();
+    let mut german_hexes: Vec<Hex> = Vec::new();
+
+    for unit_state in &state.units {
+        let (Some(hex), Some(def)) = (unit_state.hex(), units.get(&unit_state.id)) else {
+            continue;
+        };
+        let tally = match def.side {
+            Side::German => {
+                german_hexes.push(hex);
+                &mut german
+            }
+            Side::Soviet => &mut soviet,
+        };
+        tally.strength += def.get_combat_strength(&unit_state.strength);
+        tally.on_map += 1;
+        if can_trace_communication(&hex, def.side, state, units, map) {
+            tally.supplied += 1;
+        }
+        let has_neighbour = state.units.iter().any(|other| {
+            other.id != unit_state.id
+                && other.hex().is_some_and(|h| h.is_adjacent(&hex))
+                && units.get(&other.id).is_some_and(|d| d.side == def.side)
+        });
+        if has_neighbour {
+            tally.in_line += 1;
+        }
+    }
+
+    let moscow_controller = state.get_city_control("Moscow");
+    let moscow_distance = map
+        .hexes
+        .iter()
+        .filter(|h| h.city.as_ref().is_some_and(|c| c.is_moscow))
+        .flat_map(|h| german_hexes.iter().map(move |g| g.distance(&h.hex())))
+        .min();
+
+    let total_strength = german.strength + soviet.strength;
+    let strength_share = if total_strength == 0 {
+        0.5
+    } else {
+        german.strength as f64 / total_strength as f64
+    };
+
+    let measurements = [
+        (
+            "moscow_control",
+            if moscow_controller == Some(Side::German) {
+                1.0
+            } else {
+                0.0
+            },
+            MOSCOW_CONTROL_WEIGHT,
+        ),
+        (
+            "moscow_distance",
+            moscow_distance.map_or(0.0, |d| 1.0 / (1.0 + d as f64)),
+            MOSCOW_DISTANCE_WEIGHT,
+        ),
+        ("strength", strength_share - 0.5, STRENGTH_WEIGHT),
+        (
+            "supply",
+            SideTally::share(german.supplied, german.on_map)
+                - SideTally::share(soviet.supplied, soviet.on_map),
+            SUPPLY_WEIGHT,
+        ),
+        (
+            "front",
+            SideTally::share(german.in_line, german.on_map)
+                - SideTally::share(soviet.in_line, soviet.on_map),
+            FRONT_WEIGHT,
+        ),
+    ];
+    let factors: Vec<Factor> = measurements
+        .into_iter()
+        .map(|(name, value, weight)| Factor {
+            name: name.to_string(),
+            value,
+            contribution: value * weight,
+        })
+        .collect();
+
+    PositionEvaluation {
+        score: factors.iter().map(|f| f.contribution).sum(),
+        moscow_controller,
+        moscow_distance,
+        factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::bundled;
+    use crate::unit::{UnitState, UnitStrength};
+
+    #[test]
+    fn test_evaluate_position() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("evaluate-test");
+        state.place_initial_units(&units, &map);
+
+        let start = evaluate_position(&state, &units, &map);
+        assert_eq!(start.moscow_controller, Some(Side::Soviet));
+        assert_eq!(start.moscow_distance, Some(3));
+        let total: f64 = start.factors.iter().map(|f| f.contribution).sum();
+        assert!((start.score - total).abs() < 1e-9);
+        assert_eq!(start.score_for(Side::Soviet), -start.score);
+        let odds = start.win_probability(Side::German) + start.win_probability(Side::Soviet);
+        assert!((odds - 1.0).abs() < 1e-9);
+
+        // Closing on Moscow helps the Germans
+        state.get_unit_mut("V").unwrap().move_to(Hex::new(2, 0));
+        let closer = evaluate_position(&state, &units, &map);
+        assert_eq!(closer.moscow_distance, Some(1));
+        assert!(closer.score > start.score);
+
+        // So does taking it
+        state.get_unit_mut("3").unwrap().take_loss();
+        state.get_unit_mut("3").unwrap().take_loss();
+        state.update_city_control("Moscow", Side::German);
+        let taken = evaluate_position(&state, &units, &map);
+        assert!(taken.score > closer.score + MOSCOW_CONTROL_WEIGHT);
+
+        // Losing strength hurts
+        state.units.push(UnitState::new(
+            "5".to_string(),
+            Some(Hex::new(3, 0)),
+            UnitStrength::Full,
+        ));
+        assert!(evaluate_position(&state, &units, &map).score < taken.score);
+    }
+
+    #[test]
+    fn test_assess_position() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("assess-test");
+        state.place_initial_units(&units, &map);
+
+        let heuristic = assess_position(&state, &units, &map, 0, 0);
+        assert_eq!(heuristic.playouts, 0);
+        assert_eq!(heuristic.soviet_score, -heuristic.german_score);
+        assert_eq!(
+            heuristic.german_win_probability,
+            heuristic.evaluation.win_probability(Side::German)
+        );
+
+        let played = assess_position(&state, &units, &map, MAX_PLAYOUTS + 1, 1);
+        assert_eq!(played.playouts, MAX_PLAYOUTS);
+        assert!(played.german_win_probability + played.soviet_win_probability <= 1.0 + 1e-9);
+        assert_eq!(played.evaluation, heuristic.evaluation);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::combat::CombatResult;
+use crate::game_state::Phase;
+use crate::hex::Hex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use tokio::sync::broadcast;
+
+/// Live events buffered per subscriber before it is considered lagging
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Something that happened in the game, pushed to subscribers as it happens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    GameStarted,
+    UnitMoved {
+        unit_id: String,
+        from: Option<Hex>,
+        to: Hex,
+    },
+    BattleDeclared {
+        battle_index: usize,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    },
+    DieRolled {
+        battle_index: usize,
+        die_roll: i32,
+        odds: String,
+        result: CombatResult,
+    },
+    UnitRetreated {
+        unit_id: String,
+        to: Hex,
+    },
+    ReplacementApplied {
+        unit_id: String,
+        hex: Option<Hex>,
+    },
+    PhaseAdvanced {
+        turn: i32,
+        phase: Phase,
+    },
+}
+
+impl GameEvent {
+    /// Event name used as the SSE `event:` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::GameStarted => "game_started",
+            GameEvent::UnitMoved { .. } => "unit_moved",
+            GameEvent::BattleDeclared { .. } => "battle_declared",
+            GameEvent::DieRolled { .. } => "die_rolled",
+            GameEvent::UnitRetreated { .. } => "unit_retreated",
+            GameEvent::ReplacementApplied { .. } => "replacement_applied",
+            GameEvent::PhaseAdvanced { .. } => "phase_advanced",
+        }
+    }
+}
+
+/// An event with its sequence number, starting at 1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EventRecord {
+    pub id: u64,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+/// Ordered history of game events with a channel for live subscribers
+pub struct EventLog {
+    records: Vec<EventRecord>,
+    sender: broadcast::Sender<EventRecord>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        EventLog {
+            records: Vec::new(),
+            sender,
+        }
+    }
+
+    /// Record an event and send it to live subscribers, returning its ID
+    pub fn push(&mut self, event: GameEvent) -> u64 {
+        let record = EventRecord {
+            id: self.records.len() as u64 + 1,
+            event,
+        };
+        let id = record.id;
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(record.clone());
+        self.records.push(record);
+        id
+    }
+
+    /// ID of the most recent event, or 0 if there are none
+    pub fn last_id(&self) -> u64 {
+        self.records.len() as u64
+    }
+
+    /// All events after the given ID
+    pub fn since(&self, id: u64) -> Vec<EventRecord> {
+        self.records
+            .get(id.min(self.last_id()) as usize..)
+            .unwrap_or_default()
+            .to_vec()
+    }
+
+    /// Receive events pushed from now on
+    ///
+    /// Subscribe and read the backlog with `since` under the same lock so no event is missed.
+    pub fn subscribe(&self) -> broadcast::Receiver<EventRecord> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(unit_id: &str) -> GameEvent {
+        GameEvent::UnitMoved {
+            unit_id: unit_id.to_string(),
+            from: Some(Hex::new(0, 0)),
+            to: Hex::new(1, 0),
+        }
+    }
+
+    #[test]
+    fn test_catch_up_since() {
+        let mut log = EventLog::new();
+        assert_eq!(log.push(GameEvent::GameStarted), 1);
+        assert_eq!(log.push(moved("XLVII")), 2);
+        assert_eq!(log.push(moved("16A")), 3);
+
+        let ids: Vec<u64> = log.since(1).iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(log.since(0).len(), 3);
+        assert!(log.since(3).is_empty());
+        assert!(log.since(99).is_empty());
+    }
+
+    #[test]
+    fn test_subscribers_receive_new_events() {
+        let mut log = EventLog::new();
+        log.push(GameEvent::GameStarted);
+        let mut receiver = log.subscribe();
+        log.push(moved("XLVII"));
+
+        let record = receiver.try_recv().unwrap();
+        assert_eq!(record.id, 2);
+        assert_eq!(record.event, moved("XLVII"));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_event_json() {
+        let record = EventRecord {
+            id: 7,
+            event: GameEvent::PhaseAdvanced {
+                turn: 2,
+                phase: Phase::SovietMovement,
+            },
+        };
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["type"], "phase_advanced");
+        assert_eq!(json["turn"], 2);
+        assert_eq!(record.event.name(), "phase_advanced");
+    }
+}
@@ -1,9 +1,12 @@
+use crate::dice::Dice;
 use crate::hex::Hex;
-use crate::unit::{Side, UnitState};
+use crate::map::{Map, SetupMarker};
+use crate::unit::{Side, UnitState, UnitStrength, Units};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Phase {
     GermanReplacement,
@@ -31,6 +34,20 @@ impl Phase {
         }
     }
 
+    /// Get the phase name as printed on the turn sequence
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Phase::GermanReplacement => "German Replacement",
+            Phase::GermanPanzerMovement => "German Panzer Movement",
+            Phase::GermanCombat => "German Combat",
+            Phase::GermanMovement => "German Movement",
+            Phase::SovietReplacement => "Soviet Replacement",
+            Phase::SovietRailMovement => "Soviet Rail Movement",
+            Phase::SovietCombat => "Soviet Combat",
+            Phase::SovietMovement => "Soviet Movement",
+        }
+    }
+
     /// Check if this is a movement phase
     pub fn is_movement_phase(&self) -> bool {
         matches!(
@@ -67,39 +84,68 @@ impl Phase {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BattleDeclaration {
     pub attackers: Vec<String>,
     pub defender: String,
     pub resolved: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct GameState {
+    /// Identifies the game in exchanged turn files
+    #[serde(default)]
+    pub game_id: String,
     pub turn: i32,
     pub phase: Phase,
     pub units: Vec<UnitState>,
     pub city_control: HashMap<String, Side>,
     pub pending_battles: Vec<BattleDeclaration>,
+    /// Defenders a DR or DRL result has yet to retreat
+    #[serde(default)]
+    pub pending_retreats: Vec<String>,
     pub moved_this_phase: HashSet<String>,
     pub german_replacement_used: bool,
     pub soviet_replacements_remaining: i32,
     pub first_shock_army_available: bool,
+    #[serde(default)]
+    pub dice: Dice,
 }
 
 impl GameState {
     /// Create a new game state with initial setup
     pub fn new() -> Self {
+        Self::with_dice(Dice::random())
+    }
+
+    /// Create a new game whose die rolls come from an agreed seed
+    pub fn with_seed(seed: &str) -> Self {
+        Self::with_dice(Dice::from_seed(seed))
+    }
+
+    /// Create a new game whose dice seed both players build by commit–reveal
+    pub fn with_commit_reveal() -> Self {
         GameState {
+            dice: Dice::commit_reveal(),
+            ..Self::new()
+        }
+    }
+
+    fn with_dice(dice: Dice) -> Self {
+        GameState {
+            // Derived from the dice commitment so players sharing a seed share the ID
+            game_id: dice.commitment[..16].to_string(),
             turn: 1,
             phase: Phase::GermanPanzerMovement, // Skip replacement on turn 1
             units: Vec::new(),
             city_control: HashMap::new(),
             pending_battles: Vec::new(),
+            pending_retreats: Vec::new(),
             moved_this_phase: HashSet::new(),
             german_replacement_used: false,
             soviet_replacements_remaining: 0,
             first_shock_army_available: false,
+            dice,
         }
     }
 
@@ -130,6 +176,7 @@ impl GameState {
         self.phase = next_phase;
         self.moved_this_phase.clear();
         self.pending_battles.clear();
+        self.pending_retreats.clear();
 
         // Reset replacement counters at the start of replacement phases
         match self.phase {
@@ -148,6 +195,52 @@ impl GameState {
         }
     }
 
+    /// Place units on their setup hexes and give cities to the side holding them
+    pub fn place_initial_units(&mut self, units: &Units, map: &Map) {
+        // Initialize unit positions from map setup markers
+        for map_hex in &map.hexes {
+            if let Some(ref setup) = map_hex.setup {
+                // Find a matching unit for this setup location
+                // This is simplified; in a real game we'd have a proper setup algorithm
+                for unit_def in &units.units {
+                    let has_this_unit = self.units.iter().any(|u| u.id == unit_def.id);
+                    if !has_this_unit {
+                        let unit_side_matches = match setup {
+                            SetupMarker::German => unit_def.side == Side::German,
+                            SetupMarker::Soviet => unit_def.side == Side::Soviet,
+                        };
+
+                        if unit_side_matches {
+                            self.units.push(UnitState::new(
+                                unit_def.id.clone(),
+                                Some(map_hex.hex()),
+                                UnitStrength::Full,
+                            ));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Initialize city control based on unit positions
+        for map_hex in &map.hexes {
+            if let Some(ref city) = map_hex.city {
+                // Check which side has units here
+                for unit_state in &self.units {
+                    if let Some(unit_hex) = unit_state.hex() {
+                        if unit_hex == map_hex.hex() {
+                            if let Some(unit_def) = units.get(&unit_state.id) {
+                                self.city_control.insert(city.name.clone(), unit_def.side);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Get a unit by ID
     pub fn get_unit(&self, id: &str) -> Option<&UnitState> {
         self.units.iter().find(|u| u.id == id)
@@ -206,9 +299,14 @@ impl GameState {
         self.city_control.get(city_name).cloned()
     }
 
+    /// Check if the last turn has been played
+    pub fn is_over(&self) -> bool {
+        self.turn > 7
+    }
+
     /// Check victory condition (who controls Moscow)
     pub fn check_victory(&self) -> Option<Side> {
-        if self.turn > 7 {
+        if self.is_over() {
             self.get_city_control("Moscow")
         } else {
             None
@@ -1,13 +1,93 @@
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use std::cmp::max;
+use std::fmt;
+use utoipa::openapi::schema::{ObjectBuilder, Schema, SchemaType};
+use utoipa::openapi::RefOr;
+use utoipa::ToSchema;
 
 /// Axial coordinates for hex grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub struct Hex {
     pub q: i32,
     pub r: i32,
 }
 
+/// Serialized with the printed map number alongside the axial coordinates
+impl Serialize for Hex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Hex", 3)?;
+        s.serialize_field("q", &self.q)?;
+        s.serialize_field("r", &self.r)?;
+        match self.to_original() {
+            Some(original) => s.serialize_field("original", &original.to_string())?,
+            None => s.skip_field("original")?,
+        }
+        s.end()
+    }
+}
+
+/// Schema of the serialized form; `original` is only present in responses, for hexes that
+/// have a printed number
+impl<'s> ToSchema<'s> for Hex {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let schema = ObjectBuilder::new()
+            .property("q", ObjectBuilder::new().schema_type(SchemaType::Integer))
+            .required("q")
+            .property("r", ObjectBuilder::new().schema_type(SchemaType::Integer))
+            .required("r")
+            .property(
+                "original",
+                ObjectBuilder::new()
+                    .schema_type(SchemaType::String)
+                    .description(Some("Number printed on the original map, XXYY")),
+            )
+            .build();
+        ("Hex", schema.into())
+    }
+}
+
+/// Hex number as printed on the original map (XXYY = column, row)
+///
+/// The printed map uses odd-q offset coordinates, see MAPDATA.md
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OriginalHex {
+    pub column: i32,
+    pub row: i32,
+}
+
+impl OriginalHex {
+    pub fn new(column: i32, row: i32) -> Self {
+        OriginalHex { column, row }
+    }
+
+    /// Parse a four-digit hex number such as "0512"
+    pub fn from_string(s: &str) -> Option<OriginalHex> {
+        if s.len() != 4 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let original = OriginalHex::new(s[..2].parse().ok()?, s[2..].parse().ok()?);
+        original.is_printable().then_some(original)
+    }
+
+    /// Check that the number fits the printed XXYY form, columns and rows 01 to 99
+    pub fn is_printable(&self) -> bool {
+        (1..=99).contains(&self.column) && (1..=99).contains(&self.row)
+    }
+
+    /// Convert to axial coordinates
+    /// axial_q = column, axial_r = row - (column - (column & 1)) / 2
+    pub fn to_hex(&self) -> Hex {
+        Hex::new(self.column, self.row - (self.column - (self.column & 1)) / 2)
+    }
+}
+
+impl fmt::Display for OriginalHex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}{:02}", self.column, self.row)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     NE,
@@ -52,6 +132,18 @@ impl Direction {
             _ => None,
         }
     }
+
+    /// Get the direction pointing back across the same hex edge
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::NE => Direction::SW,
+            Direction::E => Direction::W,
+            Direction::SE => Direction::NW,
+            Direction::SW => Direction::NE,
+            Direction::W => Direction::E,
+            Direction::NW => Direction::SE,
+        }
+    }
 }
 
 impl Hex {
@@ -59,6 +151,19 @@ impl Hex {
         Hex { q, r }
     }
 
+    /// Parse a printed map hex number such as "0512"
+    pub fn from_original(s: &str) -> Option<Hex> {
+        OriginalHex::from_string(s).map(|original| original.to_hex())
+    }
+
+    /// Get the printed map hex number for this hex, if it has one
+    ///
+    /// Hexes whose column or row falls outside 01–99 have no XXYY number.
+    pub fn to_original(&self) -> Option<OriginalHex> {
+        let original = OriginalHex::new(self.q, self.r + (self.q - (self.q & 1)) / 2);
+        original.is_printable().then_some(original)
+    }
+
     /// Calculate distance between two hexes using axial coordinates
     /// distance = max(abs(a.q - b.q), abs(a.r - b.r), abs((a.q + a.r) - (b.q + b.r)))
     pub fn distance(&self, other: &Hex) -> i32 {
@@ -100,12 +205,9 @@ impl Hex {
             return None;
         }
 
-        for dir in Direction::all() {
-            if self.neighbor(dir) == *other {
-                return Some(dir);
-            }
-        }
-        None
+        Direction::all()
+            .into_iter()
+            .find(|&dir| self.neighbor(dir) == *other)
     }
 }
 
@@ -159,4 +261,58 @@ mod tests {
         let d = Hex::new(10, 10);
         assert_eq!(a.direction_to(&d), None);
     }
+
+    #[test]
+    fn test_original_conversion() {
+        assert_eq!(Hex::new(5, 3).to_original().unwrap().to_string(), "0505");
+        assert_eq!(Hex::new(10, 2).to_original().unwrap().to_string(), "1007");
+        assert_eq!(Hex::from_original("0505"), Some(Hex::new(5, 3)));
+        assert_eq!(Hex::from_original("1007"), Some(Hex::new(10, 2)));
+
+        // Column 00, row 00, negative rows and three-digit values have no printed number
+        assert_eq!(Hex::new(0, 0).to_original(), None);
+        assert_eq!(Hex::new(1, 0).to_original(), None);
+        assert_eq!(Hex::new(1, -1).to_original(), None);
+        assert_eq!(Hex::new(100, -49).to_original(), None);
+        assert_eq!(Hex::new(2, 99).to_original(), None);
+
+        let mut printed = 0;
+        for q in -2..110 {
+            for r in -60..110 {
+                let hex = Hex::new(q, r);
+                if let Some(original) = hex.to_original() {
+                    assert_eq!(Hex::from_original(&original.to_string()), Some(hex));
+                    printed += 1;
+                }
+            }
+        }
+        assert_eq!(printed, 99 * 99);
+    }
+
+    #[test]
+    fn test_original_parsing() {
+        assert_eq!(OriginalHex::from_string("0512"), Some(OriginalHex::new(5, 12)));
+        assert_eq!(OriginalHex::from_string("512"), None);
+        assert_eq!(OriginalHex::from_string("05,12"), None);
+        assert_eq!(OriginalHex::from_string("+512"), None);
+        assert_eq!(OriginalHex::from_string("0000"), None);
+        assert_eq!(OriginalHex::from_string("0500"), None);
+    }
+
+    #[test]
+    fn test_hex_json_includes_original() {
+        let json = serde_json::to_value(Hex::new(5, 3)).unwrap();
+        assert_eq!(json, serde_json::json!({"q": 5, "r": 3, "original": "0505"}));
+
+        let hex: Hex = serde_json::from_value(json).unwrap();
+        assert_eq!(hex, Hex::new(5, 3));
+    }
+
+    #[test]
+    fn test_opposite_direction() {
+        let hex = Hex::new(5, 3);
+        for dir in Direction::all() {
+            assert_eq!(hex.neighbor(dir).neighbor(dir.opposite()), hex);
+        }
+    }
 }
@@ -0,0 +1,31 @@
+pub mod hex;
+pub mod error;
+pub mod map;
+pub mod unit;
+pub mod game_state;
+pub mod zoc;
+pub mod movement;
+pub mod combat;
+pub mod analysis;
+pub mod evaluate;
+pub mod threats;
+pub mod ai;
+pub mod mcts;
+pub mod arena;
+pub mod bot;
+pub mod replacement;
+pub mod retreat;
+pub mod validate;
+pub mod render;
+pub mod events;
+pub mod seats;
+pub mod dice;
+pub mod action;
+pub mod turn_file;
+pub mod jsonrpc;
+pub mod api;
+pub mod openapi;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(test)]
+pub(crate) mod test_support;
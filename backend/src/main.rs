@@ -1,19 +1,7 @@
-mod hex;
-mod map;
-mod unit;
-mod game_state;
-mod zoc;
-mod movement;
-mod combat;
-mod replacement;
-mod retreat;
-mod api;
-
-use api::{create_router, AppState};
-use game_state::GameState;
-use map::Map;
-use unit::Units;
-use std::sync::{Arc, RwLock};
+use backend::api::{create_router, AppState};
+use backend::game_state::GameState;
+use backend::map::Map;
+use backend::unit::Units;
 use tower_http::services::ServeDir;
 
 #[tokio::main]
@@ -35,11 +23,13 @@ async fn main() {
     let game = GameState::new();
 
     // Create shared application state
-    let app_state = AppState {
-        game: Arc::new(RwLock::new(game)),
-        units: Arc::new(units),
-        map: Arc::new(map),
-    };
+    let app_state = AppState::new(game, units, map);
+
+    // The first game may be played without seat tokens on one screen
+    if std::env::args().any(|arg| arg == "--hotseat") {
+        app_state.seats.lock().unwrap().set_hotseat(true);
+        println!("Hotseat play: unclaimed sides accept requests without a seat token");
+    }
 
     // Build the application with routes
     let app = create_router(app_state)
@@ -1,28 +1,32 @@
 use crate::hex::{Direction, Hex};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use std::collections::HashMap;
+use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, Schema, SchemaType};
+use utoipa::openapi::{Ref, RefOr};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Terrain {
     Clear,
     Forest,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct City {
     pub name: String,
     pub is_moscow: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SetupMarker {
     German,
     Soviet,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct MapHex {
     pub q: i32,
     pub r: i32,
@@ -34,6 +38,53 @@ pub struct MapHex {
     pub setup: Option<SetupMarker>,
 }
 
+/// Serialized with the printed map number alongside q and r
+impl Serialize for MapHex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("MapHex", 9)?;
+        s.serialize_field("q", &self.q)?;
+        s.serialize_field("r", &self.r)?;
+        match self.hex().to_original() {
+            Some(original) => s.serialize_field("original", &original.to_string())?,
+            None => s.skip_field("original")?,
+        }
+        s.serialize_field("terrain", &self.terrain)?;
+        s.serialize_field("city", &self.city)?;
+        s.serialize_field("fortification", &self.fortification)?;
+        s.serialize_field("rail", &self.rail)?;
+        s.serialize_field("river_edges", &self.river_edges)?;
+        s.serialize_field("setup", &self.setup)?;
+        s.end()
+    }
+}
+
+/// Schema of the serialized form, including `original`
+impl<'s> ToSchema<'s> for MapHex {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let integer = || ObjectBuilder::new().schema_type(SchemaType::Integer);
+        let boolean = || ObjectBuilder::new().schema_type(SchemaType::Boolean);
+        let string = || ObjectBuilder::new().schema_type(SchemaType::String);
+        let schema = ObjectBuilder::new()
+            .property("q", integer())
+            .required("q")
+            .property("r", integer())
+            .required("r")
+            .property("original", string())
+            .property("terrain", Ref::from_schema_name("Terrain"))
+            .required("terrain")
+            .property("city", Ref::from_schema_name("City"))
+            .property("fortification", boolean())
+            .required("fortification")
+            .property("rail", boolean())
+            .required("rail")
+            .property("river_edges", ArrayBuilder::new().items(string()))
+            .required("river_edges")
+            .property("setup", Ref::from_schema_name("SetupMarker"))
+            .build();
+        ("MapHex", schema.into())
+    }
+}
+
 impl MapHex {
     pub fn hex(&self) -> Hex {
         Hex::new(self.q, self.r)
@@ -45,7 +96,7 @@ impl MapHex {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MapBounds {
     pub min_q: i32,
     pub max_q: i32,
@@ -53,13 +104,13 @@ pub struct MapBounds {
     pub max_r: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MapEdges {
     pub west: String,  // "german_communication"
     pub east: String,  // "soviet_communication"
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Map {
     pub hexes: Vec<MapHex>,
     pub map_bounds: MapBounds,
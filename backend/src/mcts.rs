@@ -0,0 +1,591 @@
+use crate::action::{apply_action, Action};
+use crate::ai::{best_retreat, legal_actions, score_after, Player};
+use crate::analysis::plan_attacks;
+use crate::combat::{apply_combat_result, preview_battle, CombatResult};
+use crate::evaluate::evaluate_position;
+use crate::game_state::GameState;
+use crate::map::Map;
+use crate::unit::{Side, Units};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Children a decision node may grow, best first by one-ply evaluation
+const MAX_CHILDREN: usize = 12;
+/// Attack plans whose battles are offered as declarations
+const PLAN_CANDIDATES: usize = 4;
+/// Actions a playout may take before its position is scored instead
+const MAX_PLAYOUT_ACTIONS: usize = 2000;
+
+/// How hard the search works on each decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MctsConfig {
+    /// Playouts per decision
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+    /// Stop a decision's search early after this many milliseconds
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+    /// Seeds the search's own random choices and die rolls
+    #[serde(default)]
+    pub seed: u64,
+    /// Random actions played from a new leaf before the position is evaluated
+    #[serde(default = "default_rollout_depth")]
+    pub rollout_depth: usize,
+    /// UCT exploration constant
+    #[serde(default = "default_exploration")]
+    pub exploration: f64,
+}
+
+fn default_iterations() -> usize {
+    200
+}
+
+fn default_rollout_depth() -> usize {
+    20
+}
+
+fn default_exploration() -> f64 {
+    0.7
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            iterations: default_iterations(),
+            time_limit_ms: None,
+            seed: 0,
+            rollout_depth: default_rollout_depth(),
+            exploration: default_exploration(),
+        }
+    }
+}
+
+/// Monte Carlo tree search over the engine's legal actions
+///
+/// Resolving a battle leads to a chance node with one child per combat result, weighted
+/// by its probability, so the search never peeks at the game's own dice. Searches are
+/// deterministic for a given seed unless a time limit cuts them short.
+pub struct MctsPlayer {
+    config: MctsConfig,
+    rng: StdRng,
+}
+
+impl MctsPlayer {
+    pub fn new(config: MctsConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        MctsPlayer { config, rng }
+    }
+}
+
+impl Player for MctsPlayer {
+    fn choose(&mut self, state: &GameState, units: &Units, map: &Map) -> Action {
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            units,
+            map,
+            config: &self.config,
+            rng: &mut self.rng,
+        };
+        let root = tree.decision(state.clone());
+        if let Kind::Decision { untried, .. } = &tree.nodes[root].kind {
+            if untried.len() <= 1 {
+                return untried.first().cloned().unwrap_or(Action::AdvancePhase);
+            }
+        }
+
+        let deadline = self
+            .config
+            .time_limit_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        for _ in 0..self.config.iterations {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            tree.iterate(root);
+        }
+        tree.best_action(root)
+    }
+}
+
+enum Kind {
+    /// The active side picks an action
+    Decision {
+        /// Actions not yet expanded, best first
+        untried: Vec<Action>,
+        children: Vec<(Action, usize)>,
+    },
+    /// A battle's die is rolled: each child follows one combat result
+    Chance { outcomes: Vec<(f64, usize)> },
+}
+
+struct Node {
+    state: GameState,
+    visits: u32,
+    /// Sum of German rewards backed up through the node
+    german_reward: f64,
+    kind: Kind,
+}
+
+impl Node {
+    fn mean_for(&self, side: Side) -> f64 {
+        let german = self.german_reward / self.visits as f64;
+        match side {
+            Side::German => german,
+            Side::Soviet => 1.0 - german,
+        }
+    }
+}
+
+struct Tree<'a> {
+    nodes: Vec<Node>,
+    units: &'a Units,
+    map: &'a Map,
+    config: &'a MctsConfig,
+    rng: &'a mut StdRng,
+}
+
+impl Tree<'_> {
+    fn push(&mut self, state: GameState, kind: Kind) -> usize {
+        self.nodes.push(Node {
+            state,
+            visits: 0,
+            german_reward: 0.0,
+            kind,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn decision(&mut self, state: GameState) -> usize {
+        let untried = ranked_actions(&state, self.units, self.map);
+        self.push(
+            state,
+            Kind::Decision {
+                untried,
+                children: Vec::new(),
+            },
+        )
+    }
+
+    /// The node an action leads to: a chance node for a battle, a decision node otherwise
+    fn child(&mut self, state: &GameState, action: &Action) -> usize {
+        if let Action::ResolveBattle { battle_index, .. } = action {
+            let outcomes = battle_outcomes(state, *battle_index, self.units, self.map)
+                .into_iter()
+                .map(|(probability, result)| {
+                    let mut next = state.clone();
+                    resolve_as(&mut next, *battle_index, &result, self.units, self.map);
+                    (probability, self.decision(next))
+                })
+                .collect();
+            return self.push(state.clone(), Kind::Chance { outcomes });
+        }
+        let mut next = state.clone();
+        if apply_action(action, &mut next, self.units, self.map).is_err() {
+            apply_action(&Action::AdvancePhase, &mut next, self.units, self.map).ok();
+        }
+        self.decision(next)
+    }
+
+    /// One selection, expansion, rollout and backup pass from the root
+    fn iterate(&mut self, root: usize) {
+        let mut path = vec![root];
+        let mut node = root;
+        loop {
+            if let Kind::Chance { outcomes } = &self.nodes[node].kind {
+                node = sample(outcomes, self.rng);
+                path.push(node);
+                continue;
+            }
+            if self.nodes[node].state.is_over() {
+                break;
+            }
+            let Kind::Decision { untried, children } = &mut self.nodes[node].kind else {
+                unreachable!()
+            };
+            if !untried.is_empty() {
+                let action = untried.remove(0);
+                let state = self.nodes[node].state.clone();
+                let child = self.child(&state, &action);
+                if let Kind::Decision { children, .. } = &mut self.nodes[node].kind {
+                    children.push((action, child));
+                }
+                path.push(child);
+                if let Kind::Chance { outcomes } = &self.nodes[child].kind {
+                    let outcome = sample(outcomes, self.rng);
+                    path.push(outcome);
+                }
+                break;
+            }
+            if children.is_empty() {
+                break;
+            }
+            node = self.select(node);
+            path.push(node);
+        }
+
+        let leaf = *path.last().unwrap();
+        let reward = self.rollout(self.nodes[leaf].state.clone());
+        for index in path {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.german_reward += reward;
+        }
+    }
+
+    /// The child with the best upper confidence bound for the side to move
+    fn select(&self, node: usize) -> usize {
+        let parent = &self.nodes[node];
+        let Kind::Decision { children, .. } = &parent.kind else {
+            unreachable!()
+        };
+        let side = parent.state.active_player();
+        let log_visits = (parent.visits.max(1) as f64).ln();
+        let bound = |child: usize| {
+            let child = &self.nodes[child];
+            if child.visits == 0 {
+                return f64::INFINITY;
+            }
+            child.mean_for(side)
+                + self.config.exploration * (log_visits / child.visits as f64).sqrt()
+        };
+        children
+            .iter()
+            .map(|&(_, child)| child)
+            .fold(None, |best: Option<(usize, f64)>, child| {
+                let value = bound(child);
+                match best {
+                    Some((_, b)) if b >= value => best,
+                    _ => Some((child, value)),
+                }
+            })
+            .map(|(child, _)| child)
+            .unwrap()
+    }
+
+    /// Play random actions from a state, then score it for the Germans between 0 and 1
+    fn rollout(&mut self, mut state: GameState) -> f64 {
+        play_out(&mut state, self.config.rollout_depth, self.units, self.map, self.rng);
+        reward(&state, self.units, self.map)
+    }
+
+    /// The root action searched most, or the best untried one if none was searched
+    fn best_action(&self, root: usize) -> Action {
+        let Kind::Decision { untried, children } = &self.nodes[root].kind else {
+            unreachable!()
+        };
+        children
+            .iter()
+            .fold(None, |best: Option<&(Action, usize)>, entry| match best {
+                Some(b) if self.nodes[b.1].visits >= self.nodes[entry.1].visits => best,
+                _ => Some(entry),
+            })
+            .map(|(action, _)| action.clone())
+            .or_else(|| untried.first().cloned())
+            .unwrap_or(Action::AdvancePhase)
+    }
+}
+
+/// Play up to `depth` random actions, rolling battles with `rng` rather than the game's dice
+fn play_out(state: &mut GameState, depth: usize, units: &Units, map: &Map, rng: &mut StdRng) {
+    for _ in 0..depth {
+        if state.is_over() {
+            break;
+        }
+        let actions = candidate_actions(state, units, map);
+        let action = &actions[rng.gen_range(0..actions.len())];
+        if let Action::ResolveBattle { battle_index, .. } = action {
+            let outcomes = battle_outcomes(state, *battle_index, units, map);
+            let result = sample(&outcomes, rng);
+            resolve_as(state, *battle_index, &result, units, map);
+        } else if apply_action(action, state, units, map).is_err() {
+            apply_action(&Action::AdvancePhase, state, units, map).ok();
+        }
+    }
+}
+
+/// Each side's chance of winning, estimated from `games` random playouts of a position
+///
+/// Games are played as in the search's rollouts, to the end or for `MAX_PLAYOUT_ACTIONS`
+/// actions, after which the evaluator's win probability stands in for the result. The
+/// chances are (German, Soviet); whatever is left over is games ending without a winner.
+pub fn playout_chances(
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+    games: usize,
+    seed: u64,
+) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 0.0);
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut german, mut soviet) = (0.0, 0.0);
+    for _ in 0..games {
+        let mut game = state.clone();
+        play_out(&mut game, MAX_PLAYOUT_ACTIONS, units, map, &mut rng);
+        let (g, s) = match (game.is_over(), game.check_victory()) {
+            (true, Some(Side::German)) => (1.0, 0.0),
+            (true, Some(Side::Soviet)) => (0.0, 1.0),
+            (true, None) => (0.0, 0.0),
+            (false, _) => {
+                let p = evaluate_position(&game, units, map).win_probability(Side::German);
+                (p, 1.0 - p)
+            }
+        };
+        german += g;
+        soviet += s;
+    }
+    (german / games as f64, soviet / games as f64)
+}
+
+/// The German reward for a position: 1 or 0 once the game is decided, else the evaluator's
+/// win probability
+fn reward(state: &GameState, units: &Units, map: &Map) -> f64 {
+    if state.is_over() {
+        return match state.check_victory() {
+            Some(Side::German) => 1.0,
+            Some(Side::Soviet) => 0.0,
+            None => 0.5,
+        };
+    }
+    evaluate_position(state, units, map).win_probability(Side::German)
+}
+
+/// The actions worth searching, best first by one-ply evaluation, with advancing kept
+fn ranked_actions(state: &GameState, units: &Units, map: &Map) -> Vec<Action> {
+    let side = state.active_player();
+    let mut scored: Vec<(Action, f64)> = candidate_actions(state, units, map)
+        .into_iter()
+        .filter(|action| *action != Action::AdvancePhase)
+        .filter_map(|action| {
+            let score = score_after(&action, side, state, units, map)?;
+            Some((action, score))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(MAX_CHILDREN - 1);
+
+    let mut actions: Vec<Action> = scored.into_iter().map(|(action, _)| action).collect();
+    if !state.is_over() && !matches!(actions.first(), Some(Action::ResolveBattle { .. })) {
+        actions.push(Action::AdvancePhase);
+    }
+    actions
+}
+
+/// The legal actions the search considers, in no particular order
+///
+/// In combat, a declared battle must be resolved before anything else happens, and only
+/// battles from the attack solver's best plans are offered.
+fn candidate_actions(state: &GameState, units: &Units, map: &Map) -> Vec<Action> {
+    if state.is_over() || !state.phase.is_combat_phase() {
+        return legal_actions(state, units, map);
+    }
+    if let Some(battle_index) = state.pending_battles.iter().position(|b| !b.resolved) {
+        return vec![Action::ResolveBattle {
+            battle_index,
+            loss: None,
+        }];
+    }
+
+    let mut actions = Vec::new();
+    let plans = plan_attacks(state, units, map, PLAN_CANDIDATES).unwrap_or_default();
+    for battle in plans.into_iter().flat_map(|plan| plan.battles) {
+        let declare = Action::DeclareBattle {
+            attacker_ids: battle.attackers,
+            defender_id: battle.defender,
+        };
+        if !actions.contains(&declare) {
+            actions.push(declare);
+        }
+    }
+    actions.push(Action::AdvancePhase);
+    actions
+}
+
+/// Each combat result a pending battle can produce, with its probability
+fn battle_outcomes(
+    state: &GameState,
+    battle_index: usize,
+    units: &Units,
+    map: &Map,
+) -> Vec<(f64, CombatResult)> {
+    let battle = &state.pending_battles[battle_index];
+    match preview_battle(&battle.attackers, &battle.defender, state, units, map) {
+        Ok(preview) => preview
+            .outcomes
+            .into_iter()
+            .map(|o| (o.probability, o.result))
+            .collect(),
+        Err(_) => vec![(1.0, CombatResult::NE)],
+    }
+}
+
+/// Resolve a pending battle with a given result, retreating the defender if it must
+fn resolve_as(
+    state: &mut GameState,
+    battle_index: usize,
+    result: &CombatResult,
+    units: &Units,
+    map: &Map,
+) {
+    let battle = state.pending_battles[battle_index].clone();
+    apply_combat_result(result, &battle, None, state, units).ok();
+    state.pending_battles[battle_index].resolved = true;
+
+    if matches!(result, CombatResult::DR | CombatResult::DRL) {
+        if let Some(to) = best_retreat(&battle.defender, state, units, map) {
+            let retreat = Action::Retreat {
+                unit_id: battle.defender,
+                to,
+            };
+            apply_action(&retreat, state, units, map).ok();
+        }
+    }
+}
+
+/// Pick an outcome by its probability
+fn sample<T: Clone>(outcomes: &[(f64, T)], rng: &mut StdRng) -> T {
+    let mut roll: f64 = rng.gen();
+    for (probability, outcome) in outcomes {
+        if roll < *probability {
+            return outcome.clone();
+        }
+        roll -= probability;
+    }
+    outcomes.last().map(|(_, outcome)| outcome.clone()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::bundled;
+    use crate::ai::{play_game, HeuristicPlayer};
+    use crate::game_state::Phase;
+    use crate::hex::Hex;
+    use crate::unit::{UnitState, UnitStrength};
+
+    fn quick(seed: u64) -> MctsConfig {
+        MctsConfig {
+            iterations: 40,
+            seed,
+            rollout_depth: 8,
+            ..MctsConfig::default()
+        }
+    }
+
+    /// A full-strength panzer next to a half-strength Soviet army on clear ground, with
+    /// Moscow held
+    fn battle_ready() -> GameState {
+        let mut state = GameState::with_seed("mcts-attack");
+        state.phase = Phase::GermanCombat;
+        for (id, q) in [("XXIV", 1), ("3", 0), ("5", 3)] {
+            state.units.push(UnitState::new(
+                id.to_string(),
+                Some(Hex::new(q, 0)),
+                UnitStrength::Full,
+            ));
+        }
+        state.get_unit_mut("3").unwrap().take_loss();
+        state
+    }
+
+    #[test]
+    fn test_mcts_is_deterministic() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("mcts-determinism");
+        state.place_initial_units(&units, &map);
+        state.phase = Phase::GermanMovement;
+
+        let mut first = MctsPlayer::new(quick(7));
+        let mut second = MctsPlayer::new(quick(7));
+        for _ in 0..3 {
+            let action = first.choose(&state, &units, &map);
+            assert_eq!(second.choose(&state, &units, &map), action);
+        }
+    }
+
+    #[test]
+    fn test_mcts_attacks() {
+        let (units, map) = bundled();
+        let mut state = battle_ready();
+        let mut player = MctsPlayer::new(quick(1));
+
+        let declare = player.choose(&state, &units, &map);
+        assert!(matches!(
+            declare,
+            Action::DeclareBattle { ref defender_id, .. } if defender_id == "3"
+        ));
+        apply_action(&declare, &mut state, &units, &map).unwrap();
+        assert_eq!(
+            player.choose(&state, &units, &map),
+            Action::ResolveBattle {
+                battle_index: 0,
+                loss: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_battle_outcomes() {
+        let (units, map) = bundled();
+        let mut state = battle_ready();
+        state
+            .pending_battles
+            .push(crate::game_state::BattleDeclaration {
+                attackers: vec!["XXIV".to_string()],
+                defender: "3".to_string(),
+                resolved: false,
+            });
+
+        let outcomes = battle_outcomes(&state, 0, &units, &map);
+        let total: f64 = outcomes.iter().map(|(p, _)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Resolving by hand rolls none of the game's dice
+        let rolls = state.dice.clone();
+        resolve_as(&mut state, 0, &CombatResult::DE, &units, &map);
+        assert!(state.pending_battles[0].resolved);
+        assert_eq!(state.get_unit("3").unwrap().hex(), None);
+        assert_eq!(state.dice, rolls);
+    }
+
+    #[test]
+    fn test_mcts_plays_a_game() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("mcts-game");
+        state.place_initial_units(&units, &map);
+
+        let mut german = MctsPlayer::new(MctsConfig {
+            iterations: 10,
+            rollout_depth: 4,
+            ..MctsConfig::default()
+        });
+        let mut soviet = HeuristicPlayer::new();
+        play_game(&mut german, &mut soviet, &mut state, &units, &map).unwrap();
+        assert!(state.is_over());
+    }
+
+    #[test]
+    fn test_playout_chances() {
+        let (units, map) = bundled();
+        let mut state = GameState::with_seed("playouts");
+        state.place_initial_units(&units, &map);
+
+        let (german, soviet) = playout_chances(&state, &units, &map, 20, 3);
+        assert!(german + soviet <= 1.0 + 1e-9);
+        assert_eq!(playout_chances(&state, &units, &map, 20, 3), (german, soviet));
+
+        // A finished game is settled
+        state.turn = 8;
+        let winner = match state.check_victory() {
+            Some(Side::German) => (1.0, 0.0),
+            Some(Side::Soviet) => (0.0, 1.0),
+            None => (0.0, 0.0),
+        };
+        assert_eq!(playout_chances(&state, &units, &map, 5, 0), winner);
+        assert_eq!(playout_chances(&state, &units, &map, 0, 0), (0.0, 0.0));
+    }
+}
@@ -1,9 +1,22 @@
+use crate::error::RuleError;
 use crate::game_state::{GameState, Phase};
 use crate::hex::Hex;
 use crate::map::Map;
-use crate::unit::{Side, Units};
-use crate::zoc::{calculate_enemy_zoc, is_in_enemy_zoc};
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::unit::{Side, UnitDefinition, Units};
+use crate::zoc::calculate_enemy_zoc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use utoipa::ToSchema;
+
+/// A hex a unit can end its move in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MoveOption {
+    pub hex: Hex,
+    /// Movement points spent on the cheapest path
+    pub cost: i32,
+    /// The hex is in an enemy ZOC, so the unit must stop there
+    pub stops_in_zoc: bool,
+}
 
 /// Find all valid destination hexes for a unit from its current position
 pub fn find_valid_moves(
@@ -11,32 +24,45 @@ pub fn find_valid_moves(
     state: &GameState,
     units: &Units,
     map: &Map,
-) -> Result<Vec<Hex>, String> {
+) -> Result<Vec<Hex>, RuleError> {
+    let options = find_move_options(unit_id, state, units, map)?;
+    Ok(options.into_iter().map(|option| option.hex).collect())
+}
+
+/// Movement points a unit has in the current phase
+pub fn movement_allowance(unit_def: &UnitDefinition, state: &GameState) -> i32 {
+    // Rail movement not affected by mud
+    if state.is_mud() && !matches!(state.phase, Phase::SovietRailMovement) {
+        1
+    } else {
+        unit_def.movement
+    }
+}
+
+/// Find every hex a unit can end its move in, cheapest first, with costs and ZOC stops
+pub fn find_move_options(
+    unit_id: &str,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<Vec<MoveOption>, RuleError> {
     // Get the unit state
     let unit_state = state
         .get_unit(unit_id)
-        .ok_or_else(|| format!("Unit {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Get the unit definition
     let unit_def = units
         .get(unit_id)
-        .ok_or_else(|| format!("Unit definition for {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Get current position
     let start_hex = unit_state
         .hex()
-        .ok_or_else(|| format!("Unit {} is eliminated", unit_id))?;
+        .ok_or_else(|| RuleError::NotOnMap(unit_id.to_string()))?;
 
     // Calculate movement allowance based on phase and mud
-    let mut movement_allowance = unit_def.movement;
-
-    // Check if in mud turn
-    if state.is_mud() {
-        // Rail movement not affected by mud
-        if !matches!(state.phase, Phase::SovietRailMovement) {
-            movement_allowance = 1;
-        }
-    }
+    let movement_allowance = movement_allowance(unit_def, state);
 
     // Check what type of movement is allowed based on phase
     let rail_movement_only = matches!(state.phase, Phase::SovietRailMovement);
@@ -112,13 +138,18 @@ pub fn find_valid_moves(
     reachable.remove(&start_hex);
 
     // Convert to vector and remove hexes with friendly units (can't end stacked)
-    let valid_hexes: Vec<Hex> = reachable
-        .keys()
-        .filter(|hex| !has_unit_of_side(state, units, hex, unit_def.side))
-        .copied()
+    let mut options: Vec<MoveOption> = reachable
+        .into_iter()
+        .filter(|(hex, _)| !has_unit_of_side(state, units, hex, unit_def.side))
+        .map(|(hex, cost)| MoveOption {
+            hex,
+            cost,
+            stops_in_zoc: enemy_zoc.contains(&hex),
+        })
         .collect();
+    options.sort_by_key(|option| (option.cost, option.hex.q, option.hex.r));
 
-    Ok(valid_hexes)
+    Ok(options)
 }
 
 /// Check if there's a unit of a specific side at a hex
@@ -152,28 +183,45 @@ pub fn validate_move(
     state: &GameState,
     units: &Units,
     map: &Map,
-) -> Result<(), String> {
+) -> Result<(), RuleError> {
+    check_can_move(unit_id, state, units, map)?;
+
+    // Find valid moves and check if destination is in the list
+    let valid_moves = find_valid_moves(unit_id, state, units, map)?;
+
+    if !valid_moves.contains(destination) {
+        return Err(RuleError::Unreachable(*destination));
+    }
+
+    Ok(())
+}
+
+/// Check that a unit may move at all in the current phase
+pub fn check_can_move(
+    unit_id: &str,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<(), RuleError> {
     // Check that it's the correct phase
     if !state.phase.is_movement_phase() {
-        return Err("Not a movement phase".to_string());
+        return Err(RuleError::WrongPhase("movement"));
     }
 
     // Get the unit
     let unit_def = units
         .get(unit_id)
-        .ok_or_else(|| format!("Unit {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Check that it's the correct player's turn
     if unit_def.side != state.active_player() {
-        return Err("Not your turn".to_string());
+        return Err(RuleError::NotYourTurn);
     }
 
     // Check if unit can move in this phase
     match state.phase {
-        Phase::GermanPanzerMovement => {
-            if !unit_def.can_move_in_panzer_phase() {
-                return Err("Only panzers can move in panzer phase".to_string());
-            }
+        Phase::GermanPanzerMovement if !unit_def.can_move_in_panzer_phase() => {
+            return Err(RuleError::PanzersOnly);
         }
         Phase::SovietRailMovement => {
             // Must start on rail
@@ -181,7 +229,7 @@ pub fn validate_move(
                 if let Some(start_hex) = unit_state.hex() {
                     if let Some(map_hex) = map.get_hex(&start_hex) {
                         if !map_hex.rail {
-                            return Err("Unit not on rail line".to_string());
+                            return Err(RuleError::NotOnRail);
                         }
                     }
                 }
@@ -196,7 +244,7 @@ pub fn validate_move(
             Phase::GermanMovement => {
                 // Panzers can move again
                 if !unit_def.can_move_in_panzer_phase() {
-                    return Err("Unit has already moved this phase".to_string());
+                    return Err(RuleError::AlreadyMoved);
                 }
             }
             Phase::SovietMovement => {
@@ -204,28 +252,19 @@ pub fn validate_move(
                 // This is OK
             }
             _ => {
-                return Err("Unit has already moved this phase".to_string());
+                return Err(RuleError::AlreadyMoved);
             }
         }
     }
 
-    // Find valid moves and check if destination is in the list
-    let valid_moves = find_valid_moves(unit_id, state, units, map)?;
-
-    if !valid_moves.contains(destination) {
-        return Err(format!(
-            "Cannot reach hex ({}, {})",
-            destination.q, destination.r
-        ));
-    }
-
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::unit::{UnitDefinition, UnitState, UnitStrength, UnitType};
+    use crate::test_support::{test_map, unit};
+    use crate::unit::{UnitState, UnitStrength, UnitType};
 
     #[test]
     fn test_find_valid_moves_basic() {
@@ -254,4 +293,47 @@ mod tests {
         // Note: In a real scenario, you'd use an actual map
         // For now, this test is more of a structure verification
     }
+
+    #[test]
+    fn test_panzer_moves_again_in_movement_phase() {
+        let map = test_map(6, 1, None);
+        let units = Units {
+            units: vec![
+                unit("XLVII", Side::German, UnitType::Panzer, 8, 4),
+                unit("16A", Side::Soviet, UnitType::Infantry, 8, 4),
+            ],
+        };
+
+        // A panzer already marked as moved may move again in the movement phase
+        let mut state = GameState::new();
+        state.phase = Phase::GermanMovement;
+        state.units.push(UnitState::new(
+            "XLVII".to_string(),
+            Some(Hex::new(1, 0)),
+            UnitStrength::Full,
+        ));
+        state.units.push(UnitState::new(
+            "16A".to_string(),
+            Some(Hex::new(4, 0)),
+            UnitStrength::Full,
+        ));
+        state.mark_moved("XLVII");
+
+        assert_eq!(
+            validate_move("XLVII", &Hex::new(0, 0), &state, &units, &map),
+            Ok(())
+        );
+        assert_eq!(
+            validate_move("XLVII", &Hex::new(5, 0), &state, &units, &map),
+            Err(RuleError::Unreachable(Hex::new(5, 0)))
+        );
+
+        // Options come cheapest first, flagging the hex where the ZOC stops it
+        let options = find_move_options("XLVII", &state, &units, &map).unwrap();
+        let summary: Vec<(i32, i32, bool)> = options
+            .iter()
+            .map(|o| (o.hex.q, o.cost, o.stops_in_zoc))
+            .collect();
+        assert_eq!(summary, vec![(0, 1, false), (2, 1, false), (3, 2, true)]);
+    }
 }
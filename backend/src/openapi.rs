@@ -0,0 +1,223 @@
+use crate::action::Action;
+use crate::ai::{ComputerOpponent, PlayerKind};
+use crate::analysis::AttackPlan;
+use crate::api::{
+    AssessmentResponse, AttackPlansResponse, ClaimSeatRequest, CommitSeedRequest,
+    DeclareBattleRequest, DiceAuditResponse, GameResponse, HexesResponse, MapResponse, MoveRequest,
+    NewGameRequest, PreviewResponse, ReplacementRequest, ResolutionResponse, ResolveBattleRequest,
+    RetreatRequest, RetreatResponse, RevealSeedRequest, SeatClaim, SeatClaimResponse, SeatResponse,
+    SeatsResponse, ThreatsResponse, TurnCheck, TurnCheckResponse, TurnFileResponse, UnitsResponse,
+};
+use crate::combat::{BattlePreview, BattleResolution, CombatResult, OutcomeChance};
+use crate::dice::{
+    AuditedRoll, Dice, DiceAudit, RevealedShare, RollLinks, SeedExchange, SeedShare,
+};
+use crate::evaluate::{Assessment, Factor, PositionEvaluation};
+use crate::events::{EventRecord, GameEvent};
+use crate::game_state::{BattleDeclaration, GameState, Phase};
+use crate::hex::Hex;
+use crate::map::{City, Map, MapBounds, MapEdges, MapHex, SetupMarker, Terrain};
+use crate::mcts::MctsConfig;
+use crate::render::CoordinateLabels;
+use crate::retreat::RetreatResult;
+use crate::seats::{Seat, SeatSummary};
+use crate::threats::{CutOffRisk, FrontGap, ThreatReport, UnitThreat, WeakPoint};
+use crate::turn_file::{RecordedAction, TurnFile};
+use crate::unit::{Side, UnitDefinition, UnitState, UnitStrength, UnitType, Units};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+/// OpenAPI description of the game API, served at `/api/openapi.json`
+///
+/// Every response uses the `ApiResponse` envelope; failures carry `success: false`,
+/// a stable `code` and an HTTP status derived from it.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Battle for Moscow", description = "Rules engine for Battle for Moscow"),
+    paths(
+        crate::api::openapi_document,
+        crate::api::get_game_state,
+        crate::api::new_game,
+        crate::api::advance_phase,
+        crate::api::move_unit,
+        crate::api::get_valid_moves,
+        crate::api::declare_battle,
+        crate::api::resolve_battle_endpoint,
+        crate::api::preview_battle_endpoint,
+        crate::api::get_attack_plans,
+        crate::api::get_threats,
+        crate::api::evaluate,
+        crate::api::apply_replacement_endpoint,
+        crate::api::get_replacement_hexes,
+        crate::api::retreat_unit,
+        crate::api::get_retreat_hexes,
+        crate::api::get_map,
+        crate::api::render_map,
+        crate::api::get_units,
+        crate::api::game_events,
+        crate::api::get_seats,
+        crate::api::claim_seat,
+        crate::api::release_seat,
+        crate::api::export_turn,
+        crate::api::verify_turn,
+        crate::api::import_turn,
+        crate::api::commit_seed,
+        crate::api::reveal_seed,
+        crate::api::audit_dice,
+    ),
+    components(schemas(
+        GameResponse,
+        HexesResponse,
+        ResolutionResponse,
+        PreviewResponse,
+        AttackPlansResponse,
+        ThreatsResponse,
+        AssessmentResponse,
+        RetreatResponse,
+        MapResponse,
+        UnitsResponse,
+        SeatsResponse,
+        SeatClaimResponse,
+        SeatResponse,
+        TurnFileResponse,
+        TurnCheckResponse,
+        DiceAuditResponse,
+        NewGameRequest,
+        ComputerOpponent,
+        PlayerKind,
+        MctsConfig,
+        MoveRequest,
+        DeclareBattleRequest,
+        ResolveBattleRequest,
+        ReplacementRequest,
+        RetreatRequest,
+        ClaimSeatRequest,
+        CommitSeedRequest,
+        RevealSeedRequest,
+        SeatClaim,
+        TurnCheck,
+        Action,
+        BattleResolution,
+        BattlePreview,
+        OutcomeChance,
+        AttackPlan,
+        ThreatReport,
+        UnitThreat,
+        CutOffRisk,
+        WeakPoint,
+        FrontGap,
+        Assessment,
+        PositionEvaluation,
+        Factor,
+        CombatResult,
+        Dice,
+        DiceAudit,
+        AuditedRoll,
+        RevealedShare,
+        SeedExchange,
+        SeedShare,
+        RollLinks,
+        EventRecord,
+        GameEvent,
+        BattleDeclaration,
+        GameState,
+        Phase,
+        Hex,
+        Map,
+        MapHex,
+        MapBounds,
+        MapEdges,
+        City,
+        SetupMarker,
+        Terrain,
+        CoordinateLabels,
+        RetreatResult,
+        Seat,
+        SeatSummary,
+        TurnFile,
+        RecordedAction,
+        Side,
+        UnitDefinition,
+        UnitState,
+        UnitStrength,
+        UnitType,
+        Units,
+    )),
+    modifiers(&SeatTokenScheme),
+)]
+pub struct ApiDoc;
+
+/// Registers the bearer token issued by `/api/seats/claim`
+struct SeatTokenScheme;
+
+impl Modify for SeatTokenScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "seat_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Endpoint;
+    use utoipa::openapi::PathItemType;
+
+    fn item_type(method: axum::http::Method) -> PathItemType {
+        match method {
+            axum::http::Method::GET => PathItemType::Get,
+            axum::http::Method::POST => PathItemType::Post,
+            other => panic!("Unexpected method {}", other),
+        }
+    }
+
+    #[test]
+    fn test_document_matches_router() {
+        let doc = ApiDoc::openapi();
+
+        // Every routed endpoint is documented with the same method
+        for endpoint in Endpoint::ALL {
+            let item = doc
+                .paths
+                .get_path_item(endpoint.path())
+                .unwrap_or_else(|| panic!("{} is not documented", endpoint.path()));
+            assert!(
+                item.operations.contains_key(&item_type(endpoint.method())),
+                "{} {} is not documented",
+                endpoint.method(),
+                endpoint.path()
+            );
+        }
+
+        // Every documented operation is routed
+        for (path, item) in doc.paths.paths.iter() {
+            for method in item.operations.keys() {
+                assert!(
+                    Endpoint::ALL
+                        .iter()
+                        .any(|e| e.path() == path && item_type(e.method()) == *method),
+                    "{} is documented but not routed",
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_schemas_are_defined() {
+        let doc = ApiDoc::openapi();
+        let json = serde_json::to_value(&doc).unwrap();
+        let schemas = json["components"]["schemas"].as_object().unwrap();
+
+        // Every reference in the document points at a registered schema
+        let text = json.to_string();
+        for reference in text.split("\"#/components/schemas/").skip(1) {
+            let name = reference.split('"').next().unwrap();
+            assert!(schemas.contains_key(name), "{} is not defined", name);
+        }
+        assert!(json["components"]["securitySchemes"]["seat_token"].is_object());
+    }
+}
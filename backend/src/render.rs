@@ -0,0 +1,451 @@
+use crate::game_state::GameState;
+use crate::hex::{Direction, Hex};
+use crate::map::{Map, Terrain};
+use crate::unit::{Side, UnitStrength, Units};
+use crate::zoc::calculate_enemy_zoc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+use utoipa::ToSchema;
+
+const TITLE_HEIGHT: f64 = 40.0;
+const MARGIN: f64 = 20.0;
+
+/// How hexes are labelled on the rendered map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateLabels {
+    None,
+    Axial,
+    Original,
+}
+
+/// Options controlling what is drawn on the rendered map
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Hex radius in pixels
+    pub hex_size: f64,
+    pub labels: CoordinateLabels,
+    /// Shade the zone of control exerted by this side's units
+    pub zoc: Option<Side>,
+    pub highlight: Vec<Hex>,
+    /// Draw the turn and phase above the map
+    pub title: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            hex_size: 30.0,
+            labels: CoordinateLabels::Axial,
+            zoc: None,
+            highlight: Vec::new(),
+            title: true,
+        }
+    }
+}
+
+/// Render the map and current game state as a standalone SVG document
+pub fn render_svg(map: &Map, units: &Units, state: &GameState, options: &RenderOptions) -> String {
+    let size = options.hex_size;
+    let mut svg = String::new();
+
+    // Pixel bounds of all hexes, using the same pointy-top layout as static/hex.js
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+    for map_hex in &map.hexes {
+        let (x, y) = hex_center(&map_hex.hex(), size);
+        min_x = min_x.min(x - size);
+        max_x = max_x.max(x + size);
+        min_y = min_y.min(y - size);
+        max_y = max_y.max(y + size);
+    }
+    if map.hexes.is_empty() {
+        (min_x, min_y, max_x, max_y) = (0.0, 0.0, 0.0, 0.0);
+    }
+
+    let title_height = if options.title { TITLE_HEIGHT } else { 0.0 };
+    let view_x = min_x - MARGIN;
+    let view_y = min_y - MARGIN - title_height;
+    let width = max_x - min_x + 2.0 * MARGIN;
+    let height = max_y - min_y + 2.0 * MARGIN + title_height;
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.1} {:.1} {:.1} {:.1}" width="{:.0}" height="{:.0}" font-family="sans-serif">"#,
+        view_x, view_y, width, height, width, height
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#f4ecd8"/>"##,
+        view_x, view_y, width, height
+    );
+
+    // Terrain
+    for map_hex in &map.hexes {
+        let (fill, stroke) = match map_hex.terrain {
+            Terrain::Clear => ("#a8d5a8", "#2f4f2f"),
+            Terrain::Forest => ("#2d5016", "#1a3010"),
+        };
+        let _ = writeln!(
+            svg,
+            r#"<polygon class="hex" points="{}" fill="{}" stroke="{}" stroke-width="1"/>"#,
+            hex_points(&map_hex.hex(), size, size),
+            fill,
+            stroke
+        );
+    }
+
+    // Fortifications
+    for map_hex in map.hexes.iter().filter(|mh| mh.fortification) {
+        let (x, y) = hex_center(&map_hex.hex(), size);
+        let _ = writeln!(
+            svg,
+            r##"<circle class="fortification" cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="#888" stroke-width="3" stroke-dasharray="5,5"/>"##,
+            x,
+            y,
+            size * 0.8
+        );
+    }
+
+    // Rivers, drawing each shared edge once
+    let mut drawn_edges = HashSet::new();
+    for map_hex in &map.hexes {
+        let hex = map_hex.hex();
+        for direction in Direction::all() {
+            if !map_hex.has_river_edge(direction) {
+                continue;
+            }
+            let neighbor = hex.neighbor(direction);
+            let edge = if (hex.q, hex.r) < (neighbor.q, neighbor.r) {
+                (hex, neighbor)
+            } else {
+                (neighbor, hex)
+            };
+            if !drawn_edges.insert(edge) {
+                continue;
+            }
+
+            let (a, b) = edge_corners(direction);
+            let (x1, y1) = hex_corner(&hex, size, a);
+            let (x2, y2) = hex_corner(&hex, size, b);
+            let _ = writeln!(
+                svg,
+                r##"<line class="river" x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#4169e1" stroke-width="4" stroke-linecap="round"/>"##,
+                x1, y1, x2, y2
+            );
+        }
+    }
+
+    // Rail lines between adjacent rail hexes; half the directions covers each pair once
+    let rail_hexes: HashSet<Hex> = map
+        .hexes
+        .iter()
+        .filter(|mh| mh.rail)
+        .map(|mh| mh.hex())
+        .collect();
+    for hex in map.hexes.iter().filter(|mh| mh.rail).map(|mh| mh.hex()) {
+        for direction in [Direction::E, Direction::SE, Direction::SW] {
+            let neighbor = hex.neighbor(direction);
+            if !rail_hexes.contains(&neighbor) {
+                continue;
+            }
+            let (x1, y1) = hex_center(&hex, size);
+            let (x2, y2) = hex_center(&neighbor, size);
+            let _ = writeln!(
+                svg,
+                r##"<line class="rail" x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#696969" stroke-width="2" stroke-dasharray="10,5"/>"##,
+                x1, y1, x2, y2
+            );
+        }
+    }
+
+    // Cities, ringed in the colour of the controlling side
+    for map_hex in &map.hexes {
+        let city = match map_hex.city {
+            Some(ref city) => city,
+            None => continue,
+        };
+        let (x, y) = hex_center(&map_hex.hex(), size);
+        let ring = match state.get_city_control(&city.name) {
+            Some(side) => side_colors(side).1,
+            None => "#b8860b",
+        };
+        let radius = if city.is_moscow { size * 0.4 } else { size * 0.27 };
+        let _ = writeln!(
+            svg,
+            r##"<circle class="city" cx="{:.1}" cy="{:.1}" r="{:.1}" fill="#ffd700" stroke="{}" stroke-width="2"/>"##,
+            x, y, radius, ring
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text class="city-name" x="{:.1}" y="{:.1}" font-size="{:.1}" font-weight="{}" text-anchor="middle" fill="#1a1a1a">{}</text>"##,
+            x,
+            y - size * 0.55,
+            size * 0.35,
+            if city.is_moscow { "bold" } else { "normal" },
+            escape(&city.name)
+        );
+    }
+
+    // Zone of control overlay
+    if let Some(side) = options.zoc {
+        let opposing = match side {
+            Side::German => Side::Soviet,
+            Side::Soviet => Side::German,
+        };
+        let mut zoc: Vec<Hex> = calculate_enemy_zoc(state, units, opposing)
+            .into_iter()
+            .filter(|hex| map.get_hex(hex).is_some())
+            .collect();
+        zoc.sort_by_key(|hex| (hex.q, hex.r));
+        let color = side_colors(side).0;
+        for hex in zoc {
+            let _ = writeln!(
+                svg,
+                r#"<polygon class="zoc" points="{}" fill="{}" fill-opacity="0.3" stroke="none"/>"#,
+                hex_points(&hex, size, size),
+                color
+            );
+        }
+    }
+
+    // Highlighted hexes
+    for hex in &options.highlight {
+        let _ = writeln!(
+            svg,
+            r##"<polygon class="highlight" points="{}" fill="#2ecc71" fill-opacity="0.4" stroke="#27ae60" stroke-width="3"/>"##,
+            hex_points(hex, size, size * 0.92)
+        );
+    }
+
+    // Coordinate labels
+    if options.labels != CoordinateLabels::None {
+        for map_hex in &map.hexes {
+            let hex = map_hex.hex();
+            let (x, y) = hex_center(&hex, size);
+            let label = match options.labels {
+                CoordinateLabels::Original => hex.to_original().map_or_else(String::new, |o| o.to_string()),
+                _ => format!("{},{}", hex.q, hex.r),
+            };
+            let _ = writeln!(
+                svg,
+                r##"<text class="coord" x="{:.1}" y="{:.1}" font-size="{:.1}" text-anchor="middle" fill="#333" fill-opacity="0.7">{}</text>"##,
+                x,
+                y + size * 0.8,
+                size * 0.25,
+                label
+            );
+        }
+    }
+
+    // Unit counters
+    for unit_state in &state.units {
+        let hex = match unit_state.hex() {
+            Some(hex) => hex,
+            None => continue,
+        };
+        let unit_def = match units.get(&unit_state.id) {
+            Some(def) => def,
+            None => continue,
+        };
+        let (x, y) = hex_center(&hex, size);
+        let (fill, stroke) = side_colors(unit_def.side);
+        let (w, h) = (size * 1.2, size * 0.9);
+        let _ = writeln!(
+            svg,
+            r#"<g class="unit" data-unit-id="{}">"#,
+            escape(&unit_state.id)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="3" fill="{}" stroke="{}" stroke-width="2"/>"#,
+            x - w / 2.0,
+            y - h / 2.0,
+            w,
+            h,
+            fill,
+            stroke
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" text-anchor="middle" fill="white">{}</text>"#,
+            x,
+            y - h * 0.05,
+            size * 0.32,
+            escape(&unit_state.id)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-weight="bold" text-anchor="middle" fill="white">{}-{}</text>"#,
+            x,
+            y + h * 0.38,
+            size * 0.3,
+            unit_def.get_combat_strength(&unit_state.strength),
+            unit_def.movement
+        );
+        if unit_state.strength == UnitStrength::Half {
+            let _ = writeln!(
+                svg,
+                r#"<circle class="half-strength" cx="{:.1}" cy="{:.1}" r="{:.1}" fill="white"/>"#,
+                x + w / 2.0 - size * 0.12,
+                y - h / 2.0 + size * 0.12,
+                size * 0.08
+            );
+        }
+        svg.push_str("</g>\n");
+    }
+
+    // Turn and phase caption
+    if options.title {
+        let mud = if state.is_mud() { " (Mud)" } else { "" };
+        let _ = writeln!(
+            svg,
+            r##"<text class="title" x="{:.1}" y="{:.1}" font-size="20" font-weight="bold" fill="#1a1a1a">Turn {}{} — {}</text>"##,
+            view_x + MARGIN,
+            view_y + MARGIN + 8.0,
+            state.turn,
+            mud,
+            state.phase.display_name()
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Convert axial coordinates to the pixel centre of a hex
+fn hex_center(hex: &Hex, size: f64) -> (f64, f64) {
+    let sqrt3 = 3f64.sqrt();
+    let x = size * (sqrt3 * hex.q as f64 + sqrt3 / 2.0 * hex.r as f64);
+    let y = size * 1.5 * hex.r as f64;
+    (x, y)
+}
+
+/// Get a corner of a pointy-top hex; corner 0 is upper right, continuing clockwise
+fn hex_corner(hex: &Hex, size: f64, corner: usize) -> (f64, f64) {
+    let (x, y) = hex_center(hex, size);
+    let angle = (60.0 * corner as f64 - 30.0).to_radians();
+    (x + size * angle.cos(), y + size * angle.sin())
+}
+
+/// Get the two corners bounding the edge facing a direction
+fn edge_corners(direction: Direction) -> (usize, usize) {
+    match direction {
+        Direction::E => (0, 1),
+        Direction::SE => (1, 2),
+        Direction::SW => (2, 3),
+        Direction::W => (3, 4),
+        Direction::NW => (4, 5),
+        Direction::NE => (5, 0),
+    }
+}
+
+/// Build the polygon points for a hex, optionally inset by drawing at a smaller radius
+fn hex_points(hex: &Hex, size: f64, radius: f64) -> String {
+    let (cx, cy) = hex_center(hex, size);
+    (0..6)
+        .map(|corner| {
+            let angle = (60.0 * corner as f64 - 30.0).to_radians();
+            format!("{:.1},{:.1}", cx + radius * angle.cos(), cy + radius * angle.sin())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fill and stroke colours for a side, matching static/style.css
+fn side_colors(side: Side) -> (&'static str, &'static str) {
+    match side {
+        Side::German => ("#666666", "#333333"),
+        Side::Soviet => ("#c41e3a", "#8b0000"),
+    }
+}
+
+/// Escape text for use in SVG content and attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, unit};
+    use crate::unit::{UnitState, UnitType};
+
+    fn test_map() -> Map {
+        let mut map = test_support::test_map(4, 1, Some(Hex::new(3, 0)));
+        for hex in &mut map.hexes {
+            hex.rail = true;
+        }
+        map.hexes[1].river_edges = vec!["E".to_string()];
+        map.hexes[2].river_edges = vec!["W".to_string()];
+        map.hexes[2].terrain = Terrain::Forest;
+        map.hexes[2].fortification = true;
+        map
+    }
+
+    fn test_units() -> Units {
+        Units {
+            units: vec![unit("XLVII", Side::German, UnitType::Panzer, 9, 6)],
+        }
+    }
+
+    fn count(svg: &str, needle: &str) -> usize {
+        svg.matches(needle).count()
+    }
+
+    #[test]
+    fn test_render_map_features() {
+        let map = test_map();
+        let state = GameState::new();
+        let svg = render_svg(&map, &test_units(), &state, &RenderOptions::default());
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(count(&svg, r#"class="hex""#), 4);
+        // The mirrored river edge is drawn once
+        assert_eq!(count(&svg, r#"class="river""#), 1);
+        assert_eq!(count(&svg, r#"class="rail""#), 3);
+        assert_eq!(count(&svg, r#"class="fortification""#), 1);
+        assert_eq!(count(&svg, r#"class="city""#), 1);
+        assert!(svg.contains(">Moscow</text>"));
+        assert!(svg.contains("Turn 1 — German Panzer Movement"));
+    }
+
+    #[test]
+    fn test_render_units_and_overlays() {
+        let map = test_map();
+        let mut state = GameState::new();
+        state.units.push(UnitState::new(
+            "XLVII".to_string(),
+            Some(Hex::new(1, 0)),
+            UnitStrength::Half,
+        ));
+
+        let options = RenderOptions {
+            labels: CoordinateLabels::Original,
+            zoc: Some(Side::German),
+            highlight: vec![Hex::new(0, 0), Hex::new(3, 0)],
+            title: false,
+            ..RenderOptions::default()
+        };
+        let svg = render_svg(&map, &test_units(), &state, &options);
+
+        assert!(svg.contains(r#"data-unit-id="XLVII""#));
+        assert!(svg.contains(">4-6</text>"));
+        assert_eq!(count(&svg, r#"class="half-strength""#), 1);
+        // Only the on-map neighbours of the panzer are shaded
+        assert_eq!(count(&svg, r#"class="zoc""#), 2);
+        assert_eq!(count(&svg, r#"class="highlight""#), 2);
+        assert!(svg.contains(">0301</text>"));
+        assert!(!svg.contains(r#"class="title""#));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<A & \"B\">"), "&lt;A &amp; &quot;B&quot;&gt;");
+    }
+}
@@ -1,8 +1,11 @@
+use crate::error::RuleError;
 use crate::game_state::GameState;
 use crate::hex::Hex;
 use crate::map::Map;
-use crate::unit::{Side, Units};
+use crate::unit::{Side, UnitStrength, Units};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
+use utoipa::ToSchema;
 
 /// Check if a hex can trace a path to the friendly map edge (communication line)
 pub fn can_trace_communication(
@@ -108,10 +111,10 @@ pub fn get_valid_replacement_hexes(
                 }
                 Side::Soviet => {
                     // Only Moscow
-                    if city.is_moscow {
-                        if can_trace_communication(&hex, friendly_side, state, units, map) {
-                            valid_hexes.push(hex);
-                        }
+                    if city.is_moscow
+                        && can_trace_communication(&hex, friendly_side, state, units, map)
+                    {
+                        valid_hexes.push(hex);
                     }
                 }
             }
@@ -128,37 +131,37 @@ pub fn validate_replacement_placement(
     state: &GameState,
     units: &Units,
     map: &Map,
-) -> Result<(), String> {
+) -> Result<(), RuleError> {
     // Get the unit
     let unit_state = state
         .get_unit(unit_id)
-        .ok_or_else(|| format!("Unit {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     let unit_def = units
         .get(unit_id)
-        .ok_or_else(|| format!("Unit definition for {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Unit must be eliminated or at half strength
     if unit_state.strength == crate::unit::UnitStrength::Full {
-        return Err("Unit is at full strength and cannot receive replacements".to_string());
+        return Err(RuleError::FullStrength);
     }
 
     // For Germans: must have replacement available
     if unit_def.side == Side::German {
         if state.german_replacement_used {
-            return Err("German replacement already used this turn".to_string());
+            return Err(RuleError::ReplacementUsed);
         }
     } else {
         // For Soviets: must have replacements remaining
         if state.soviet_replacements_remaining <= 0 {
-            return Err("No Soviet replacements remaining".to_string());
+            return Err(RuleError::NoReplacementsLeft);
         }
     }
 
     // Check if this hex is a valid replacement hex
     let valid_hexes = get_valid_replacement_hexes(unit_def.side, state, units, map);
     if !valid_hexes.contains(hex) {
-        return Err("Invalid replacement hex - must be a controlled city with communication".to_string());
+        return Err(RuleError::InvalidReplacementHex);
     }
 
     // If unit is eliminated, hex must be unoccupied
@@ -167,7 +170,7 @@ pub fn validate_replacement_placement(
         for other_unit in &state.units {
             if let Some(other_hex) = other_unit.hex() {
                 if other_hex == *hex && other_unit.id != unit_id {
-                    return Err("Hex is occupied by another unit".to_string());
+                    return Err(RuleError::HexOccupied);
                 }
             }
         }
@@ -176,7 +179,7 @@ pub fn validate_replacement_placement(
         // or must be in a valid replacement hex
         if let Some(current_hex) = unit_state.hex() {
             if current_hex != *hex && !valid_hexes.contains(&current_hex) {
-                return Err("Unit must be in a valid replacement hex or be placed in one".to_string());
+                return Err(RuleError::NotInReplacementHex);
             }
         }
     }
@@ -184,21 +187,96 @@ pub fn validate_replacement_placement(
     Ok(())
 }
 
+/// Replacements a side may still use this turn
+pub fn replacements_remaining(side: Side, state: &GameState) -> i32 {
+    match side {
+        Side::German if state.german_replacement_used => 0,
+        Side::German => 1,
+        Side::Soviet => state.soviet_replacements_remaining.max(0),
+    }
+}
+
+/// A unit a replacement could be spent on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReplacementOption {
+    pub unit_id: String,
+    pub strength: UnitStrength,
+    /// Where a half-strength unit stands; `None` for an eliminated unit
+    pub hex: Option<Hex>,
+    /// Where an eliminated unit may be brought back at half strength
+    pub placements: Vec<Hex>,
+}
+
+/// Every unit of `side` a replacement could restore or bring back, in roster order
+///
+/// Half-strength units need communication to the friendly edge, except Soviet units in
+/// Moscow. Eliminated units need an empty replacement hex and must have arrived.
+pub fn replacement_options(
+    side: Side,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Vec<ReplacementOption> {
+    let hexes = get_valid_replacement_hexes(side, state, units, map);
+    let empty_hexes: Vec<Hex> = hexes
+        .into_iter()
+        .filter(|hex| state.get_units_at(hex).is_empty())
+        .collect();
+
+    let mut options = Vec::new();
+    for unit_def in units.units.iter().filter(|def| def.side == side) {
+        let Some(unit_state) = state.get_unit(&unit_def.id) else {
+            continue;
+        };
+        let arrived = unit_def
+            .available_turn
+            .is_none_or(|turn| state.turn >= turn);
+
+        match (&unit_state.strength, unit_state.hex()) {
+            (UnitStrength::Half, Some(hex)) => {
+                let in_moscow = map
+                    .get_hex(&hex)
+                    .and_then(|map_hex| map_hex.city.as_ref())
+                    .is_some_and(|city| city.is_moscow);
+                let supplied = can_trace_communication(&hex, side, state, units, map);
+                if supplied || (in_moscow && side == Side::Soviet) {
+                    options.push(ReplacementOption {
+                        unit_id: unit_def.id.clone(),
+                        strength: UnitStrength::Half,
+                        hex: Some(hex),
+                        placements: Vec::new(),
+                    });
+                }
+            }
+            (UnitStrength::Eliminated, _) if arrived && !empty_hexes.is_empty() => {
+                options.push(ReplacementOption {
+                    unit_id: unit_def.id.clone(),
+                    strength: UnitStrength::Eliminated,
+                    hex: None,
+                    placements: empty_hexes.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    options
+}
+
 /// Apply a replacement to a unit
 pub fn apply_replacement(
     unit_id: &str,
     hex: Option<&Hex>,
     state: &mut GameState,
     units: &Units,
-) -> Result<(), String> {
+) -> Result<(), RuleError> {
     let unit_def = units
         .get(unit_id)
-        .ok_or_else(|| format!("Unit definition for {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Get the unit
     let unit_state = state
         .get_unit_mut(unit_id)
-        .ok_or_else(|| format!("Unit {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Restore one step
     unit_state.restore();
@@ -226,9 +304,11 @@ pub fn apply_replacement(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{test_map, unit};
     use crate::unit::{UnitDefinition, UnitState, UnitStrength, UnitType};
 
     #[test]
+    #[allow(unused_variables, unused_mut)]
     fn test_can_trace_communication_simple() {
         // Create a simple test setup
         let mut state = GameState::new();
@@ -238,6 +318,27 @@ mod tests {
         // For now, it's a structure test
     }
 
+    #[test]
+    fn test_enemy_unit_cuts_communication() {
+        let mut state = GameState::new();
+        let units = Units {
+            units: vec![unit("V", Side::German, UnitType::Infantry, 6, 4)],
+        };
+
+        // A single row of hexes, Soviet edge at q = 3
+        let map = test_map(4, 1, None);
+
+        assert!(can_trace_communication(&Hex::new(1, 0), Side::Soviet, &state, &units, &map));
+
+        // An enemy unit in the only path cuts communication
+        state.units.push(UnitState::new(
+            "V".to_string(),
+            Some(Hex::new(2, 0)),
+            UnitStrength::Full,
+        ));
+        assert!(!can_trace_communication(&Hex::new(1, 0), Side::Soviet, &state, &units, &map));
+    }
+
     #[test]
     fn test_validate_replacement_full_strength_unit() {
         let mut state = GameState::new();
@@ -247,34 +348,62 @@ mod tests {
             UnitStrength::Full,
         ));
 
-        let mut units = Units { units: Vec::new() };
-        units.units.push(UnitDefinition {
-            id: "5".to_string(),
-            side: Side::Soviet,
-            unit_type: UnitType::Infantry,
-            full_strength: 8,
-            half_strength: 4,
-            movement: 4,
-            available_turn: None,
-        });
+        let units = Units {
+            units: vec![unit("5", Side::Soviet, UnitType::Infantry, 8, 4)],
+        };
 
         // Create a minimal map
-        let map = Map {
-            hexes: vec![],
-            map_bounds: crate::map::MapBounds {
-                min_q: 0,
-                max_q: 10,
-                min_r: 0,
-                max_r: 10,
-            },
-            edges: crate::map::MapEdges {
-                west: "german_communication".to_string(),
-                east: "soviet_communication".to_string(),
-            },
-        };
+        let map = test_map(11, 11, None);
 
         // Should fail because unit is at full strength
         let result = validate_replacement_placement("5", &Hex::new(5, 3), &state, &units, &map);
         assert!(result.is_err());
     }
+    #[test]
+    fn test_replacement_options() {
+        // A row of hexes with Moscow in the middle and the Soviet edge at q = 4
+        let mut state = GameState::new();
+        let infantry = |id: &str, side| unit(id, side, UnitType::Infantry, 8, 4);
+        let units = Units {
+            units: vec![
+                infantry("5", Side::Soviet),
+                infantry("16", Side::Soviet),
+                infantry("29", Side::Soviet),
+                UnitDefinition {
+                    available_turn: Some(4),
+                    ..infantry("1S", Side::Soviet)
+                },
+                infantry("V", Side::German),
+            ],
+        };
+        let map = test_map(5, 1, Some(Hex::new(2, 0)));
+        state.update_city_control("Moscow", Side::Soviet);
+        for (id, q, strength) in [
+            ("5", Some(3), UnitStrength::Half),
+            ("16", Some(0), UnitStrength::Half),
+            ("29", None, UnitStrength::Eliminated),
+            ("1S", None, UnitStrength::Eliminated),
+            ("V", Some(1), UnitStrength::Full),
+        ] {
+            let hex = q.map(|q| Hex::new(q, 0));
+            state
+                .units
+                .push(UnitState::new(id.to_string(), hex, strength));
+        }
+
+        // 16 is cut off behind the German unit and 1st Shock has not arrived
+        let options = replacement_options(Side::Soviet, &state, &units, &map);
+        let ids: Vec<&str> = options.iter().map(|o| o.unit_id.as_str()).collect();
+        assert_eq!(ids, vec!["5", "29"]);
+        assert_eq!(options[0].hex, Some(Hex::new(3, 0)));
+        assert_eq!(options[1].placements, vec![Hex::new(2, 0)]);
+
+        state.turn = 4;
+        let options = replacement_options(Side::Soviet, &state, &units, &map);
+        assert_eq!(options.len(), 3);
+        state.soviet_replacements_remaining = 5;
+        assert_eq!(replacements_remaining(Side::Soviet, &state), 5);
+        state.german_replacement_used = true;
+        assert_eq!(replacements_remaining(Side::German, &state), 0);
+    }
 }
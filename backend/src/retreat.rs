@@ -1,8 +1,10 @@
+use crate::error::RuleError;
 use crate::game_state::GameState;
 use crate::hex::Hex;
 use crate::map::Map;
-use crate::unit::{Side, Units};
+use crate::unit::Units;
 use crate::zoc::is_in_enemy_zoc;
+use utoipa::ToSchema;
 
 /// Find valid retreat hexes for a unit (2 hexes away from starting position)
 pub fn find_valid_retreat_hexes(
@@ -12,10 +14,9 @@ pub fn find_valid_retreat_hexes(
     units: &Units,
     map: &Map,
 ) -> Vec<Hex> {
-    let unit_def = match units.get(unit_id) {
-        Some(def) => def,
-        None => return Vec::new(),
-    };
+    if units.get(unit_id).is_none() {
+        return Vec::new();
+    }
 
     let mut valid_hexes = Vec::new();
 
@@ -87,7 +88,7 @@ fn can_retreat_through_path(
 }
 
 /// Check if there's an enemy unit at a hex (from any perspective)
-fn has_enemy_unit_at(hex: &Hex, state: &GameState, units: &Units) -> bool {
+fn has_enemy_unit_at(hex: &Hex, state: &GameState, _units: &Units) -> bool {
     for unit_state in &state.units {
         if unit_state.hex() == Some(*hex) {
             return true;
@@ -103,25 +104,22 @@ pub fn execute_retreat(
     state: &mut GameState,
     units: &Units,
     map: &Map,
-) -> Result<RetreatResult, String> {
+) -> Result<RetreatResult, RuleError> {
     // Get unit
     let unit_def = units
         .get(unit_id)
-        .ok_or_else(|| format!("Unit {} not found", unit_id))?;
+        .ok_or_else(|| RuleError::UnitNotFound(unit_id.to_string()))?;
 
     // Get current position
     let from_hex = state
         .get_unit(unit_id)
         .and_then(|u| u.hex())
-        .ok_or_else(|| format!("Unit {} has no position", unit_id))?;
+        .ok_or_else(|| RuleError::NotOnMap(unit_id.to_string()))?;
 
     // Validate retreat hex
     let valid_hexes = find_valid_retreat_hexes(unit_id, &from_hex, state, units, map);
     if !valid_hexes.contains(to_hex) {
-        return Err(format!(
-            "Invalid retreat hex ({}, {})",
-            to_hex.q, to_hex.r
-        ));
+        return Err(RuleError::InvalidRetreatHex(*to_hex));
     }
 
     // Check if retreating into enemy ZOC (unit is eliminated)
@@ -142,7 +140,7 @@ pub fn execute_retreat(
     Ok(RetreatResult::Success)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
 pub enum RetreatResult {
     Success,
     EliminatedInZoc,
@@ -151,7 +149,7 @@ pub enum RetreatResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::unit::{UnitDefinition, UnitState, UnitStrength, UnitType};
+    use crate::unit::{UnitState, UnitStrength};
 
     #[test]
     fn test_retreat_distance() {
@@ -0,0 +1,311 @@
+use crate::error::RuleError;
+use crate::unit::Side;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::HashMap;
+
+/// A place at the table: one of the two sides, or a spectator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Seat {
+    German,
+    Soviet,
+    Spectator,
+}
+
+impl From<Side> for Seat {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::German => Seat::German,
+            Side::Soviet => Seat::Soviet,
+        }
+    }
+}
+
+/// Which seats are taken, without revealing their tokens
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SeatSummary {
+    pub german: bool,
+    pub soviet: bool,
+    pub spectators: usize,
+    /// The side the server plays itself, if any
+    pub computer: Option<Side>,
+    /// Whether callers without a token may act for unclaimed sides
+    pub hotseat: bool,
+}
+
+/// Seats claimed at the table, keyed by their opaque tokens
+///
+/// Acting for a side takes that side's token. A game started for hotseat play on one
+/// screen also lets callers without a token act for sides nobody has claimed. A side
+/// given to the computer counts as claimed, and no token acts for it.
+#[derive(Debug, Default)]
+pub struct Seats {
+    tokens: HashMap<String, Seat>,
+    computer: Option<Side>,
+    hotseat: bool,
+}
+
+impl Seats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim a seat, returning the token that proves ownership
+    pub fn claim(&mut self, seat: Seat) -> Result<String, RuleError> {
+        if seat != Seat::Spectator && self.is_claimed(seat) {
+            return Err(RuleError::SeatTaken(seat));
+        }
+
+        let token = new_token();
+        self.tokens.insert(token.clone(), seat);
+        Ok(token)
+    }
+
+    /// Give up the seat held by a token
+    pub fn release(&mut self, token: &str) -> Result<Seat, RuleError> {
+        self.tokens.remove(token).ok_or(RuleError::UnknownSeatToken)
+    }
+
+    /// Get the seat a token was issued for
+    pub fn seat_for(&self, token: &str) -> Option<Seat> {
+        self.tokens.get(token).copied()
+    }
+
+    pub fn is_claimed(&self, seat: Seat) -> bool {
+        self.is_held(seat) || self.computer.is_some_and(|side| Seat::from(side) == seat)
+    }
+
+    /// Whether a player holds a token for the seat
+    fn is_held(&self, seat: Seat) -> bool {
+        self.tokens.values().any(|&s| s == seat)
+    }
+
+    /// Give a side to the computer, or take it back with `None`
+    pub fn set_computer(&mut self, side: Option<Side>) -> Result<(), RuleError> {
+        if let Some(side) = side {
+            if self.is_held(Seat::from(side)) {
+                return Err(RuleError::SeatTaken(Seat::from(side)));
+            }
+        }
+        self.computer = side;
+        Ok(())
+    }
+
+    /// The side the computer plays
+    pub fn computer(&self) -> Option<Side> {
+        self.computer
+    }
+
+    /// Allow or forbid token-less play for unclaimed sides
+    pub fn set_hotseat(&mut self, hotseat: bool) {
+        self.hotseat = hotseat;
+    }
+
+    pub fn summary(&self) -> SeatSummary {
+        SeatSummary {
+            german: self.is_claimed(Seat::German),
+            soviet: self.is_claimed(Seat::Soviet),
+            spectators: self
+                .tokens
+                .values()
+                .filter(|&&s| s == Seat::Spectator)
+                .count(),
+            computer: self.computer,
+            hotseat: self.hotseat,
+        }
+    }
+
+    /// Check that the caller may act for a side
+    ///
+    /// Only the side's own token does, or no token at all for an unclaimed side of a
+    /// hotseat game. Spectator and opponent tokens never do.
+    pub fn authorize(&self, token: Option<&str>, side: Side) -> Result<(), RuleError> {
+        if self.computer == Some(side) {
+            return Err(RuleError::ComputerSeat(side));
+        }
+        let seat = Seat::from(side);
+
+        match token.map(|t| self.seat_for(t)) {
+            Some(Some(held)) if held == seat => Ok(()),
+            Some(Some(held)) => Err(RuleError::WrongSeat(held, side)),
+            Some(None) => Err(RuleError::UnknownSeatToken),
+            None if self.hotseat && !self.is_claimed(seat) => Ok(()),
+            None => Err(RuleError::SeatTokenRequired(side)),
+        }
+    }
+
+    /// Check that the caller may act for the game as a whole, e.g. start a new game
+    ///
+    /// Allowed for either side's token, or without a token while no player holds a
+    /// side's seat. Spectators never may.
+    pub fn authorize_player(&self, token: Option<&str>) -> Result<(), RuleError> {
+        match token.map(|t| self.seat_for(t)) {
+            Some(Some(Seat::German)) | Some(Some(Seat::Soviet)) => Ok(()),
+            Some(Some(Seat::Spectator)) => Err(RuleError::NotSeated),
+            Some(None) => Err(RuleError::UnknownSeatToken),
+            None if !self.is_held(Seat::German) && !self.is_held(Seat::Soviet) => Ok(()),
+            None => Err(RuleError::NotSeated),
+        }
+    }
+
+    /// Check that the caller may import a turn file played by `side`
+    ///
+    /// As for `authorize_player`, except that once someone else holds the side's seat,
+    /// only that seat may import the side's turns.
+    pub fn authorize_import(&self, token: Option<&str>, side: Side) -> Result<(), RuleError> {
+        self.authorize_player(token)?;
+        let seat = Seat::from(side);
+        match token.and_then(|t| self.seat_for(t)) {
+            Some(held) if held != seat && self.is_claimed(seat) => {
+                Err(RuleError::WrongSeat(held, side))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Generate an unguessable 128-bit token as hex
+fn new_token() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_sides_once() {
+        let mut seats = Seats::new();
+        let german = seats.claim(Seat::German).unwrap();
+        assert_eq!(german.len(), 32);
+        assert!(seats.claim(Seat::German).is_err());
+
+        let soviet = seats.claim(Seat::Soviet).unwrap();
+        assert_ne!(german, soviet);
+        seats.claim(Seat::Spectator).unwrap();
+        seats.claim(Seat::Spectator).unwrap();
+
+        assert_eq!(
+            seats.summary(),
+            SeatSummary {
+                german: true,
+                soviet: true,
+                spectators: 2,
+                computer: None,
+                hotseat: false,
+            }
+        );
+
+        assert_eq!(seats.release(&german), Ok(Seat::German));
+        assert!(seats.release(&german).is_err());
+        assert!(seats.claim(Seat::German).is_ok());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let mut seats = Seats::new();
+
+        // Unclaimed sides still need a token outside hotseat games
+        assert_eq!(
+            seats.authorize(None, Side::German),
+            Err(RuleError::SeatTokenRequired(Side::German))
+        );
+        assert!(seats.authorize_player(None).is_ok());
+
+        let german = seats.claim(Seat::German).unwrap();
+        let spectator = seats.claim(Seat::Spectator).unwrap();
+
+        assert!(seats.authorize(Some(&german), Side::German).is_ok());
+        assert!(seats.authorize(None, Side::German).is_err());
+        assert!(seats.authorize(Some("forged"), Side::German).is_err());
+        assert!(seats.authorize(Some(&spectator), Side::German).is_err());
+
+        // Neither the opponent nor a spectator may play the unclaimed Soviet side
+        assert_eq!(
+            seats.authorize(Some(&german), Side::Soviet),
+            Err(RuleError::WrongSeat(Seat::German, Side::Soviet))
+        );
+        assert_eq!(
+            seats.authorize(Some(&spectator), Side::Soviet),
+            Err(RuleError::WrongSeat(Seat::Spectator, Side::Soviet))
+        );
+        assert!(seats.authorize(None, Side::Soviet).is_err());
+
+        assert!(seats.authorize_player(Some(&german)).is_ok());
+        assert!(seats.authorize_player(Some(&spectator)).is_err());
+        assert!(seats.authorize_player(None).is_err());
+    }
+
+    #[test]
+    fn test_authorize_import() {
+        let mut seats = Seats::new();
+        let soviet = seats.claim(Seat::Soviet).unwrap();
+
+        // A play-by-email opponent's turn arrives while their seat here is open
+        assert!(seats.authorize_import(Some(&soviet), Side::German).is_ok());
+        assert!(seats.authorize_import(Some(&soviet), Side::Soviet).is_ok());
+
+        // Once the Germans are seated, only they import German turns
+        let german = seats.claim(Seat::German).unwrap();
+        assert_eq!(
+            seats.authorize_import(Some(&soviet), Side::German),
+            Err(RuleError::WrongSeat(Seat::Soviet, Side::German))
+        );
+        assert!(seats.authorize_import(Some(&german), Side::German).is_ok());
+        assert!(seats.authorize_import(None, Side::German).is_err());
+    }
+
+    #[test]
+    fn test_hotseat() {
+        let mut seats = Seats::new();
+        seats.set_hotseat(true);
+        assert!(seats.summary().hotseat);
+
+        // Open sides accept callers without a token
+        assert!(seats.authorize(None, Side::German).is_ok());
+        assert!(seats.authorize(None, Side::Soviet).is_ok());
+
+        // ...but never a spectator, the opponent or a forged token
+        let german = seats.claim(Seat::German).unwrap();
+        let spectator = seats.claim(Seat::Spectator).unwrap();
+        assert!(seats.authorize(None, Side::German).is_err());
+        assert!(seats.authorize(Some(&german), Side::Soviet).is_err());
+        assert!(seats.authorize(Some(&spectator), Side::Soviet).is_err());
+        assert_eq!(
+            seats.authorize(Some("forged"), Side::Soviet),
+            Err(RuleError::UnknownSeatToken)
+        );
+        assert!(seats.authorize(None, Side::Soviet).is_ok());
+    }
+
+    #[test]
+    fn test_computer_seat() {
+        let mut seats = Seats::new();
+        let german = seats.claim(Seat::German).unwrap();
+        assert_eq!(
+            seats.set_computer(Some(Side::German)),
+            Err(RuleError::SeatTaken(Seat::German))
+        );
+
+        seats.set_computer(Some(Side::Soviet)).unwrap();
+        assert_eq!(seats.summary().computer, Some(Side::Soviet));
+        assert!(seats.claim(Seat::Soviet).is_err());
+        assert_eq!(
+            seats.authorize(Some(&german), Side::Soviet),
+            Err(RuleError::ComputerSeat(Side::Soviet))
+        );
+
+        // The computer does not lock players out of starting a new game
+        seats.release(&german).unwrap();
+        assert!(seats.authorize_player(None).is_ok());
+        seats.set_hotseat(true);
+        assert!(seats.authorize(None, Side::German).is_ok());
+        assert!(seats.authorize(None, Side::Soviet).is_err());
+
+        seats.set_computer(None).unwrap();
+        assert!(seats.authorize(None, Side::Soviet).is_ok());
+    }
+}
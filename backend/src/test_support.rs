@@ -0,0 +1,65 @@
+use crate::hex::Hex;
+use crate::map::{City, Map, MapBounds, MapEdges, MapHex, Terrain};
+use crate::unit::{Side, UnitDefinition, UnitType, Units};
+
+/// The units and map the game ships with
+pub fn bundled() -> (Units, Map) {
+    let map = Map::load_from_json(include_str!("../../data/map.json")).unwrap();
+    let units = Units::load_from_json(include_str!("../../data/units.json")).unwrap();
+    (units, map)
+}
+
+/// A clear hex with no city, rail, river or fortification
+pub fn clear(q: i32, r: i32) -> MapHex {
+    MapHex {
+        q,
+        r,
+        terrain: Terrain::Clear,
+        city: None,
+        fortification: false,
+        rail: false,
+        river_edges: Vec::new(),
+        setup: None,
+    }
+}
+
+/// A clear map `width` hexes wide and `height` rows tall, with Moscow at `moscow`
+pub fn test_map(width: i32, height: i32, moscow: Option<Hex>) -> Map {
+    let hexes = (0..width)
+        .flat_map(|q| (0..height).map(move |r| (q, r)))
+        .map(|(q, r)| MapHex {
+            city: (moscow == Some(Hex::new(q, r))).then(|| City {
+                name: "Moscow".to_string(),
+                is_moscow: true,
+            }),
+            ..clear(q, r)
+        })
+        .collect();
+
+    Map {
+        hexes,
+        map_bounds: MapBounds {
+            min_q: 0,
+            max_q: width - 1,
+            min_r: 0,
+            max_r: height - 1,
+        },
+        edges: MapEdges {
+            west: "german_communication".to_string(),
+            east: "soviet_communication".to_string(),
+        },
+    }
+}
+
+/// A unit available from the start, at half strength once reduced
+pub fn unit(id: &str, side: Side, unit_type: UnitType, full: i32, movement: i32) -> UnitDefinition {
+    UnitDefinition {
+        id: id.to_string(),
+        side,
+        unit_type,
+        full_strength: full,
+        half_strength: full / 2,
+        movement,
+        available_turn: None,
+    }
+}
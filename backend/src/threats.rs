@@ -0,0 +1,691 @@
+use crate::combat::{preview_battle, BattlePreview, CombatResult};
+use crate::game_state::{GameState, Phase};
+use crate::hex::Hex;
+use crate::map::Map;
+use crate::movement::find_move_options;
+use crate::unit::{Side, Units};
+use crate::zoc::calculate_enemy_zoc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use utoipa::ToSchema;
+
+/// Chance of losing a city or approach at which it counts as weakly held
+pub const WEAK_HOLD_CHANCE: f64 = 0.5;
+/// Separate routes to the map edge a unit needs before it counts as safe from encirclement
+const MAX_ROUTES: usize = 3;
+/// Capacity of a hex the enemy cannot close in one move
+const OPEN: usize = usize::MAX;
+
+/// A friendly unit the enemy can attack next phase, with the strongest attack it can mount
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct UnitThreat {
+    pub unit: String,
+    pub hex: Hex,
+    /// Enemy units able to stand next to the unit, strongest first
+    pub attackers: Vec<String>,
+    /// The battle if all of them attack from the hexes they would move to
+    pub best_attack: BattlePreview,
+    /// Chance the unit is forced out or destroyed: DR, DRL or DE
+    pub dislodge_chance: f64,
+}
+
+/// A friendly unit whose communication with its map edge is cut or could be
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CutOffRisk {
+    pub unit: String,
+    pub hex: Hex,
+    /// Separate routes to the map edge; 0 when already cut off
+    pub routes: usize,
+    /// Empty hexes every route passes through, nearest the unit
+    pub chokepoints: Vec<Hex>,
+    /// Enemy units that could close the chokepoints next phase
+    pub enemies: Vec<String>,
+}
+
+/// A city or Moscow approach the side holds that could be lost next phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeakPoint {
+    /// The city's name, or "Moscow approach" for a hex next to Moscow
+    pub name: String,
+    pub hex: Hex,
+    /// Friendly unit holding the hex
+    pub garrison: Option<String>,
+    /// Enemy units already in the hex, able to move into it or attack its garrison
+    pub enemies: Vec<String>,
+    /// 1 for an open hex the enemy can enter, or the garrison's dislodge chance
+    pub loss_chance: f64,
+}
+
+/// An empty hex behind the friendly line that the enemy can reach without entering its ZOC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FrontGap {
+    pub hex: Hex,
+    /// Enemy units able to move there
+    pub enemies: Vec<String>,
+}
+
+/// What the enemy could do to one side next phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ThreatReport {
+    /// The side under threat
+    pub side: Side,
+    /// Most likely to be dislodged first
+    pub unit_threats: Vec<UnitThreat>,
+    pub cut_off_risks: Vec<CutOffRisk>,
+    /// Most likely to be lost first
+    pub weak_points: Vec<WeakPoint>,
+    pub front_gaps: Vec<FrontGap>,
+}
+
+/// Where each enemy unit could stand after its next ordinary move
+struct EnemyReach {
+    /// Enemy unit IDs with their combat strength and hexes, strongest first
+    units: Vec<(String, i32, Hex, HashSet<Hex>)>,
+}
+
+impl EnemyReach {
+    fn new(enemy: Side, state: &GameState, units: &Units, map: &Map) -> Self {
+        // Movement ranges as in the enemy's regular movement phase
+        let mut moving = state.clone();
+        moving.phase = match enemy {
+            Side::German => Phase::GermanMovement,
+            Side::Soviet => Phase::SovietMovement,
+        };
+
+        let mut reach: Vec<(String, i32, Hex, HashSet<Hex>)> = Vec::new();
+        for unit_state in &state.units {
+            let (Some(hex), Some(def)) = (unit_state.hex(), units.get(&unit_state.id)) else {
+                continue;
+            };
+            if def.side != enemy {
+                continue;
+            }
+            let mut hexes: HashSet<Hex> = find_move_options(&unit_state.id, &moving, units, map)
+                .map(|options| options.into_iter().map(|option| option.hex).collect())
+                .unwrap_or_default();
+            hexes.insert(hex);
+            let strength = def.get_combat_strength(&unit_state.strength);
+            reach.push((unit_state.id.clone(), strength, hex, hexes));
+        }
+        reach.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        EnemyReach { units: reach }
+    }
+
+    /// Enemy units able to end their move in a hex
+    fn reaching(&self, hex: &Hex) -> Vec<String> {
+        self.units
+            .iter()
+            .filter(|(_, _, _, hexes)| hexes.contains(hex))
+            .map(|(id, _, _, _)| id.clone())
+            .collect()
+    }
+
+    fn can_reach(&self, hex: &Hex) -> bool {
+        self.units
+            .iter()
+            .any(|(_, _, _, hexes)| hexes.contains(hex))
+    }
+}
+
+/// Analyze the threats the enemy poses to `side` in its next phase
+///
+/// Enemy units are assumed to move as in their regular movement phase, ignoring each
+/// other, so a threat counted against two units cannot always be carried out against both.
+pub fn analyze_threats(side: Side, state: &GameState, units: &Units, map: &Map) -> ThreatReport {
+    let enemy = match side {
+        Side::German => Side::Soviet,
+        Side::Soviet => Side::German,
+    };
+    let reach = EnemyReach::new(enemy, state, units, map);
+    let occupied: HashMap<Hex, (String, Side)> = state
+        .units
+        .iter()
+        .filter_map(|u| Some((u.hex()?, (u.id.clone(), units.get(&u.id)?.side))))
+        .collect();
+    let friendly: Vec<(String, Hex)> = occupied
+        .iter()
+        .filter(|(_, (_, s))| *s == side)
+        .map(|(hex, (id, _))| (id.clone(), *hex))
+        .collect();
+
+    let mut unit_threats: Vec<UnitThreat> = friendly
+        .iter()
+        .filter_map(|(id, hex)| unit_threat(id, hex, &reach, &occupied, state, units, map))
+        .collect();
+    unit_threats.sort_by(|a, b| {
+        b.dislodge_chance
+            .total_cmp(&a.dislodge_chance)
+            .then(a.unit.cmp(&b.unit))
+    });
+
+    let mut cut_off_risks: Vec<CutOffRisk> = friendly
+        .iter()
+        .filter_map(|(id, hex)| cut_off_risk(id, hex, side, &reach, &occupied, map))
+        .collect();
+    cut_off_risks.sort_by(|a, b| a.routes.cmp(&b.routes).then(a.unit.cmp(&b.unit)));
+
+    let weak_points = weak_points(side, &reach, &occupied, &unit_threats, state, map);
+    let front_gaps = front_gaps(side, enemy, &reach, &occupied, &friendly, state, units, map);
+
+    ThreatReport {
+        side,
+        unit_threats,
+        cut_off_risks,
+        weak_points,
+        front_gaps,
+    }
+}
+
+/// The strongest attack the enemy could make on a unit, if any
+///
+/// Units already adjacent attack from where they stand; the rest take the free
+/// neighbouring hexes they can reach, strongest first, avoiding river crossings.
+fn unit_threat(
+    unit: &str,
+    hex: &Hex,
+    reach: &EnemyReach,
+    occupied: &HashMap<Hex, (String, Side)>,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Option<UnitThreat> {
+    let river = |from: &Hex| {
+        from.direction_to(hex)
+            .zip(map.get_hex(hex))
+            .is_some_and(|(direction, map_hex)| map_hex.has_river_edge(direction))
+    };
+    let mut free: Vec<Hex> = hex
+        .neighbors()
+        .into_iter()
+        .filter(|n| map.get_hex(n).is_some() && !occupied.contains_key(n))
+        .collect();
+    // Hexes without a river toward the unit are taken first
+    free.sort_by_key(|n| (river(n), n.q, n.r));
+
+    let mut attackers = Vec::new();
+    let mut hypothetical = state.clone();
+    for (id, _, at, hexes) in &reach.units {
+        if at.is_adjacent(hex) {
+            attackers.push(id.clone());
+        } else if let Some(i) = free.iter().position(|n| hexes.contains(n)) {
+            let to = free.remove(i);
+            hypothetical.get_unit_mut(id)?.move_to(to);
+            attackers.push(id.clone());
+        }
+    }
+    if attackers.is_empty() {
+        return None;
+    }
+
+    let best_attack = preview_battle(&attackers, unit, &hypothetical, units, map).ok()?;
+    let dislodge_chance = best_attack
+        .outcomes
+        .iter()
+        .filter(|o| {
+            matches!(
+                o.result,
+                CombatResult::DR | CombatResult::DRL | CombatResult::DE
+            )
+        })
+        .fold(0.0, |total, o| total + o.probability);
+    Some(UnitThreat {
+        unit: unit.to_string(),
+        hex: *hex,
+        attackers,
+        best_attack,
+        dislodge_chance,
+    })
+}
+
+/// How many separate routes a unit has to its map edge and whether the enemy could close them
+///
+/// Routes are counted as a maximum flow over hexes not held by the enemy, where an empty
+/// hex the enemy can reach carries one route and any other hex carries any number. The
+/// narrowest set of such hexes nearest the unit is at risk when enough different enemy
+/// units can each move into one of them.
+fn cut_off_risk(
+    unit: &str,
+    hex: &Hex,
+    side: Side,
+    reach: &EnemyReach,
+    occupied: &HashMap<Hex, (String, Side)>,
+    map: &Map,
+) -> Option<CutOffRisk> {
+    let bounds = &map.map_bounds;
+    let on_edge = |h: &Hex| match side {
+        Side::German => map.is_west_edge(h),
+        Side::Soviet => map.is_east_edge(h),
+    };
+    if on_edge(hex) {
+        return None;
+    }
+
+    // Each hex is split into an entry and an exit node joined by its capacity
+    let hexes: Vec<Hex> = (bounds.min_q..=bounds.max_q)
+        .flat_map(|q| (bounds.min_r..=bounds.max_r).map(move |r| Hex::new(q, r)))
+        .filter(|h| occupied.get(h).is_none_or(|(_, s)| *s == side))
+        .collect();
+    let index: HashMap<Hex, usize> = hexes.iter().enumerate().map(|(i, h)| (*h, i)).collect();
+    let sink = 2 * hexes.len();
+    let mut graph = FlowGraph::new(sink + 1);
+    for (i, h) in hexes.iter().enumerate() {
+        let closable = !occupied.contains_key(h) && reach.can_reach(h);
+        graph.add_edge(2 * i, 2 * i + 1, if closable { 1 } else { OPEN });
+        for neighbor in h.neighbors() {
+            if let Some(&j) = index.get(&neighbor) {
+                graph.add_edge(2 * i + 1, 2 * j, OPEN);
+            }
+        }
+        if on_edge(h) {
+            graph.add_edge(2 * i + 1, sink, OPEN);
+        }
+    }
+
+    let source = 2 * index[hex] + 1;
+    let routes = graph.max_flow(source, sink, MAX_ROUTES);
+    if routes >= MAX_ROUTES {
+        return None;
+    }
+
+    // The cut nearest the unit: hexes entered but not left in the residual graph
+    let reachable = graph.reachable_from(source);
+    let chokepoints: Vec<Hex> = hexes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| reachable[2 * i] && !reachable[2 * i + 1])
+        .map(|(_, h)| *h)
+        .collect();
+    let enemies = match assign_blockers(&chokepoints, reach) {
+        Some(enemies) => enemies,
+        None if routes == 0 => Vec::new(),
+        None => return None,
+    };
+    Some(CutOffRisk {
+        unit: unit.to_string(),
+        hex: *hex,
+        routes,
+        chokepoints,
+        enemies,
+    })
+}
+
+/// A different enemy unit for every hex, if there are enough of them that can get there
+fn assign_blockers(hexes: &[Hex], reach: &EnemyReach) -> Option<Vec<String>> {
+    /// Kuhn's augmenting path search for a matching of hex `h`
+    fn assign(
+        h: usize,
+        candidates: &[Vec<usize>],
+        seen: &mut [bool],
+        owner: &mut [Option<usize>],
+    ) -> bool {
+        for &e in &candidates[h] {
+            if seen[e] {
+                continue;
+            }
+            seen[e] = true;
+            if owner[e].is_none_or(|other| assign(other, candidates, seen, owner)) {
+                owner[e] = Some(h);
+                return true;
+            }
+        }
+        false
+    }
+
+    if hexes.is_empty() {
+        return None;
+    }
+    let candidates: Vec<Vec<usize>> = hexes
+        .iter()
+        .map(|h| {
+            (0..reach.units.len())
+                .filter(|&e| reach.units[e].3.contains(h))
+                .collect()
+        })
+        .collect();
+    let mut owner = vec![None; reach.units.len()];
+    for h in 0..hexes.len() {
+        let mut seen = vec![false; reach.units.len()];
+        if !assign(h, &candidates, &mut seen, &mut owner) {
+            return None;
+        }
+    }
+    Some(
+        owner
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.is_some())
+            .map(|(e, _)| reach.units[e].0.clone())
+            .collect(),
+    )
+}
+
+/// A directed graph with edge capacities, for counting routes
+struct FlowGraph {
+    /// Per node, indices into `edges`
+    adjacent: Vec<Vec<usize>>,
+    /// (to, remaining capacity); edge `i ^ 1` is the reverse of edge `i`
+    edges: Vec<(usize, usize)>,
+}
+
+impl FlowGraph {
+    fn new(nodes: usize) -> Self {
+        FlowGraph {
+            adjacent: vec![Vec::new(); nodes],
+            edges: Vec::new(),
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: usize) {
+        self.adjacent[from].push(self.edges.len());
+        self.edges.push((to, capacity));
+        self.adjacent[to].push(self.edges.len());
+        self.edges.push((from, 0));
+    }
+
+    /// Push flow one unit at a time along shortest paths, stopping at `limit`
+    fn max_flow(&mut self, source: usize, sink: usize, limit: usize) -> usize {
+        let mut flow = 0;
+        while flow < limit {
+            let mut via = vec![None; self.adjacent.len()];
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for &e in &self.adjacent[node] {
+                    let (to, capacity) = self.edges[e];
+                    if capacity > 0 && to != source && via[to].is_none() {
+                        via[to] = Some(e);
+                        queue.push_back(to);
+                    }
+                }
+            }
+            if via[sink].is_none() {
+                break;
+            }
+            let mut node = sink;
+            while let Some(e) = via[node] {
+                if self.edges[e].1 != OPEN {
+                    self.edges[e].1 -= 1;
+                }
+                if self.edges[e ^ 1].1 != OPEN {
+                    self.edges[e ^ 1].1 += 1;
+                }
+                node = self.edges[e ^ 1].0;
+            }
+            flow += 1;
+        }
+        flow
+    }
+
+    /// Nodes reachable from `source` over edges with capacity left
+    fn reachable_from(&self, source: usize) -> Vec<bool> {
+        let mut seen = vec![false; self.adjacent.len()];
+        seen[source] = true;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &e in &self.adjacent[node] {
+                let (to, capacity) = self.edges[e];
+                if capacity > 0 && !seen[to] {
+                    seen[to] = true;
+                    queue.push_back(to);
+                }
+            }
+        }
+        seen
+    }
+}
+
+/// Controlled cities, and Moscow's neighbours while the side holds it, that could fall
+fn weak_points(
+    side: Side,
+    reach: &EnemyReach,
+    occupied: &HashMap<Hex, (String, Side)>,
+    unit_threats: &[UnitThreat],
+    state: &GameState,
+    map: &Map,
+) -> Vec<WeakPoint> {
+    let mut points: Vec<(String, Hex)> = Vec::new();
+    for map_hex in &map.hexes {
+        let Some(ref city) = map_hex.city else {
+            continue;
+        };
+        if state.get_city_control(&city.name) != Some(side) {
+            continue;
+        }
+        points.push((city.name.clone(), map_hex.hex()));
+        if city.is_moscow {
+            for neighbor in map_hex.hex().neighbors() {
+                if map.get_hex(&neighbor).is_some() {
+                    points.push(("Moscow approach".to_string(), neighbor));
+                }
+            }
+        }
+    }
+
+    let mut weak: Vec<WeakPoint> = points
+        .into_iter()
+        .map(|(name, hex)| {
+            let (garrison, enemies, loss_chance) = match occupied.get(&hex) {
+                Some((id, s)) if *s == side => match unit_threats.iter().find(|t| &t.unit == id) {
+                    Some(threat) => (
+                        Some(id.clone()),
+                        threat.attackers.clone(),
+                        threat.dislodge_chance,
+                    ),
+                    None => (Some(id.clone()), Vec::new(), 0.0),
+                },
+                Some((id, _)) => (None, vec![id.clone()], 1.0),
+                None => {
+                    let enemies = reach.reaching(&hex);
+                    let chance = if enemies.is_empty() { 0.0 } else { 1.0 };
+                    (None, enemies, chance)
+                }
+            };
+            WeakPoint {
+                name,
+                hex,
+                garrison,
+                enemies,
+                loss_chance,
+            }
+        })
+        .filter(|point| point.loss_chance >= WEAK_HOLD_CHANCE)
+        .collect();
+    weak.sort_by(|a, b| {
+        b.loss_chance
+            .total_cmp(&a.loss_chance)
+            .then((a.hex.q, a.hex.r).cmp(&(b.hex.q, b.hex.r)))
+    });
+    weak
+}
+
+/// Empty hexes within two of a friendly unit and nearer the friendly edge than it, outside
+/// the friendly ZOC, that the enemy can move into
+#[allow(clippy::too_many_arguments)]
+fn front_gaps(
+    side: Side,
+    enemy: Side,
+    reach: &EnemyReach,
+    occupied: &HashMap<Hex, (String, Side)>,
+    friendly: &[(String, Hex)],
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Vec<FrontGap> {
+    // Columns from the friendly map edge
+    let depth = |hex: &Hex| match side {
+        Side::German => hex.q - map.map_bounds.min_q,
+        Side::Soviet => map.map_bounds.max_q - hex.q,
+    };
+    let friendly_zoc = calculate_enemy_zoc(state, units, enemy);
+
+    let mut gaps: Vec<FrontGap> = map
+        .hexes
+        .iter()
+        .map(|map_hex| map_hex.hex())
+        .filter(|hex| !occupied.contains_key(hex) && !friendly_zoc.contains(hex))
+        .filter(|hex| {
+            friendly
+                .iter()
+                .any(|(_, at)| at.distance(hex) <= 2 && depth(hex) < depth(at))
+        })
+        .filter_map(|hex| {
+            let enemies = reach.reaching(&hex);
+            (!enemies.is_empty()).then_some(FrontGap { hex, enemies })
+        })
+        .collect();
+    gaps.sort_by_key(|gap| (gap.hex.q, gap.hex.r));
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_map, unit};
+    use crate::unit::{UnitState, UnitStrength, UnitType};
+
+    fn test_units() -> Units {
+        Units {
+            units: vec![
+                unit("A", Side::German, UnitType::Infantry, 6, 4),
+                unit("B", Side::German, UnitType::Infantry, 6, 4),
+                unit("P", Side::German, UnitType::Panzer, 12, 6),
+                unit("S1", Side::Soviet, UnitType::Infantry, 8, 4),
+                unit("S2", Side::Soviet, UnitType::Infantry, 8, 4),
+            ],
+        }
+    }
+
+    fn place(state: &mut GameState, id: &str, q: i32, r: i32) {
+        state.units.push(UnitState::new(
+            id.to_string(),
+            Some(Hex::new(q, r)),
+            UnitStrength::Full,
+        ));
+    }
+
+    #[test]
+    fn test_unit_threats() {
+        let (map, units) = (test_map(5, 3, None), test_units());
+        let mut state = GameState::new();
+        place(&mut state, "A", 2, 1);
+        place(&mut state, "S1", 4, 1);
+        place(&mut state, "S2", 4, 0);
+
+        let report = analyze_threats(Side::German, &state, &units, &map);
+        assert_eq!(report.side, Side::German);
+        assert_eq!(report.unit_threats.len(), 1);
+        let threat = &report.unit_threats[0];
+        assert_eq!(threat.unit, "A");
+        assert_eq!(threat.attackers, vec!["S1".to_string(), "S2".to_string()]);
+        assert_eq!(threat.best_attack.attack_strength, 16);
+        assert_eq!(threat.best_attack.modified_odds, "2:1");
+        assert!((threat.dislodge_chance - 2.0 / 6.0).abs() < 1e-9);
+
+        // The Soviets are in no danger from a lone infantry corps
+        let report = analyze_threats(Side::Soviet, &state, &units, &map);
+        assert_eq!(
+            report.unit_threats[0].best_attack.modified_odds,
+            "Below 1:1"
+        );
+        assert_eq!(report.unit_threats[0].dislodge_chance, 0.0);
+    }
+
+    #[test]
+    fn test_cut_off_risks() {
+        let (map, units) = (test_map(5, 2, None), test_units());
+
+        // Every route west from A runs through (2, 0), which S2 can reach
+        let mut state = GameState::new();
+        place(&mut state, "A", 3, 0);
+        place(&mut state, "S1", 2, 1);
+        place(&mut state, "S2", 0, 1);
+        let report = analyze_threats(Side::German, &state, &units, &map);
+        let risk = &report.cut_off_risks[0];
+        assert_eq!(risk.unit, "A");
+        assert_eq!(risk.routes, 1);
+        assert_eq!(risk.chokepoints, vec![Hex::new(2, 0)]);
+        assert_eq!(risk.enemies.len(), 1);
+
+        // With the column held, A is already cut off
+        let mut state = GameState::new();
+        place(&mut state, "A", 3, 0);
+        place(&mut state, "S1", 2, 1);
+        place(&mut state, "S2", 2, 0);
+        let report = analyze_threats(Side::German, &state, &units, &map);
+        assert_eq!(report.cut_off_risks[0].routes, 0);
+        assert!(report.cut_off_risks[0].enemies.is_empty());
+
+        // On a wider map the Soviets cannot close three routes with two units
+        let map = test_map(5, 5, None);
+        let mut state = GameState::new();
+        place(&mut state, "A", 3, 2);
+        place(&mut state, "S1", 4, 0);
+        place(&mut state, "S2", 4, 4);
+        assert!(analyze_threats(Side::German, &state, &units, &map)
+            .cut_off_risks
+            .is_empty());
+    }
+
+    #[test]
+    fn test_weak_points() {
+        let (map, units) = (test_map(5, 3, Some(Hex::new(4, 1))), test_units());
+        let mut state = GameState::new();
+        state.update_city_control("Moscow", Side::Soviet);
+        place(&mut state, "S1", 4, 1);
+        state.get_unit_mut("S1").unwrap().take_loss();
+        place(&mut state, "A", 2, 1);
+        place(&mut state, "P", 1, 1);
+
+        let report = analyze_threats(Side::Soviet, &state, &units, &map);
+        let moscow = report
+            .weak_points
+            .iter()
+            .find(|point| point.name == "Moscow")
+            .unwrap();
+        assert_eq!(moscow.garrison.as_deref(), Some("S1"));
+        assert_eq!(moscow.enemies, vec!["P".to_string(), "A".to_string()]);
+        let threat = &report.unit_threats[0];
+        assert_eq!(threat.best_attack.modified_odds, "3:1");
+        assert_eq!(moscow.loss_chance, threat.dislodge_chance);
+
+        // The empty approaches are open to the German units
+        let approaches: Vec<&WeakPoint> = report
+            .weak_points
+            .iter()
+            .filter(|point| point.name == "Moscow approach")
+            .collect();
+        assert!(approaches.iter().all(|point| point.loss_chance == 1.0));
+        assert!(approaches
+            .iter()
+            .any(|point| point.hex == Hex::new(3, 1) && point.garrison.is_none()));
+
+        // The Germans hold no cities
+        assert!(analyze_threats(Side::German, &state, &units, &map)
+            .weak_points
+            .is_empty());
+    }
+
+    #[test]
+    fn test_front_gaps() {
+        let (map, units) = (test_map(5, 5, None), test_units());
+        let mut state = GameState::new();
+        place(&mut state, "A", 2, 0);
+        place(&mut state, "B", 2, 4);
+        place(&mut state, "S1", 4, 1);
+
+        // S1 can slip between A and B into (1, 2)
+        let report = analyze_threats(Side::German, &state, &units, &map);
+        let gap = report
+            .front_gaps
+            .iter()
+            .find(|gap| gap.hex == Hex::new(1, 2))
+            .unwrap();
+        assert_eq!(gap.enemies, vec!["S1".to_string()]);
+        let zoc = calculate_enemy_zoc(&state, &units, Side::Soviet);
+        assert!(report.front_gaps.iter().all(|gap| !zoc.contains(&gap.hex)));
+
+        // Closing the line leaves no way through
+        place(&mut state, "P", 2, 2);
+        let report = analyze_threats(Side::German, &state, &units, &map);
+        assert!(report.front_gaps.is_empty());
+    }
+}
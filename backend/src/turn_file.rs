@@ -0,0 +1,371 @@
+use crate::action::{apply_action, Action, ActionOutcome};
+use crate::dice::sha256_hex;
+use crate::error::RuleError;
+use crate::events::GameEvent;
+use crate::game_state::GameState;
+use crate::map::Map;
+use crate::unit::{Side, Units};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use serde_json::Value;
+
+/// Version written to new turn files
+pub const TURN_FILE_VERSION: u32 = 1;
+
+/// One side's turn, exchanged between players in play-by-email games
+///
+/// The file names the game and the exact state it starts from, lists the actions in
+/// order with the die rolls they produced, and ends with the hash of the resulting state.
+/// Rolls come from the dice seed both players agreed on when starting the game, so the
+/// receiving engine recomputes them instead of trusting the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TurnFile {
+    pub version: u32,
+    pub game_id: String,
+    pub side: Side,
+    pub base_state_hash: String,
+    pub dice_commitment: String,
+    pub actions: Vec<RecordedAction>,
+    pub result_state_hash: String,
+}
+
+/// An action as taken, with the die roll it consumed if any
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RecordedAction {
+    #[serde(flatten)]
+    pub action: Action,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub die_roll: Option<i32>,
+}
+
+/// Result of checking a turn file against the engine
+#[derive(Debug, Clone)]
+pub struct ReplayedTurn {
+    pub state: GameState,
+    pub events: Vec<GameEvent>,
+}
+
+/// Hash of a game state, independent of map and set iteration order
+pub fn state_hash(state: &GameState) -> String {
+    let value = serde_json::to_value(state).expect("Game state is always serializable");
+    sha256_hex(canonical_json(&value).as_bytes())
+}
+
+/// JSON with object keys sorted and the moved-units set in sorted order
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    let field = match (key.as_str(), &map[key]) {
+                        ("moved_this_phase", Value::Array(items)) => {
+                            let mut items = items.clone();
+                            items.sort_by_key(|item| item.to_string());
+                            Value::Array(items)
+                        }
+                        (_, field) => field.clone(),
+                    };
+                    format!("{}:{}", Value::String(key.clone()), canonical_json(&field))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Collects the actions of the side currently playing into a turn file
+#[derive(Debug, Default)]
+pub struct TurnRecorder {
+    turn: Option<TurnFile>,
+}
+
+impl TurnRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an action applied to `before`, producing `after`
+    ///
+    /// A new turn file starts whenever the acting side changes or the state no longer
+    /// follows on from the last recorded action, e.g. after a new game or an import.
+    pub fn record(
+        &mut self,
+        action: &Action,
+        outcome: &ActionOutcome,
+        before: &GameState,
+        after: &GameState,
+    ) {
+        let side = before.active_player();
+        let base_hash = state_hash(before);
+        let continues = self.turn.as_ref().is_some_and(|turn| {
+            turn.side == side
+                && turn.game_id == before.game_id
+                && turn.result_state_hash == base_hash
+        });
+
+        if !continues {
+            self.turn = Some(TurnFile {
+                version: TURN_FILE_VERSION,
+                game_id: before.game_id.clone(),
+                side,
+                base_state_hash: base_hash,
+                dice_commitment: before.dice.commitment.clone(),
+                actions: Vec::new(),
+                result_state_hash: String::new(),
+            });
+        }
+
+        if let Some(ref mut turn) = self.turn {
+            turn.actions.push(RecordedAction {
+                action: action.clone(),
+                die_roll: die_roll(outcome, before, after),
+            });
+            turn.result_state_hash = state_hash(after);
+        }
+    }
+
+    /// The turn file for the most recent side to act
+    pub fn current(&self) -> Option<&TurnFile> {
+        self.turn.as_ref()
+    }
+}
+
+/// The die roll an action consumed, if the dice advanced
+fn die_roll(outcome: &ActionOutcome, before: &GameState, after: &GameState) -> Option<i32> {
+    outcome
+        .resolution
+        .as_ref()
+        .filter(|_| after.dice.rolls > before.dice.rolls)
+        .map(|resolution| resolution.die_roll)
+}
+
+/// Replay a turn file on a copy of the state, checking every step
+///
+/// Rejects files for another game or another starting position, actions that are
+/// illegal or not made by the turn's side, die rolls that differ from the committed
+/// seed, and a final state that does not match the file.
+pub fn replay_turn(
+    turn: &TurnFile,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+) -> Result<ReplayedTurn, RuleError> {
+    if turn.version != TURN_FILE_VERSION {
+        return Err(RuleError::InvalidTurnFile(format!(
+            "Unsupported turn file version {}",
+            turn.version
+        )));
+    }
+    if turn.game_id != state.game_id {
+        return Err(RuleError::InvalidTurnFile(format!(
+            "Turn file is for game {}, this is game {}",
+            turn.game_id, state.game_id
+        )));
+    }
+    if turn.dice_commitment != state.dice.commitment {
+        return Err(RuleError::InvalidTurnFile(
+            "Turn file was played with different dice".to_string(),
+        ));
+    }
+    if turn.base_state_hash != state_hash(state) {
+        return Err(RuleError::InvalidTurnFile(
+            "Turn file does not start from the current game state".to_string(),
+        ));
+    }
+
+    let mut next = state.clone();
+    let mut events = Vec::new();
+
+    for (i, recorded) in turn.actions.iter().enumerate() {
+        let number = i + 1;
+        if next.active_player() != turn.side {
+            return Err(RuleError::InvalidTurnFile(format!(
+                "Action {} is played by {:?} while {:?} is active",
+                number,
+                turn.side,
+                next.active_player()
+            )));
+        }
+
+        let before = next.clone();
+        let outcome = apply_action(&recorded.action, &mut next, units, map).map_err(|e| {
+            RuleError::InvalidTurnFile(format!("Action {} is illegal: {}", number, e))
+        })?;
+
+        let rolled = die_roll(&outcome, &before, &next);
+        if rolled != recorded.die_roll {
+            return Err(RuleError::InvalidTurnFile(format!(
+                "Action {} die roll {} does not match the committed seed (expected {})",
+                number,
+                describe_roll(recorded.die_roll),
+                describe_roll(rolled)
+            )));
+        }
+
+        events.extend(outcome.events);
+    }
+
+    if state_hash(&next) != turn.result_state_hash {
+        return Err(RuleError::InvalidTurnFile(
+            "Resulting state does not match the turn file".to_string(),
+        ));
+    }
+
+    Ok(ReplayedTurn {
+        state: next,
+        events,
+    })
+}
+
+fn describe_roll(roll: Option<i32>) -> String {
+    match roll {
+        Some(roll) => roll.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Hex;
+    use crate::test_support::{test_map, unit};
+    use crate::unit::{UnitState, UnitStrength, UnitType};
+
+    fn test_units() -> Units {
+        Units {
+            units: vec![
+                unit("XLVII", Side::German, UnitType::Panzer, 8, 4),
+                unit("16A", Side::Soviet, UnitType::Infantry, 8, 4),
+            ],
+        }
+    }
+
+    fn test_state() -> GameState {
+        let mut state = GameState::with_seed("pbem-test");
+        state.units.push(UnitState::new(
+            "XLVII".to_string(),
+            Some(Hex::new(0, 0)),
+            UnitStrength::Full,
+        ));
+        state.units.push(UnitState::new(
+            "16A".to_string(),
+            Some(Hex::new(3, 0)),
+            UnitStrength::Full,
+        ));
+        state
+    }
+
+    /// Play a German turn on one engine, recording it
+    fn play_turn(state: &mut GameState, units: &Units, map: &Map) -> TurnFile {
+        let actions = vec![
+            Action::Move {
+                unit_id: "XLVII".to_string(),
+                to: Hex::new(2, 0),
+            },
+            Action::AdvancePhase,
+            Action::DeclareBattle {
+                attacker_ids: vec!["XLVII".to_string()],
+                defender_id: "16A".to_string(),
+            },
+            Action::ResolveBattle {
+                battle_index: 0,
+                loss: None,
+            },
+        ];
+
+        let mut recorder = TurnRecorder::new();
+        for action in actions {
+            let before = state.clone();
+            let outcome = apply_action(&action, state, units, map).unwrap();
+            recorder.record(&action, &outcome, &before, state);
+        }
+        recorder.current().unwrap().clone()
+    }
+
+    #[test]
+    fn test_export_and_replay() {
+        let (map, units) = (test_map(4, 1, None), test_units());
+        let mut sender = test_state();
+        let receiver = test_state();
+        let turn = play_turn(&mut sender, &units, &map);
+
+        assert_eq!(turn.side, Side::German);
+        assert_eq!(turn.base_state_hash, state_hash(&receiver));
+        assert_eq!(turn.actions.len(), 4);
+        assert!(turn.actions[3].die_roll.is_some());
+
+        let replayed = replay_turn(&turn, &receiver, &units, &map).unwrap();
+        assert_eq!(state_hash(&replayed.state), state_hash(&sender));
+        assert_eq!(replayed.events.len(), 4);
+
+        // The file survives a round trip through JSON
+        let json = serde_json::to_string(&turn).unwrap();
+        let parsed: TurnFile = serde_json::from_str(&json).unwrap();
+        assert!(replay_turn(&parsed, &receiver, &units, &map).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_tampering() {
+        let (map, units) = (test_map(4, 1, None), test_units());
+        let receiver = test_state();
+        let turn = play_turn(&mut test_state(), &units, &map);
+
+        let mut tampered = turn.clone();
+        let roll = tampered.actions[3].die_roll.unwrap();
+        tampered.actions[3].die_roll = Some(roll % 6 + 1);
+        let err = replay_turn(&tampered, &receiver, &units, &map)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("die roll"), "{}", err);
+
+        let mut illegal = turn.clone();
+        illegal.actions[0].action = Action::Move {
+            unit_id: "XLVII".to_string(),
+            to: Hex::new(9, 9),
+        };
+        let err = replay_turn(&illegal, &receiver, &units, &map)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Action 1 is illegal"), "{}", err);
+
+        // Resolving the battle again would re-roll it
+        let mut rerolled = turn.clone();
+        rerolled.actions.push(turn.actions[3].clone());
+        let err = replay_turn(&rerolled, &receiver, &units, &map)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Action 5 is illegal"), "{}", err);
+
+        let mut wrong_base = receiver.clone();
+        wrong_base.turn = 2;
+        assert!(replay_turn(&turn, &wrong_base, &units, &map).is_err());
+
+        let other_dice = GameState {
+            units: receiver.units.clone(),
+            ..GameState::with_seed("another-seed")
+        };
+        assert!(replay_turn(&turn, &other_dice, &units, &map).is_err());
+    }
+
+    #[test]
+    fn test_state_hash_ignores_set_order() {
+        let mut a = test_state();
+        let mut b = a.clone();
+        a.mark_moved("XLVII");
+        a.mark_moved("16A");
+        b.mark_moved("16A");
+        b.mark_moved("XLVII");
+        assert_eq!(state_hash(&a), state_hash(&b));
+
+        b.turn = 2;
+        assert_ne!(state_hash(&a), state_hash(&b));
+    }
+}
@@ -1,14 +1,18 @@
 use crate::hex::Hex;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, Schema, SchemaType};
+use utoipa::openapi::{Ref, RefOr};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     German,
     Soviet,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum UnitType {
     Infantry,
@@ -16,7 +20,7 @@ pub enum UnitType {
 }
 
 /// Static unit definition (from units.json)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UnitDefinition {
     pub id: String,
     pub side: Side,
@@ -55,7 +59,7 @@ impl UnitDefinition {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum UnitStrength {
     Full,
@@ -64,13 +68,61 @@ pub enum UnitStrength {
 }
 
 /// Runtime unit state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct UnitState {
     pub id: String,
     pub position: Option<[i32; 2]>, // [q, r] or null if eliminated
     pub strength: UnitStrength,
 }
 
+/// Serialized with the printed map number of the position alongside [q, r]
+impl Serialize for UnitState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("UnitState", 4)?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("position", &self.position)?;
+        s.serialize_field(
+            "position_original",
+            &self
+                .hex()
+                .and_then(|h| h.to_original())
+                .map(|original| original.to_string()),
+        )?;
+        s.serialize_field("strength", &self.strength)?;
+        s.end()
+    }
+}
+
+/// Schema of the serialized form, including `position_original`
+impl<'s> ToSchema<'s> for UnitState {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let integer = ObjectBuilder::new().schema_type(SchemaType::Integer);
+        let schema = ObjectBuilder::new()
+            .property("id", ObjectBuilder::new().schema_type(SchemaType::String))
+            .required("id")
+            .property(
+                "position",
+                ArrayBuilder::new()
+                    .items(integer)
+                    .min_items(Some(2))
+                    .max_items(Some(2))
+                    .nullable(true)
+                    .description(Some("[q, r], or null when off the map")),
+            )
+            .required("position")
+            .property(
+                "position_original",
+                ObjectBuilder::new()
+                    .schema_type(SchemaType::String)
+                    .nullable(true),
+            )
+            .property("strength", Ref::from_schema_name("UnitStrength"))
+            .required("strength")
+            .build();
+        ("UnitState", schema.into())
+    }
+}
+
 impl UnitState {
     pub fn new(id: String, position: Option<Hex>, strength: UnitStrength) -> Self {
         UnitState {
@@ -115,7 +167,7 @@ impl UnitState {
 }
 
 /// Collection of all unit definitions
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Units {
     pub units: Vec<UnitDefinition>,
 }
@@ -181,6 +233,21 @@ mod tests {
         assert_eq!(state.hex(), None);
     }
 
+    #[test]
+    fn test_unit_state_json() {
+        let state = UnitState::new("XLVII".to_string(), Some(Hex::new(5, 3)), UnitStrength::Full);
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["position"], serde_json::json!([5, 3]));
+        assert_eq!(json["position_original"], "0505");
+
+        let parsed: UnitState = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.hex(), Some(Hex::new(5, 3)));
+
+        let eliminated = UnitState::new("5".to_string(), None, UnitStrength::Eliminated);
+        let json = serde_json::to_value(&eliminated).unwrap();
+        assert!(json["position_original"].is_null());
+    }
+
     #[test]
     fn test_unit_state_restore() {
         let mut state = UnitState::new(
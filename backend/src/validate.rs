@@ -0,0 +1,531 @@
+use crate::hex::{Direction, Hex};
+use crate::map::{Map, SetupMarker};
+use crate::unit::{Side, Units};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Stable identifier for each kind of data problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCode {
+    ReadError,
+    ParseError,
+    OutOfBounds,
+    DuplicateHex,
+    MissingHex,
+    InvalidRiverEdge,
+    AsymmetricRiver,
+    OrphanRail,
+    DuplicateCity,
+    MoscowCount,
+    MoscowName,
+    DuplicateUnit,
+    SetupExceedsUnits,
+    UnitsWithoutSetup,
+}
+
+/// A single problem found in the map or unit data
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hex: Option<Hex>,
+}
+
+impl Diagnostic {
+    fn error(code: DiagnosticCode, message: String, hex: Option<Hex>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            hex,
+        }
+    }
+
+    fn warning(code: DiagnosticCode, message: String, hex: Option<Hex>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message,
+            hex,
+        }
+    }
+
+    /// Report a data file that could not be read at all
+    pub fn read_error(path: &str, err: &std::io::Error) -> Self {
+        Diagnostic::error(
+            DiagnosticCode::ReadError,
+            format!("{}: {}", path, err),
+            None,
+        )
+    }
+
+    /// Report a data file that could not be parsed
+    pub fn parse_error(path: &str, err: &serde_json::Error) -> Self {
+        Diagnostic::error(
+            DiagnosticCode::ParseError,
+            format!("{}: {}", path, err),
+            None,
+        )
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Run every map and unit check, returning all problems found
+pub fn validate(map: &Map, units: &Units) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    check_hex_coverage(map, &mut diagnostics);
+    check_river_edges(map, &mut diagnostics);
+    check_rail(map, &mut diagnostics);
+    check_cities(map, &mut diagnostics);
+    check_units(units, &mut diagnostics);
+    check_setup(map, units, &mut diagnostics);
+
+    diagnostics
+}
+
+/// Every hex must be in bounds, listed once, and every in-bounds hex must exist
+fn check_hex_coverage(map: &Map, diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen = HashSet::new();
+
+    for map_hex in &map.hexes {
+        let hex = map_hex.hex();
+
+        if !map.is_in_bounds(&hex) {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticCode::OutOfBounds,
+                format!("Hex ({}, {}) is outside the map bounds", hex.q, hex.r),
+                Some(hex),
+            ));
+        }
+
+        if !seen.insert(hex) {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticCode::DuplicateHex,
+                format!("Hex ({}, {}) is listed more than once", hex.q, hex.r),
+                Some(hex),
+            ));
+        }
+    }
+
+    let bounds = &map.map_bounds;
+    for q in bounds.min_q..=bounds.max_q {
+        for r in bounds.min_r..=bounds.max_r {
+            let hex = Hex::new(q, r);
+            if !seen.contains(&hex) {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticCode::MissingHex,
+                    format!("Hex ({}, {}) is inside the map bounds but not defined", q, r),
+                    Some(hex),
+                ));
+            }
+        }
+    }
+}
+
+/// A river edge must name a real direction and be mirrored by the neighbour
+fn check_river_edges(map: &Map, diagnostics: &mut Vec<Diagnostic>) {
+    for map_hex in &map.hexes {
+        let hex = map_hex.hex();
+
+        for edge in &map_hex.river_edges {
+            let direction = match Direction::from_string(edge) {
+                Some(direction) => direction,
+                None => {
+                    diagnostics.push(Diagnostic::error(
+                        DiagnosticCode::InvalidRiverEdge,
+                        format!(
+                            "Hex ({}, {}) has unknown river edge \"{}\"",
+                            hex.q, hex.r, edge
+                        ),
+                        Some(hex),
+                    ));
+                    continue;
+                }
+            };
+
+            // Edges along the map border have no neighbour to mirror them
+            let neighbor = hex.neighbor(direction);
+            if let Some(neighbor_hex) = map.get_hex(&neighbor) {
+                if !neighbor_hex.has_river_edge(direction.opposite()) {
+                    diagnostics.push(Diagnostic::error(
+                        DiagnosticCode::AsymmetricRiver,
+                        format!(
+                            "Hex ({}, {}) has a river on its {} edge but ({}, {}) has none on its {} edge",
+                            hex.q,
+                            hex.r,
+                            direction.to_string(),
+                            neighbor.q,
+                            neighbor.r,
+                            direction.opposite().to_string()
+                        ),
+                        Some(hex),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Rail hexes must connect to at least one other rail hex
+fn check_rail(map: &Map, diagnostics: &mut Vec<Diagnostic>) {
+    let rail_hexes: HashSet<Hex> = map
+        .hexes
+        .iter()
+        .filter(|mh| mh.rail)
+        .map(|mh| mh.hex())
+        .collect();
+
+    for map_hex in map.hexes.iter().filter(|mh| mh.rail) {
+        let hex = map_hex.hex();
+        if !hex.neighbors().iter().any(|n| rail_hexes.contains(n)) {
+            diagnostics.push(Diagnostic::warning(
+                DiagnosticCode::OrphanRail,
+                format!(
+                    "Rail hex ({}, {}) is not connected to any other rail hex",
+                    hex.q, hex.r
+                ),
+                Some(hex),
+            ));
+        }
+    }
+}
+
+/// City names must be unique and exactly one city must be Moscow
+fn check_cities(map: &Map, diagnostics: &mut Vec<Diagnostic>) {
+    let mut names: HashMap<&str, Hex> = HashMap::new();
+    let mut moscow_hexes = Vec::new();
+
+    for map_hex in &map.hexes {
+        let city = match map_hex.city {
+            Some(ref city) => city,
+            None => continue,
+        };
+        let hex = map_hex.hex();
+
+        if let Some(first) = names.get(city.name.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticCode::DuplicateCity,
+                format!(
+                    "City \"{}\" at ({}, {}) is already defined at ({}, {})",
+                    city.name, hex.q, hex.r, first.q, first.r
+                ),
+                Some(hex),
+            ));
+        } else {
+            names.insert(&city.name, hex);
+        }
+
+        if city.is_moscow {
+            moscow_hexes.push(hex);
+
+            // City control and victory are looked up by this name
+            if city.name != "Moscow" {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticCode::MoscowName,
+                    format!(
+                        "Moscow hex ({}, {}) is named \"{}\"; it must be named \"Moscow\"",
+                        hex.q, hex.r, city.name
+                    ),
+                    Some(hex),
+                ));
+            }
+        }
+    }
+
+    if moscow_hexes.len() != 1 {
+        diagnostics.push(Diagnostic::error(
+            DiagnosticCode::MoscowCount,
+            format!(
+                "Map must have exactly one Moscow hex, found {}",
+                moscow_hexes.len()
+            ),
+            moscow_hexes.get(1).copied(),
+        ));
+    }
+}
+
+/// Unit IDs must be unique
+fn check_units(units: &Units, diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen = HashSet::new();
+
+    for unit in &units.units {
+        if !seen.insert(unit.id.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticCode::DuplicateUnit,
+                format!("Unit {} is defined more than once", unit.id),
+                None,
+            ));
+        }
+    }
+}
+
+/// Setup markers must not outnumber the units that can start on the map
+fn check_setup(map: &Map, units: &Units, diagnostics: &mut Vec<Diagnostic>) {
+    for side in [Side::German, Side::Soviet] {
+        let marker = match side {
+            Side::German => SetupMarker::German,
+            Side::Soviet => SetupMarker::Soviet,
+        };
+        let side_name = match side {
+            Side::German => "German",
+            Side::Soviet => "Soviet",
+        };
+
+        let markers = map
+            .hexes
+            .iter()
+            .filter(|mh| mh.setup.as_ref() == Some(&marker))
+            .count();
+
+        // Late arrivals such as 1st Shock Army cannot be set up
+        let available = units
+            .units
+            .iter()
+            .filter(|u| u.side == side && u.available_turn.unwrap_or(1) <= 1)
+            .count();
+
+        if markers > available {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticCode::SetupExceedsUnits,
+                format!(
+                    "{} setup hexes ({}) outnumber {} units available at start ({})",
+                    side_name, markers, side_name, available
+                ),
+                None,
+            ));
+        } else if side == Side::German && markers < available {
+            // Every German unit starts on the map; leftover Soviets are replacements
+            diagnostics.push(Diagnostic::warning(
+                DiagnosticCode::UnitsWithoutSetup,
+                format!(
+                    "{} German units have no setup hex ({} hexes for {} units)",
+                    available - markers,
+                    markers,
+                    available
+                ),
+                None,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{City, MapBounds, MapEdges, MapHex, Terrain};
+    use crate::test_support::bundled;
+    use crate::unit::{UnitDefinition, UnitType};
+
+    fn clear_hex(q: i32, r: i32) -> MapHex {
+        MapHex {
+            q,
+            r,
+            terrain: Terrain::Clear,
+            city: None,
+            fortification: false,
+            rail: false,
+            river_edges: vec![],
+            setup: None,
+        }
+    }
+
+    fn test_map(hexes: Vec<MapHex>, max_q: i32) -> Map {
+        Map {
+            hexes,
+            map_bounds: MapBounds {
+                min_q: 0,
+                max_q,
+                min_r: 0,
+                max_r: 0,
+            },
+            edges: MapEdges {
+                west: "german_communication".to_string(),
+                east: "soviet_communication".to_string(),
+            },
+        }
+    }
+
+    fn moscow() -> Option<City> {
+        Some(City {
+            name: "Moscow".to_string(),
+            is_moscow: true,
+        })
+    }
+
+    fn unit(id: &str, side: Side) -> UnitDefinition {
+        UnitDefinition {
+            id: id.to_string(),
+            side,
+            unit_type: UnitType::Infantry,
+            full_strength: 8,
+            half_strength: 4,
+            movement: 4,
+            available_turn: None,
+        }
+    }
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<DiagnosticCode> {
+        diagnostics.iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_shipped_data_is_valid() {
+        let (units, map) = bundled();
+
+        let errors: Vec<_> = validate(&map, &units)
+            .into_iter()
+            .filter(|d| d.is_error())
+            .collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_bounds_duplicates_and_gaps() {
+        let mut moscow_hex = clear_hex(0, 0);
+        moscow_hex.city = moscow();
+        let map = test_map(vec![moscow_hex, clear_hex(0, 0), clear_hex(5, 0)], 2);
+
+        let found = codes(&validate(&map, &Units { units: vec![] }));
+        assert!(found.contains(&DiagnosticCode::DuplicateHex));
+        assert!(found.contains(&DiagnosticCode::OutOfBounds));
+        assert_eq!(
+            found
+                .iter()
+                .filter(|c| **c == DiagnosticCode::MissingHex)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_asymmetric_river() {
+        let mut west = clear_hex(0, 0);
+        west.city = moscow();
+        let mut east = clear_hex(1, 0);
+        east.river_edges = vec!["W".to_string()];
+        let map = test_map(vec![west, east], 1);
+
+        let diagnostics = validate(&map, &Units { units: vec![] });
+        assert_eq!(codes(&diagnostics), vec![DiagnosticCode::AsymmetricRiver]);
+        assert_eq!(diagnostics[0].hex, Some(Hex::new(1, 0)));
+
+        let mut map = map;
+        map.hexes[0].river_edges = vec!["E".to_string()];
+        assert!(validate(&map, &Units { units: vec![] }).is_empty());
+
+        map.hexes[0].river_edges.push("North".to_string());
+        assert_eq!(
+            codes(&validate(&map, &Units { units: vec![] })),
+            vec![DiagnosticCode::InvalidRiverEdge]
+        );
+    }
+
+    #[test]
+    fn test_orphan_rail() {
+        let mut a = clear_hex(0, 0);
+        a.rail = true;
+        a.city = moscow();
+        let b = clear_hex(1, 0);
+        let mut c = clear_hex(2, 0);
+        c.rail = true;
+        let map = test_map(vec![a, b, c], 2);
+
+        let diagnostics = validate(&map, &Units { units: vec![] });
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code == DiagnosticCode::OrphanRail && !d.is_error()));
+    }
+
+    #[test]
+    fn test_city_checks() {
+        let mut a = clear_hex(0, 0);
+        a.city = Some(City {
+            name: "Tula".to_string(),
+            is_moscow: false,
+        });
+        let mut b = clear_hex(1, 0);
+        b.city = Some(City {
+            name: "Tula".to_string(),
+            is_moscow: false,
+        });
+        let map = test_map(vec![a, b], 1);
+
+        let found = codes(&validate(&map, &Units { units: vec![] }));
+        assert_eq!(
+            found,
+            vec![DiagnosticCode::DuplicateCity, DiagnosticCode::MoscowCount]
+        );
+
+        let mut map = map;
+        map.hexes[1].city = Some(City {
+            name: "Moskva".to_string(),
+            is_moscow: true,
+        });
+        let found = codes(&validate(&map, &Units { units: vec![] }));
+        assert_eq!(found, vec![DiagnosticCode::MoscowName]);
+    }
+
+    #[test]
+    fn test_setup_counts() {
+        let mut a = clear_hex(0, 0);
+        a.setup = Some(SetupMarker::German);
+        let mut b = clear_hex(1, 0);
+        b.setup = Some(SetupMarker::Soviet);
+        b.city = moscow();
+        let mut c = clear_hex(2, 0);
+        c.setup = Some(SetupMarker::Soviet);
+        let map = test_map(vec![a, b, c], 2);
+
+        let mut shock = unit("1S", Side::Soviet);
+        shock.available_turn = Some(4);
+        let units = Units {
+            units: vec![
+                unit("V", Side::German),
+                unit("VII", Side::German),
+                unit("3", Side::Soviet),
+                shock,
+            ],
+        };
+
+        let diagnostics = validate(&map, &units);
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                DiagnosticCode::UnitsWithoutSetup,
+                DiagnosticCode::SetupExceedsUnits
+            ]
+        );
+        assert!(!diagnostics[0].is_error());
+        assert!(diagnostics[1].is_error());
+    }
+
+    #[test]
+    fn test_duplicate_unit() {
+        let mut a = clear_hex(0, 0);
+        a.city = moscow();
+        let map = test_map(vec![a], 0);
+        let units = Units {
+            units: vec![unit("3", Side::Soviet), unit("3", Side::Soviet)],
+        };
+
+        assert_eq!(
+            codes(&validate(&map, &units)),
+            vec![DiagnosticCode::DuplicateUnit]
+        );
+    }
+}
@@ -1,6 +1,5 @@
 use crate::game_state::GameState;
 use crate::hex::Hex;
-use crate::map::Map;
 use crate::unit::{Side, Units};
 use std::collections::HashSet;
 
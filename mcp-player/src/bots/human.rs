@@ -0,0 +1,202 @@
+use crate::game::Narrator;
+use crate::text::map::{render_ascii, AsciiMapOptions};
+use backend::action::Action;
+use backend::ai::{legal_actions, Player};
+use backend::combat::preview_battle;
+use backend::game_state::{BattleDeclaration, GameState};
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::retreat::find_valid_retreat_hexes;
+use backend::unit::{Side, Units};
+use std::io::{self, BufRead, Write};
+
+/// A person at the terminal, picking each action from a numbered list
+pub struct HumanPlayer {
+    side: Side,
+    narrator: Narrator,
+}
+
+impl HumanPlayer {
+    pub fn new(side: Side, narrator: Narrator) -> Self {
+        HumanPlayer { side, narrator }
+    }
+
+    fn hex(&self, hex: &Hex) -> String {
+        self.narrator.format_hex(hex.q as i64, hex.r as i64)
+    }
+
+    /// Ask for a number from 1 to `count`, or `None` at end of input
+    fn pick(&self, count: usize) -> Option<usize> {
+        let stdin = io::stdin();
+        loop {
+            print!("{:?} choice [1-{}]> ", self.side, count);
+            io::stdout().flush().ok()?;
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).ok()? == 0 {
+                println!();
+                return None;
+            }
+            match line.trim().parse::<usize>() {
+                Ok(n) if (1..=count).contains(&n) => return Some(n - 1),
+                _ => println!("Enter a number from 1 to {}", count),
+            }
+        }
+    }
+}
+
+impl Player for HumanPlayer {
+    fn choose(&mut self, state: &GameState, units: &Units, map: &Map) -> Action {
+        let options = AsciiMapOptions {
+            labels: self.narrator.coordinate_labels(),
+            zoom: None,
+            overlay: None,
+        };
+        println!("{}", render_ascii(map, units, state, &options));
+
+        let legal = legal_actions(state, units, map);
+        for (i, action) in legal.iter().enumerate() {
+            println!(
+                "{:>3}. {}",
+                i + 1,
+                describe_action(action, state, units, map, &self.narrator)
+            );
+        }
+        // Without input, end phases until the game is over
+        match self.pick(legal.len()) {
+            Some(i) => legal[i].clone(),
+            None => Action::AdvancePhase,
+        }
+    }
+
+    fn retreat(
+        &mut self,
+        unit_id: &str,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> Option<Hex> {
+        let from = state.get_unit(unit_id)?.hex()?;
+        let hexes = find_valid_retreat_hexes(unit_id, &from, state, units, map);
+        if hexes.is_empty() {
+            return None;
+        }
+        println!("{} must retreat from {}:", unit_id, self.hex(&from));
+        for (i, hex) in hexes.iter().enumerate() {
+            println!("{:>3}. {}", i + 1, self.hex(hex));
+        }
+        self.pick(hexes.len()).map(|i| hexes[i])
+    }
+
+    fn choose_loss(
+        &mut self,
+        battle: &BattleDeclaration,
+        _state: &GameState,
+        _units: &Units,
+        _map: &Map,
+    ) -> Option<String> {
+        if battle.attackers.len() < 2 {
+            return battle.attackers.first().cloned();
+        }
+        println!(
+            "If the attack on {} fails, who takes the loss?",
+            battle.defender
+        );
+        for (i, id) in battle.attackers.iter().enumerate() {
+            println!("{:>3}. {}", i + 1, id);
+        }
+        self.pick(battle.attackers.len())
+            .map(|i| battle.attackers[i].clone())
+    }
+
+    fn game_over(&mut self, state: &GameState) {
+        match state.check_victory() {
+            Some(side) if side == self.side => println!("You won."),
+            Some(_) => println!("You lost."),
+            None => println!("The game ended undecided."),
+        }
+    }
+}
+
+/// One line describing an action, for choosing from a list
+pub fn describe_action(
+    action: &Action,
+    state: &GameState,
+    units: &Units,
+    map: &Map,
+    narrator: &Narrator,
+) -> String {
+    let hex = |hex: &Hex| narrator.format_hex(hex.q as i64, hex.r as i64);
+    match action {
+        Action::Move { unit_id, to } => format!("Move {} to {}", unit_id, hex(to)),
+        Action::DeclareBattle {
+            attacker_ids,
+            defender_id,
+        } => {
+            let odds = preview_battle(attacker_ids, defender_id, state, units, map)
+                .map(|preview| format!(" at {}", preview.modified_odds))
+                .unwrap_or_default();
+            format!(
+                "Attack {} with {}{}",
+                defender_id,
+                attacker_ids.join(", "),
+                odds
+            )
+        }
+        Action::ResolveBattle { battle_index, .. } => {
+            match state.pending_battles.get(*battle_index) {
+                Some(battle) => format!(
+                    "Resolve the attack on {} by {}",
+                    battle.defender,
+                    battle.attackers.join(", ")
+                ),
+                None => format!("Resolve battle {}", battle_index + 1),
+            }
+        }
+        Action::Retreat { unit_id, to } => format!("Retreat {} to {}", unit_id, hex(to)),
+        Action::Replacement {
+            unit_id,
+            hex: Some(at),
+        } => format!("Bring {} back at {}", unit_id, hex(at)),
+        Action::Replacement { unit_id, hex: None } => {
+            format!("Bring {} back to full strength", unit_id)
+        }
+        Action::AdvancePhase => "End the phase".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::embedded::bundled_data;
+
+    #[test]
+    fn test_describe_action() {
+        let (units, map) = bundled_data().unwrap();
+        let mut state = GameState::new();
+        state.place_initial_units(&units, &map);
+        let narrator = Narrator::new("axial".to_string(), "normal".to_string());
+        let describe = |action: &Action, state: &GameState| {
+            describe_action(action, state, &units, &map, &narrator)
+        };
+
+        let to = Hex::new(1, 0);
+        let action = Action::Move {
+            unit_id: "V".to_string(),
+            to,
+        };
+        assert_eq!(describe(&action, &state), "Move V to [1,0]");
+        assert_eq!(describe(&Action::AdvancePhase, &state), "End the phase");
+
+        let battle = BattleDeclaration {
+            attackers: vec!["V".to_string()],
+            defender: "3".to_string(),
+            resolved: false,
+        };
+        state.pending_battles.push(battle);
+        let action = Action::ResolveBattle {
+            battle_index: 0,
+            loss: None,
+        };
+        assert_eq!(describe(&action, &state), "Resolve the attack on 3 by V");
+    }
+}
@@ -0,0 +1,95 @@
+pub mod human;
+
+use crate::game::embedded::bundled_data;
+use crate::game::Narrator;
+use anyhow::{anyhow, Result};
+use backend::ai::Player;
+use backend::arena::{play_logged_game, GameRecord, PlayerSpec};
+use backend::game_state::GameState;
+use backend::map::Map;
+use backend::unit::{Side, Units};
+use human::HumanPlayer;
+
+/// Play one game between two players on the bundled map and report how it went
+///
+/// Each player is `human`, `heuristic`, `mcts[:settings]` or `bot:COMMAND` for an
+/// external program speaking the bot protocol.
+pub fn run_bot_game(
+    german: &str,
+    soviet: &str,
+    seed: Option<&str>,
+    narrator: impl Fn() -> Narrator,
+) -> Result<GameRecord> {
+    let (units, map) = bundled_data()?;
+    let mut state = match seed {
+        Some(seed) => GameState::with_seed(seed),
+        None => GameState::new(),
+    };
+    state.place_initial_units(&units, &map);
+
+    let mut german_player = player_for(german, Side::German, &units, &map, &narrator)?;
+    let mut soviet_player = player_for(soviet, Side::Soviet, &units, &map, &narrator)?;
+    let (mut record, _) = play_logged_game(
+        german_player.as_mut(),
+        soviet_player.as_mut(),
+        &mut state,
+        &units,
+        &map,
+    )
+    .map_err(|e| anyhow!("Game aborted: {}", e))?;
+    record.seed = state.dice.audit().seed;
+    record.german = german.to_string();
+    record.soviet = soviet.to_string();
+    Ok(record)
+}
+
+fn player_for(
+    spec: &str,
+    side: Side,
+    units: &Units,
+    map: &Map,
+    narrator: impl Fn() -> Narrator,
+) -> Result<Box<dyn Player>> {
+    if spec == "human" {
+        return Ok(Box::new(HumanPlayer::new(side, narrator())));
+    }
+    let spec: PlayerSpec = spec.parse().map_err(|e: String| anyhow!(e))?;
+    Ok(spec.player(0, side, units, map)?)
+}
+
+/// The result of a game, for people
+pub fn report(record: &GameRecord) -> String {
+    let mut output = format!("{} (German) vs {} (Soviet)\n", record.german, record.soviet);
+    match record.winner {
+        Some(side) => output.push_str(&format!("{:?} victory\n", side)),
+        None => output.push_str("No winner\n"),
+    }
+    if let Some(turn) = record.moscow_captured_turn {
+        output.push_str(&format!("Moscow taken on turn {}\n", turn));
+    }
+    output.push_str(&format!(
+        "Steps lost: German {}, Soviet {}\n{} actions, dice seed \"{}\"",
+        record.german_steps_lost, record.soviet_steps_lost, record.actions, record.seed
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn narrator() -> Narrator {
+        Narrator::new("axial".to_string(), "normal".to_string())
+    }
+
+    #[test]
+    fn test_run_bot_game() {
+        let record = run_bot_game("heuristic", "heuristic", Some("bots"), narrator).unwrap();
+        assert_eq!(record.seed, "bots");
+        assert!(record.winner.is_some());
+        assert!(report(&record).starts_with("heuristic (German) vs heuristic (Soviet)"));
+
+        assert!(run_bot_game("random", "human", None, narrator).is_err());
+        assert!(run_bot_game("bot:", "human", None, narrator).is_err());
+    }
+}
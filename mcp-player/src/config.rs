@@ -7,18 +7,27 @@ pub struct Config {
     pub game: GameConfig,
     pub player: PlayerConfig,
     pub display: DisplayConfig,
+    #[serde(default)]
+    pub orchestrator: OrchestratorConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameConfig {
+    /// Where the game runs: "http" for a server at `api_url`, or "embedded" in-process
+    #[serde(default = "default_backend")]
+    pub backend: String,
     #[serde(default = "default_api_url")]
     pub api_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerConfig {
+    /// Seat to claim on the server: "german", "soviet", "spectator", or "both" for hotseat play
     #[serde(default = "default_side")]
     pub side: String,
+    /// Token of a seat claimed earlier, reused instead of claiming a new one
+    #[serde(default)]
+    pub seat_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +38,49 @@ pub struct DisplayConfig {
     pub verbosity: String,
 }
 
+/// Settings for games between language models (`--mode orchestrate`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestratorConfig {
+    /// Root of an OpenAI-compatible API, for `openai:MODEL` players
+    #[serde(default = "default_model_api_url")]
+    pub api_url: String,
+    /// Environment variable holding the API key, if the API needs one
+    #[serde(default = "default_api_key_env")]
+    pub api_key_env: String,
+    #[serde(default = "default_temperature")]
+    pub temperature: f64,
+    /// Times a model is asked again after a reply that cannot be parsed or is refused
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Commands a side may give in one phase before the phase is ended for it
+    #[serde(default = "default_phase_budget")]
+    pub phase_budget: u32,
+    /// Model calls a side may make in a game; after that its phases are ended for it
+    #[serde(default = "default_game_budget")]
+    pub game_budget: u32,
+    /// Directory the per-game transcripts are written to
+    #[serde(default = "default_transcript_dir")]
+    pub transcript_dir: String,
+}
+
+impl Default for OrchestratorConfig {
+    fn default() -> Self {
+        OrchestratorConfig {
+            api_url: default_model_api_url(),
+            api_key_env: default_api_key_env(),
+            temperature: default_temperature(),
+            retries: default_retries(),
+            phase_budget: default_phase_budget(),
+            game_budget: default_game_budget(),
+            transcript_dir: default_transcript_dir(),
+        }
+    }
+}
+
+fn default_backend() -> String {
+    "http".to_string()
+}
+
 fn default_api_url() -> String {
     "http://localhost:3000/api".to_string()
 }
@@ -45,19 +97,50 @@ fn default_verbosity() -> String {
     "normal".to_string()
 }
 
+fn default_model_api_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+fn default_temperature() -> f64 {
+    0.2
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_phase_budget() -> u32 {
+    30
+}
+
+fn default_game_budget() -> u32 {
+    1000
+}
+
+fn default_transcript_dir() -> String {
+    "transcripts".to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
             game: GameConfig {
+                backend: default_backend(),
                 api_url: default_api_url(),
             },
             player: PlayerConfig {
                 side: default_side(),
+                seat_token: None,
             },
             display: DisplayConfig {
                 coordinate_format: default_coordinate_format(),
                 verbosity: default_verbosity(),
             },
+            orchestrator: OrchestratorConfig::default(),
         }
     }
 }
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use backend::analysis::AttackPlan;
+use backend::api::TurnCheck;
+use backend::combat::{BattlePreview, BattleResolution};
+use backend::dice::DiceAudit;
+use backend::evaluate::Assessment;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::retreat::RetreatResult;
+use backend::threats::ThreatReport;
+use backend::turn_file::TurnFile;
+use backend::unit::{Side, Units};
+use crate::config::Config;
+use crate::game::client::GameClient;
+use crate::game::embedded::EmbeddedEngine;
+
+/// A game the player interfaces can query and act on
+///
+/// Implemented by `GameClient`, which talks to a server over HTTP, and by
+/// `EmbeddedEngine`, which runs the rules engine in-process. Rule violations surface as
+/// `EngineError` in both.
+#[async_trait]
+pub trait GameBackend: Send + Sync {
+    /// Get the current game state
+    async fn get_state(&self) -> Result<GameState>;
+
+    /// Get the map data
+    async fn get_map(&self) -> Result<Map>;
+
+    /// Get the unit definitions
+    async fn get_units(&self) -> Result<Units>;
+
+    /// Start a new game, with an agreed dice seed for play-by-email
+    async fn new_game(&self, seed: Option<String>) -> Result<GameState>;
+
+    /// Move a unit
+    async fn move_unit(&self, unit_id: &str, to: (i32, i32)) -> Result<GameState>;
+
+    /// Get the hexes a unit can move to
+    async fn valid_moves(&self, unit_id: &str) -> Result<Vec<Hex>>;
+
+    /// Declare a battle against one defender
+    async fn declare_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<GameState>;
+
+    /// Resolve a declared battle by its index
+    async fn resolve_battle(&self, battle_index: usize) -> Result<BattleResolution>;
+
+    /// Resolve the first declared battle that is still pending
+    async fn resolve_next_battle(&self) -> Result<(usize, BattleResolution)> {
+        let state = self.get_state().await?;
+        let index = state
+            .pending_battles
+            .iter()
+            .position(|battle| !battle.resolved)
+            .ok_or_else(|| anyhow!("No declared battles are waiting to be resolved"))?;
+        Ok((index, self.resolve_battle(index).await?))
+    }
+
+    /// Work out the odds and possible outcomes of a battle without declaring it
+    async fn preview_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<BattlePreview>;
+
+    /// Rank the ways the active side could split its units among the battles open to it
+    async fn attack_plans(&self, limit: Option<usize>) -> Result<Vec<AttackPlan>>;
+
+    /// What the enemy could do to a side, the active side if none is given, next phase
+    async fn threats(&self, side: Option<Side>) -> Result<ThreatReport>;
+
+    /// Who is winning, with chances read from the score or from random playouts
+    async fn evaluate(&self, playouts: Option<usize>) -> Result<Assessment>;
+
+    /// Retreat a unit after combat
+    async fn retreat(&self, unit_id: &str, to: (i32, i32)) -> Result<RetreatResult>;
+
+    /// Get the hexes a unit can retreat to
+    async fn retreat_hexes(&self, unit_id: &str) -> Result<Vec<Hex>>;
+
+    /// Advance to the next phase
+    async fn advance_phase(&self) -> Result<GameState>;
+
+    /// Use a replacement, placing the unit at `hex` if it is off the map
+    async fn apply_replacement(&self, unit_id: &str, hex: Option<(i32, i32)>) -> Result<GameState>;
+
+    /// Get the hexes where the active side may place replacements
+    async fn replacement_hexes(&self) -> Result<Vec<Hex>>;
+
+    /// Get the turn file of the side that acted most recently
+    async fn export_turn(&self) -> Result<TurnFile>;
+
+    /// Check an opponent's turn file without applying it
+    async fn verify_turn(&self, turn: &TurnFile) -> Result<TurnCheck>;
+
+    /// Check and apply an opponent's turn file
+    async fn import_turn(&self, turn: &TurnFile) -> Result<GameState>;
+
+    /// Get the seeds and rolls of a finished game
+    async fn dice_audit(&self) -> Result<DiceAudit>;
+}
+
+/// Open the backend selected by `game.backend`
+///
+/// "http" joins the server at `game.api_url`; "embedded" starts a local game in-process.
+pub async fn connect(config: &Config) -> Result<Box<dyn GameBackend>> {
+    match config.game.backend.to_lowercase().as_str() {
+        "http" => Ok(Box::new(GameClient::connect(config).await?)),
+        "embedded" => Ok(Box::new(EmbeddedEngine::bundled()?)),
+        other => Err(anyhow!("Unknown game backend: {}", other)),
+    }
+}
@@ -1,84 +1,379 @@
-use anyhow::Result;
-use reqwest::Client;
-use serde_json::Value;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use backend::analysis::AttackPlan;
+use backend::api::{
+    rule_status, DeclareBattleRequest, MoveRequest, NewGameRequest, ReplacementRequest,
+    ResolveBattleRequest, RetreatRequest, TurnCheck,
+};
+use backend::client::{ApiClient, ClientError};
+use backend::combat::{BattlePreview, BattleResolution};
+use backend::dice::DiceAudit;
+use backend::evaluate::Assessment;
+use backend::error::RuleError;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::retreat::RetreatResult;
+use backend::seats::Seat;
+use backend::threats::ThreatReport;
+use backend::turn_file::TurnFile;
+use backend::unit::{Side, Units};
+use crate::config::Config;
+use crate::game::backend::GameBackend;
+use std::fmt;
+
+/// An error reported by the engine
+///
+/// Callers can downcast an `anyhow::Error` to this and match on `code` (see
+/// `backend::error::RuleError::code`) instead of parsing the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineError {
+    pub status: u16,
+    pub code: String,
+    pub message: String,
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Engine refusals become `EngineError`; transport failures pass through
+fn engine_error(err: ClientError) -> anyhow::Error {
+    match err {
+        ClientError::Api {
+            status,
+            code,
+            message,
+        } => EngineError {
+            status,
+            code,
+            message,
+        }
+        .into(),
+        ClientError::Transport(e) => e.into(),
+    }
+}
+
+/// Report a rule violation found in-process as the server would
+pub fn rule_error(err: RuleError) -> anyhow::Error {
+    EngineError {
+        status: rule_status(&err).as_u16(),
+        code: err.code().to_string(),
+        message: err.to_string(),
+    }
+    .into()
+}
 
 /// HTTP client for communicating with the Battle for Moscow game engine
 pub struct GameClient {
-    client: Client,
-    base_url: String,
+    api: ApiClient,
+    /// Start new games as hotseat games, playing both sides without a token
+    hotseat: bool,
 }
 
 impl GameClient {
-    pub fn new(base_url: String) -> Self {
+    /// Create a client for `game.api_url`
+    ///
+    /// The URL may name the server (`http://localhost:3000`) or its API root
+    /// (`http://localhost:3000/api`).
+    pub fn new(api_url: String) -> Self {
+        let url = api_url.trim_end_matches('/');
+        let server = url.strip_suffix("/api").unwrap_or(url);
         Self {
-            client: Client::new(),
-            base_url,
+            api: ApiClient::new(server),
+            hotseat: false,
         }
     }
 
-    /// Get the current game state
-    pub async fn get_state(&self) -> Result<Value> {
-        let url = format!("{}/state", self.base_url);
-        let response = self.client.get(&url).send().await?;
-        Ok(response.json().await?)
+    /// Create a client holding the seat configured in `player.side`
+    ///
+    /// Reuses `player.seat_token` when set; "both" claims nothing and plays hotseat games,
+    /// whose unclaimed sides accept requests without a token.
+    pub async fn connect(config: &Config) -> Result<Self> {
+        let mut client = Self::new(config.game.api_url.clone());
+        if let Some(ref token) = config.player.seat_token {
+            client.api.set_token(Some(token.clone()));
+            return Ok(client);
+        }
+
+        let seat = match config.player.side.to_lowercase().as_str() {
+            "both" => {
+                client.hotseat = true;
+                return Ok(client);
+            }
+            "german" => Seat::German,
+            "soviet" => Seat::Soviet,
+            "spectator" => Seat::Spectator,
+            other => return Err(anyhow!("Unknown player side: {}", other)),
+        };
+
+        let claim = client
+            .api
+            .claim_seat(seat)
+            .await
+            .map_err(|e| anyhow!("Could not claim the {:?} seat: {}", seat, e))?;
+        client.api.set_token(Some(claim.token));
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl GameBackend for GameClient {
+    async fn get_state(&self) -> Result<GameState> {
+        self.api.game_state().await.map_err(engine_error)
+    }
+
+    async fn get_map(&self) -> Result<Map> {
+        self.api.map().await.map_err(engine_error)
+    }
+
+    async fn get_units(&self) -> Result<Units> {
+        self.api.units().await.map_err(engine_error)
+    }
+
+    async fn new_game(&self, seed: Option<String>) -> Result<GameState> {
+        let request = NewGameRequest {
+            seed,
+            hotseat: self.hotseat,
+            ..NewGameRequest::default()
+        };
+        self.api.new_game(&request).await.map_err(engine_error)
+    }
+
+    async fn move_unit(&self, unit_id: &str, to: (i32, i32)) -> Result<GameState> {
+        let request = MoveRequest {
+            unit_id: unit_id.to_string(),
+            to_q: Some(to.0),
+            to_r: Some(to.1),
+            to_original: None,
+        };
+        self.api.move_unit(&request).await.map_err(engine_error)
     }
 
-    /// Move a unit
-    pub async fn move_unit(&self, unit_id: &str, to: (i32, i32)) -> Result<Value> {
-        let url = format!("{}/move", self.base_url);
-        let body = serde_json::json!({
-            "unit_id": unit_id,
-            "to": to,
-        });
-        let response = self.client.post(&url).json(&body).send().await?;
-        Ok(response.json().await?)
+    async fn valid_moves(&self, unit_id: &str) -> Result<Vec<Hex>> {
+        self.api.valid_moves(unit_id).await.map_err(engine_error)
     }
 
-    /// Declare attacks
-    pub async fn declare_attacks(&self, battles: Vec<Value>) -> Result<Value> {
-        let url = format!("{}/declare_attacks", self.base_url);
-        let body = serde_json::json!({ "battles": battles });
-        let response = self.client.post(&url).json(&body).send().await?;
-        Ok(response.json().await?)
+    async fn declare_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<GameState> {
+        let request = DeclareBattleRequest {
+            attacker_ids,
+            defender_id,
+        };
+        self.api.declare_battle(&request).await.map_err(engine_error)
     }
 
-    /// Resolve next battle
-    pub async fn resolve_next_battle(&self) -> Result<Value> {
-        let url = format!("{}/resolve_battle", self.base_url);
-        let response = self.client.post(&url).send().await?;
-        Ok(response.json().await?)
+    async fn resolve_battle(&self, battle_index: usize) -> Result<BattleResolution> {
+        let request = ResolveBattleRequest {
+            battle_index,
+            loss: None,
+        };
+        self.api.resolve_battle(&request).await.map_err(engine_error)
     }
 
-    /// End current phase
-    pub async fn end_phase(&self) -> Result<Value> {
-        let url = format!("{}/end_phase", self.base_url);
-        let response = self.client.post(&url).send().await?;
-        Ok(response.json().await?)
+    async fn preview_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<BattlePreview> {
+        let request = DeclareBattleRequest {
+            attacker_ids,
+            defender_id,
+        };
+        self.api.preview_battle(&request).await.map_err(engine_error)
     }
 
-    /// Use a replacement
-    pub async fn use_replacement(&self, unit_id: &str, hex: Option<(i32, i32)>) -> Result<Value> {
-        let url = format!("{}/replacement", self.base_url);
-        let mut body = serde_json::json!({ "unit_id": unit_id });
-        if let Some(hex) = hex {
-            body["hex"] = serde_json::json!(hex);
+    async fn attack_plans(&self, limit: Option<usize>) -> Result<Vec<AttackPlan>> {
+        self.api.attack_plans(limit).await.map_err(engine_error)
+    }
+
+    async fn threats(&self, side: Option<Side>) -> Result<ThreatReport> {
+        self.api.threats(side).await.map_err(engine_error)
+    }
+
+    async fn evaluate(&self, playouts: Option<usize>) -> Result<Assessment> {
+        self.api.evaluate(playouts, None).await.map_err(engine_error)
+    }
+
+    async fn retreat(&self, unit_id: &str, to: (i32, i32)) -> Result<RetreatResult> {
+        let request = RetreatRequest {
+            unit_id: unit_id.to_string(),
+            to_q: Some(to.0),
+            to_r: Some(to.1),
+            to_original: None,
+        };
+        self.api.retreat(&request).await.map_err(engine_error)
+    }
+
+    async fn retreat_hexes(&self, unit_id: &str) -> Result<Vec<Hex>> {
+        self.api.retreat_hexes(unit_id).await.map_err(engine_error)
+    }
+
+    async fn advance_phase(&self) -> Result<GameState> {
+        self.api.advance_phase().await.map_err(engine_error)
+    }
+
+    async fn apply_replacement(
+        &self,
+        unit_id: &str,
+        hex: Option<(i32, i32)>,
+    ) -> Result<GameState> {
+        let request = ReplacementRequest {
+            unit_id: unit_id.to_string(),
+            hex_q: hex.map(|h| h.0),
+            hex_r: hex.map(|h| h.1),
+            hex_original: None,
+        };
+        self.api
+            .apply_replacement(&request)
+            .await
+            .map_err(engine_error)
+    }
+
+    async fn replacement_hexes(&self) -> Result<Vec<Hex>> {
+        self.api.replacement_hexes().await.map_err(engine_error)
+    }
+
+    async fn export_turn(&self) -> Result<TurnFile> {
+        self.api.export_turn().await.map_err(engine_error)
+    }
+
+    async fn verify_turn(&self, turn: &TurnFile) -> Result<TurnCheck> {
+        self.api.verify_turn(turn).await.map_err(engine_error)
+    }
+
+    async fn import_turn(&self, turn: &TurnFile) -> Result<GameState> {
+        self.api.import_turn(turn).await.map_err(engine_error)
+    }
+
+    async fn dice_audit(&self) -> Result<DiceAudit> {
+        self.api.dice_audit().await.map_err(engine_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::api::{create_router, AppState};
+    use backend::combat::CombatResult;
+    use backend::game_state::Phase;
+    use backend::unit::{UnitState, UnitStrength, UnitType};
+
+    /// Serve a fresh game from the real router on an ephemeral port
+    async fn spawn_engine() -> GameClient {
+        let map = Map::load_from_json(include_str!("../../../data/map.json")).unwrap();
+        let units = Units::load_from_json(include_str!("../../../data/units.json")).unwrap();
+        let mut game = GameState::with_seed("client-test");
+        for (id, q) in [("XLVII", 0), ("3", 3)] {
+            let hex = Some(Hex::new(q, 0));
+            game.units.push(UnitState::new(id.to_string(), hex, UnitStrength::Full));
         }
-        let response = self.client.post(&url).json(&body).send().await?;
-        Ok(response.json().await?)
+        let state = AppState::new(game, units, map);
+        state.seats.lock().unwrap().set_hotseat(true);
+        let app = create_router(state);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let mut client = GameClient::new(format!("http://{}/api", addr));
+        client.hotseat = true;
+        client
     }
 
-    /// Advance unit after combat
-    pub async fn advance_unit(&self, unit_id: &str) -> Result<Value> {
-        let url = format!("{}/advance", self.base_url);
-        let body = serde_json::json!({ "unit_id": unit_id });
-        let response = self.client.post(&url).json(&body).send().await?;
-        Ok(response.json().await?)
+    fn engine_code(err: &anyhow::Error) -> &str {
+        &err.downcast_ref::<EngineError>()
+            .expect("Expected an engine error")
+            .code
+    }
+
+    #[tokio::test]
+    async fn test_move_and_advance() {
+        let client = spawn_engine().await;
+        let units = client.get_units().await.unwrap();
+        let state = client.get_state().await.unwrap();
+        assert_eq!(state.phase, Phase::GermanPanzerMovement);
+        assert!(!client.get_map().await.unwrap().hexes.is_empty());
+
+        assert_eq!(units.get("XLVII").unwrap().unit_type, UnitType::Panzer);
+
+        let (panzer, to) = ("XLVII", Hex::new(0, 0));
+        let moves = client.valid_moves(panzer).await.unwrap();
+        let target = *moves.iter().find(|&&hex| hex != to).unwrap();
+
+        let state = client.move_unit(panzer, (target.q, target.r)).await.unwrap();
+        assert_eq!(state.get_unit(panzer).unwrap().hex(), Some(target));
+
+        // Moving again is refused with a typed error
+        let err = client.move_unit(panzer, (to.q, to.r)).await.unwrap_err();
+        assert_eq!(engine_code(&err), "already_moved");
+        assert_eq!(err.downcast_ref::<EngineError>().unwrap().status, 422);
+
+        let state = client.advance_phase().await.unwrap();
+        assert_eq!(state.phase, Phase::GermanCombat);
+        let turn = client.export_turn().await.unwrap();
+        assert_eq!(turn.actions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_battle_flow() {
+        let client = spawn_engine().await;
+        let err = client.resolve_next_battle().await.unwrap_err();
+        assert!(err.downcast_ref::<EngineError>().is_none());
+
+        let err = client.resolve_battle(0).await.unwrap_err();
+        assert_eq!(engine_code(&err), "wrong_phase");
+
+        // Close in on the Soviet unit, then attack it
+        client.move_unit("XLVII", (2, 0)).await.unwrap();
+        client.advance_phase().await.unwrap();
+        let state = client
+            .declare_battle(vec!["XLVII".to_string()], "3".to_string())
+            .await
+            .unwrap();
+        assert_eq!(state.pending_battles.len(), 1);
+
+        let (index, resolution) = client.resolve_next_battle().await.unwrap();
+        assert_eq!(index, 0);
+        // Moscow shifts the 1:1 attack below 1:1, which resolves without a roll
+        assert_eq!(resolution.die_roll, 0);
+        assert_eq!(resolution.modified_odds, "Below 1:1");
+        assert_eq!(resolution.result, CombatResult::NE);
+        let state = client.get_state().await.unwrap();
+        assert!(state.pending_battles[0].resolved);
+        assert_eq!(state.dice.rolls, 0);
+
+        let err = client.valid_moves("no-such-unit").await.unwrap_err();
+        assert_eq!(engine_code(&err), "unit_not_found");
+        // Only a defender a result drove back may retreat
+        let err = client.retreat("3", (5, 0)).await.unwrap_err();
+        assert_eq!(engine_code(&err), "no_retreat_pending");
+    }
+
+    #[tokio::test]
+    async fn test_new_game_and_replacements() {
+        let client = spawn_engine().await;
+        let state = client.new_game(Some("pbem".to_string())).await.unwrap();
+        assert_eq!(state.turn, 1);
+
+        let err = client.apply_replacement("3", None).await.unwrap_err();
+        assert_eq!(engine_code(&err), "wrong_phase");
+        client.replacement_hexes().await.unwrap();
+
+        let err = client.dice_audit().await.unwrap_err();
+        assert_eq!(engine_code(&err), "game_not_over");
     }
 
-    /// Skip advance
-    pub async fn skip_advance(&self) -> Result<Value> {
-        let url = format!("{}/skip_advance", self.base_url);
-        let response = self.client.post(&url).send().await?;
-        Ok(response.json().await?)
+    #[tokio::test]
+    async fn test_unreachable_engine() {
+        let client = GameClient::new("http://127.0.0.1:1".to_string());
+        let err = client.get_state().await.unwrap_err();
+        assert!(err.downcast_ref::<EngineError>().is_none());
     }
 }
@@ -0,0 +1,340 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use backend::action::{apply_action, Action, ActionOutcome};
+use backend::analysis::{plan_attacks, AttackPlan, DEFAULT_PLAN_LIMIT};
+use backend::api::TurnCheck;
+use backend::combat::{preview_battle, BattlePreview, BattleResolution};
+use backend::dice::DiceAudit;
+use backend::evaluate::{assess_position, Assessment};
+use backend::error::RuleError;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::movement::find_valid_moves;
+use backend::replacement::get_valid_replacement_hexes;
+use backend::retreat::{find_valid_retreat_hexes, RetreatResult};
+use backend::threats::{analyze_threats, ThreatReport};
+use backend::turn_file::{replay_turn, TurnFile, TurnRecorder};
+use backend::unit::{Side, Units};
+use crate::game::backend::GameBackend;
+use crate::game::client::rule_error;
+use std::sync::Mutex;
+
+/// Map and units compiled into the binary
+const MAP_JSON: &str = include_str!("../../../data/map.json");
+const UNITS_JSON: &str = include_str!("../../../data/units.json");
+
+/// The units and map bundled with the binary
+pub fn bundled_data() -> Result<(Units, Map)> {
+    let units = Units::load_from_json(UNITS_JSON).map_err(|e| anyhow!(e))?;
+    let map = Map::load_from_json(MAP_JSON).map_err(|e| anyhow!(e))?;
+    Ok((units, map))
+}
+
+/// The rules engine running in-process, for local games without a server
+///
+/// Actions go through the same `apply_action` the server uses and are recorded for turn
+/// export. There are no seats: whoever holds the engine plays both sides.
+pub struct EmbeddedEngine {
+    game: Mutex<GameState>,
+    turns: Mutex<TurnRecorder>,
+    units: Units,
+    map: Map,
+}
+
+impl EmbeddedEngine {
+    /// Start a game with the given data, units placed on their setup hexes
+    pub fn new(units: Units, map: Map) -> Self {
+        let mut game = GameState::new();
+        game.place_initial_units(&units, &map);
+        EmbeddedEngine {
+            game: Mutex::new(game),
+            turns: Mutex::new(TurnRecorder::new()),
+            units,
+            map,
+        }
+    }
+
+    /// Start a game with the map and units bundled with the binary
+    pub fn bundled() -> Result<Self> {
+        let (units, map) = bundled_data()?;
+        Ok(Self::new(units, map))
+    }
+
+    /// Apply an action and record it for turn export
+    fn perform(&self, action: Action) -> Result<(GameState, ActionOutcome)> {
+        let mut game = self.game.lock().unwrap();
+        let before = game.clone();
+        let outcome =
+            apply_action(&action, &mut game, &self.units, &self.map).map_err(rule_error)?;
+        self.turns
+            .lock()
+            .unwrap()
+            .record(&action, &outcome, &before, &game);
+        Ok((game.clone(), outcome))
+    }
+}
+
+#[async_trait]
+impl GameBackend for EmbeddedEngine {
+    async fn get_state(&self) -> Result<GameState> {
+        Ok(self.game.lock().unwrap().clone())
+    }
+
+    async fn get_map(&self) -> Result<Map> {
+        Ok(self.map.clone())
+    }
+
+    async fn get_units(&self) -> Result<Units> {
+        Ok(self.units.clone())
+    }
+
+    async fn new_game(&self, seed: Option<String>) -> Result<GameState> {
+        let mut game = self.game.lock().unwrap();
+        *game = match seed {
+            Some(ref seed) => GameState::with_seed(seed),
+            None => GameState::new(),
+        };
+        game.place_initial_units(&self.units, &self.map);
+        Ok(game.clone())
+    }
+
+    async fn move_unit(&self, unit_id: &str, to: (i32, i32)) -> Result<GameState> {
+        let action = Action::Move {
+            unit_id: unit_id.to_string(),
+            to: Hex::new(to.0, to.1),
+        };
+        Ok(self.perform(action)?.0)
+    }
+
+    async fn valid_moves(&self, unit_id: &str) -> Result<Vec<Hex>> {
+        let game = self.game.lock().unwrap();
+        find_valid_moves(unit_id, &game, &self.units, &self.map).map_err(rule_error)
+    }
+
+    async fn declare_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<GameState> {
+        let action = Action::DeclareBattle {
+            attacker_ids,
+            defender_id,
+        };
+        Ok(self.perform(action)?.0)
+    }
+
+    async fn resolve_battle(&self, battle_index: usize) -> Result<BattleResolution> {
+        let (_, outcome) = self.perform(Action::ResolveBattle {
+            battle_index,
+            loss: None,
+        })?;
+        outcome
+            .resolution
+            .ok_or_else(|| anyhow!("Battle {} produced no resolution", battle_index))
+    }
+
+    async fn preview_battle(
+        &self,
+        attacker_ids: Vec<String>,
+        defender_id: String,
+    ) -> Result<BattlePreview> {
+        let game = self.game.lock().unwrap();
+        preview_battle(&attacker_ids, &defender_id, &game, &self.units, &self.map)
+            .map_err(rule_error)
+    }
+
+    async fn attack_plans(&self, limit: Option<usize>) -> Result<Vec<AttackPlan>> {
+        let game = self.game.lock().unwrap();
+        let limit = limit.unwrap_or(DEFAULT_PLAN_LIMIT);
+        plan_attacks(&game, &self.units, &self.map, limit).map_err(rule_error)
+    }
+
+    async fn threats(&self, side: Option<Side>) -> Result<ThreatReport> {
+        let game = self.game.lock().unwrap();
+        let side = side.unwrap_or_else(|| game.active_player());
+        Ok(analyze_threats(side, &game, &self.units, &self.map))
+    }
+
+    async fn evaluate(&self, playouts: Option<usize>) -> Result<Assessment> {
+        let game = self.game.lock().unwrap().clone();
+        let playouts = playouts.unwrap_or(0);
+        Ok(assess_position(&game, &self.units, &self.map, playouts, 0))
+    }
+
+    async fn retreat(&self, unit_id: &str, to: (i32, i32)) -> Result<RetreatResult> {
+        let action = Action::Retreat {
+            unit_id: unit_id.to_string(),
+            to: Hex::new(to.0, to.1),
+        };
+        let (_, outcome) = self.perform(action)?;
+        outcome
+            .retreat
+            .ok_or_else(|| anyhow!("Retreat of {} produced no result", unit_id))
+    }
+
+    async fn retreat_hexes(&self, unit_id: &str) -> Result<Vec<Hex>> {
+        let game = self.game.lock().unwrap();
+        let from = game
+            .get_unit(unit_id)
+            .and_then(|unit| unit.hex())
+            .ok_or_else(|| rule_error(RuleError::NotOnMap(unit_id.to_string())))?;
+        Ok(find_valid_retreat_hexes(
+            unit_id,
+            &from,
+            &game,
+            &self.units,
+            &self.map,
+        ))
+    }
+
+    async fn advance_phase(&self) -> Result<GameState> {
+        Ok(self.perform(Action::AdvancePhase)?.0)
+    }
+
+    async fn apply_replacement(&self, unit_id: &str, hex: Option<(i32, i32)>) -> Result<GameState> {
+        let action = Action::Replacement {
+            unit_id: unit_id.to_string(),
+            hex: hex.map(|(q, r)| Hex::new(q, r)),
+        };
+        Ok(self.perform(action)?.0)
+    }
+
+    async fn replacement_hexes(&self) -> Result<Vec<Hex>> {
+        let game = self.game.lock().unwrap();
+        let side = game.active_player();
+        Ok(get_valid_replacement_hexes(
+            side,
+            &game,
+            &self.units,
+            &self.map,
+        ))
+    }
+
+    async fn export_turn(&self) -> Result<TurnFile> {
+        self.turns
+            .lock()
+            .unwrap()
+            .current()
+            .cloned()
+            .ok_or_else(|| rule_error(RuleError::NoTurnRecorded))
+    }
+
+    async fn verify_turn(&self, turn: &TurnFile) -> Result<TurnCheck> {
+        let game = self.game.lock().unwrap();
+        replay_turn(turn, &game, &self.units, &self.map).map_err(rule_error)?;
+        Ok(TurnCheck {
+            side: turn.side,
+            actions: turn.actions.len(),
+            die_rolls: turn.actions.iter().filter_map(|a| a.die_roll).collect(),
+        })
+    }
+
+    async fn import_turn(&self, turn: &TurnFile) -> Result<GameState> {
+        let mut game = self.game.lock().unwrap();
+        let replayed = replay_turn(turn, &game, &self.units, &self.map).map_err(rule_error)?;
+        *game = replayed.state;
+        Ok(game.clone())
+    }
+
+    async fn dice_audit(&self) -> Result<DiceAudit> {
+        let game = self.game.lock().unwrap();
+        if !game.is_over() {
+            return Err(rule_error(RuleError::GameNotOver));
+        }
+        Ok(game.dice.audit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::client::EngineError;
+    use backend::game_state::Phase;
+    use backend::turn_file::state_hash;
+    use backend::unit::{UnitState, UnitStrength};
+
+    /// A panzer and a Soviet infantry unit three hexes apart on the real map
+    fn engine() -> EmbeddedEngine {
+        let bundled = EmbeddedEngine::bundled().unwrap();
+        let mut game = GameState::with_seed("embedded-test");
+        for (id, q) in [("XLVII", 0), ("3", 3)] {
+            let hex = Some(Hex::new(q, 0));
+            game.units
+                .push(UnitState::new(id.to_string(), hex, UnitStrength::Full));
+        }
+        EmbeddedEngine {
+            game: Mutex::new(game),
+            ..bundled
+        }
+    }
+
+    fn engine_code(err: &anyhow::Error) -> &str {
+        &err.downcast_ref::<EngineError>()
+            .expect("Expected an engine error")
+            .code
+    }
+
+    #[tokio::test]
+    async fn test_play_and_export() {
+        let receiver = engine();
+        let engine = engine();
+        let moves = engine.valid_moves("XLVII").await.unwrap();
+        assert!(moves.contains(&Hex::new(2, 0)));
+
+        let state = engine.move_unit("XLVII", (2, 0)).await.unwrap();
+        assert_eq!(state.get_unit("XLVII").unwrap().hex(), Some(Hex::new(2, 0)));
+
+        let err = engine.move_unit("XLVII", (1, 0)).await.unwrap_err();
+        assert_eq!(engine_code(&err), "already_moved");
+        assert_eq!(err.downcast_ref::<EngineError>().unwrap().status, 422);
+
+        let state = engine.advance_phase().await.unwrap();
+        assert_eq!(state.phase, Phase::GermanCombat);
+        engine
+            .declare_battle(vec!["XLVII".to_string()], "3".to_string())
+            .await
+            .unwrap();
+        let (index, _) = engine.resolve_next_battle().await.unwrap();
+        assert_eq!(index, 0);
+
+        // The recorded turn replays on a fresh copy of the starting position
+        let turn = engine.export_turn().await.unwrap();
+        assert_eq!(turn.actions.len(), 4);
+        let check = receiver.verify_turn(&turn).await.unwrap();
+        assert_eq!(check.actions, 4);
+        let imported = receiver.import_turn(&turn).await.unwrap();
+        assert_eq!(
+            state_hash(&imported),
+            state_hash(&engine.get_state().await.unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_match_server() {
+        let engine = engine();
+        let err = engine.valid_moves("no-such-unit").await.unwrap_err();
+        assert_eq!(engine_code(&err), "unit_not_found");
+        assert_eq!(err.downcast_ref::<EngineError>().unwrap().status, 404);
+
+        let err = engine.resolve_battle(0).await.unwrap_err();
+        assert_eq!(engine_code(&err), "wrong_phase");
+        let err = engine.export_turn().await.unwrap_err();
+        assert_eq!(engine_code(&err), "no_turn_recorded");
+        assert_eq!(err.downcast_ref::<EngineError>().unwrap().status, 404);
+        let err = engine.dice_audit().await.unwrap_err();
+        assert_eq!(engine_code(&err), "game_not_over");
+        assert_eq!(err.downcast_ref::<EngineError>().unwrap().status, 409);
+        let err = engine.retreat_hexes("no-such-unit").await.unwrap_err();
+        assert_eq!(engine_code(&err), "not_on_map");
+    }
+
+    #[tokio::test]
+    async fn test_new_game() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        engine.advance_phase().await.unwrap();
+        let state = engine.new_game(Some("pbem".to_string())).await.unwrap();
+        assert_eq!(state.phase, Phase::GermanPanzerMovement);
+        assert!(!state.units.is_empty());
+    }
+}
@@ -1,5 +1,7 @@
+pub mod backend;
 pub mod client;
+pub mod embedded;
 pub mod narrator;
 
-pub use client::GameClient;
+pub use backend::GameBackend;
 pub use narrator::Narrator;
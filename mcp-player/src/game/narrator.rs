@@ -1,74 +1,242 @@
-use serde_json::Value;
+use backend::analysis::AttackPlan;
+use backend::combat::{AttackOption, BattlePreview, BattleResolution, CombatResult};
+use backend::evaluate::Assessment;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::movement::MoveOption;
+use backend::render::CoordinateLabels;
+use backend::replacement::{can_trace_communication, replacements_remaining, ReplacementOption};
+use backend::threats::ThreatReport;
+use backend::unit::{Side, UnitDefinition, UnitState, UnitStrength, UnitType, Units};
+use backend::zoc::is_in_enemy_zoc;
+
+/// The dates each turn covers; turns 3 and 4 last two weeks
+const TURN_DATES: [&str; 7] = [
+    "2–8 October 1941",
+    "9–15 October 1941",
+    "16–31 October 1941",
+    "1–15 November 1941",
+    "16–22 November 1941",
+    "23–30 November 1941",
+    "1–7 December 1941",
+];
+
+/// How much detail the narrator gives, from `display.verbosity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Brief,
+    Normal,
+    Detailed,
+}
+
+impl Verbosity {
+    /// Read a verbosity setting; anything unrecognised is normal
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "brief" => Verbosity::Brief,
+            "detailed" => Verbosity::Detailed,
+            _ => Verbosity::Normal,
+        }
+    }
+}
 
 /// Converts game state into human-readable narrative text
 pub struct Narrator {
     coordinate_format: String,
-    verbosity: String,
+    verbosity: Verbosity,
 }
 
 impl Narrator {
     pub fn new(coordinate_format: String, verbosity: String) -> Self {
         Self {
             coordinate_format,
-            verbosity,
+            verbosity: Verbosity::parse(&verbosity),
         }
     }
 
+    /// Turn number, dates and phase, e.g. "TURN 3 of 7 (16–31 October 1941, MUD) — German Combat"
+    pub fn turn_heading(&self, state: &GameState) -> String {
+        if state.is_over() {
+            return format!("GAME OVER after turn {}", TURN_DATES.len());
+        }
+        let dates = TURN_DATES
+            .get((state.turn - 1) as usize)
+            .copied()
+            .unwrap_or("date unknown");
+        let mud = if state.is_mud() { ", MUD" } else { "" };
+        format!(
+            "TURN {} of {} ({}{}) — {}",
+            state.turn,
+            TURN_DATES.len(),
+            dates,
+            mud,
+            state.phase.display_name()
+        )
+    }
+
     /// Generate situation overview
-    pub fn narrate_situation(&self, state: &Value) -> String {
+    pub fn narrate_situation(&self, state: &GameState, units: &Units, map: &Map) -> String {
         let mut output = String::new();
 
-        // Extract key information from state
-        let turn = state["turn"].as_i64().unwrap_or(1);
-        let phase = state["phase"].as_str().unwrap_or("Unknown");
-        let weather = state["weather"].as_str().unwrap_or("Clear");
+        output.push_str(&format!("{}\n\n", self.turn_heading(state)));
 
-        output.push_str(&format!("TURN {} ({}) — {}\n\n", turn, weather, phase));
+        if state.is_mud() {
+            match self.verbosity {
+                Verbosity::Brief => output.push_str("Mud: movement 1 hex, attacks halved.\n\n"),
+                _ => output.push_str("The autumn rains have turned the roads to mire. All movement except Soviet rail movement is limited to 1 hex. Attack strength is halved.\n\n"),
+            }
+        }
 
-        // Add weather effects if mud
-        if weather == "Mud" {
-            output.push_str("The autumn rains have turned the roads to mire. All movement is limited to 1 hex. Attack strength is halved.\n\n");
+        let moscow = match state.get_city_control("Moscow") {
+            Some(side) => format!("{:?} controlled", side),
+            None => "uncontrolled".to_string(),
+        };
+        output.push_str(&format!("Moscow: {}\n", moscow));
+        if state.is_over() {
+            match state.check_victory() {
+                Some(side) => output.push_str(&format!("The {:?} side has won.\n", side)),
+                None => output.push_str("Nobody holds Moscow: the game is drawn.\n"),
+            }
+        }
+        output.push_str(&format!(
+            "Replacements remaining this turn: German {}, Soviet {}\n",
+            replacements_remaining(Side::German, state),
+            replacements_remaining(Side::Soviet, state)
+        ));
+
+        if self.verbosity == Verbosity::Brief {
+            return output;
         }
 
-        // Add strategic situation
-        let moscow_controller = state["moscow_controller"].as_str().unwrap_or("Soviet");
-        output.push_str(&format!("Moscow: {} controlled\n\n", moscow_controller));
+        output.push_str("\nFORCES:\n");
+        let mut cut_off = Vec::new();
+        for side in [Side::German, Side::Soviet] {
+            let (mut on_map, mut strength, mut eliminated, mut not_in_play) = (0, 0, 0, 0);
+            for def in units.units.iter().filter(|def| def.side == side) {
+                let unit = match state.get_unit(&def.id) {
+                    Some(unit) => unit,
+                    None => {
+                        not_in_play += 1;
+                        continue;
+                    }
+                };
+                match unit.hex() {
+                    Some(hex) if unit.strength != UnitStrength::Eliminated => {
+                        on_map += 1;
+                        strength += def.get_combat_strength(&unit.strength);
+                        if !can_trace_communication(&hex, side, state, units, map) {
+                            cut_off.push(def.id.clone());
+                        }
+                    }
+                    _ => eliminated += 1,
+                }
+            }
+            output.push_str(&format!(
+                "- {:?}: {} unit{} on the map (total strength {}), {} eliminated, {} not in play\n",
+                side,
+                on_map,
+                if on_map == 1 { "" } else { "s" },
+                strength,
+                eliminated,
+                not_in_play
+            ));
+        }
+        if cut_off.is_empty() {
+            output.push_str("Every unit on the map is in communication.\n");
+        } else {
+            output.push_str(&format!("OUT OF COMMUNICATION: {}\n", cut_off.join(", ")));
+        }
 
-        // TODO: Add more detailed situation analysis
-        output.push_str("Analyzing current position...\n");
+        if !state.pending_battles.is_empty() {
+            let unresolved = state.pending_battles.iter().filter(|b| !b.resolved).count();
+            output.push_str(&format!(
+                "Battles declared: {} ({} still to resolve)\n",
+                state.pending_battles.len(),
+                unresolved
+            ));
+        }
+
+        if self.verbosity == Verbosity::Detailed {
+            output.push_str("\nCITIES:\n");
+            for map_hex in &map.hexes {
+                if let Some(ref city) = map_hex.city {
+                    let controller = match state.get_city_control(&city.name) {
+                        Some(side) => format!("{:?}", side),
+                        None => "uncontrolled".to_string(),
+                    };
+                    output.push_str(&format!(
+                        "- {} at {}: {}\n",
+                        city.name,
+                        self.format_hex(map_hex.q as i64, map_hex.r as i64),
+                        controller
+                    ));
+                }
+            }
+
+            for def in units
+                .units
+                .iter()
+                .filter(|def| def.available_turn.is_some())
+            {
+                output.push_str(&format!("\n{} {}\n", def.id, arrival(def, state)));
+            }
+        }
 
         output
     }
 
     /// Generate unit list
-    pub fn narrate_units(&self, state: &Value, side: Option<&str>) -> String {
+    pub fn narrate_units(
+        &self,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+        side: Option<Side>,
+    ) -> String {
         let mut output = String::new();
 
         let sides = match side {
-            Some(s) => vec![s],
-            None => vec!["german", "soviet"],
+            Some(side) => vec![side],
+            None => vec![Side::German, Side::Soviet],
         };
 
-        for side_name in sides {
-            output.push_str(&format!("{} UNITS:\n\n", side_name.to_uppercase()));
-
-            if let Some(units) = state["units"].as_array() {
-                for unit in units {
-                    if unit["side"].as_str().unwrap_or("") == side_name {
-                        let id = unit["id"].as_str().unwrap_or("Unknown");
-                        let strength = unit["strength"].as_i64().unwrap_or(0);
-                        let position = &unit["position"];
-                        let q = position[0].as_i64().unwrap_or(0);
-                        let r = position[1].as_i64().unwrap_or(0);
-
-                        output.push_str(&format!(
-                            "- {} (strength {}) at {}\n",
-                            id,
-                            strength,
-                            self.format_hex(q, r)
-                        ));
+        for side in sides {
+            output.push_str(&format!(
+                "{} UNITS:\n",
+                format!("{:?}", side).to_uppercase()
+            ));
+
+            let mut eliminated = Vec::new();
+            let mut not_in_play = Vec::new();
+            for def in units.units.iter().filter(|def| def.side == side) {
+                let unit = match state.get_unit(&def.id) {
+                    Some(unit) => unit,
+                    None => {
+                        not_in_play.push(def.id.as_str());
+                        continue;
                     }
-                }
+                };
+                let hex = match unit.hex() {
+                    Some(hex) if unit.strength != UnitStrength::Eliminated => hex,
+                    _ => {
+                        match def.available_turn {
+                            Some(turn) if state.turn < turn => {
+                                eliminated.push(format!("{} (arrives turn {})", def.id, turn))
+                            }
+                            _ => eliminated.push(def.id.clone()),
+                        }
+                        continue;
+                    }
+                };
+                output.push_str(&self.unit_line(def, unit, hex, state, units, map));
+            }
+
+            if !eliminated.is_empty() {
+                output.push_str(&format!("Eliminated: {}\n", eliminated.join(", ")));
+            }
+            if !not_in_play.is_empty() && self.verbosity != Verbosity::Brief {
+                output.push_str(&format!("Not in play: {}\n", not_in_play.join(", ")));
             }
 
             output.push('\n');
@@ -77,99 +245,521 @@ impl Narrator {
         output
     }
 
+    /// One line of the unit list, as long as the verbosity asks for
+    fn unit_line(
+        &self,
+        def: &UnitDefinition,
+        unit: &UnitState,
+        hex: Hex,
+        state: &GameState,
+        units: &Units,
+        map: &Map,
+    ) -> String {
+        let at = self.format_hex(hex.q as i64, hex.r as i64);
+        let strength = def.get_combat_strength(&unit.strength);
+        let step = match unit.strength {
+            UnitStrength::Full => "full",
+            _ => "half",
+        };
+        let supplied = can_trace_communication(&hex, def.side, state, units, map);
+
+        if self.verbosity == Verbosity::Brief {
+            let cut_off = if supplied { "" } else { ", CUT OFF" };
+            return format!("- {} {} {} {}{}\n", def.id, at, strength, step, cut_off);
+        }
+
+        let unit_type = match def.unit_type {
+            UnitType::Infantry => "infantry",
+            UnitType::Panzer => "panzer",
+        };
+        let mut line = format!(
+            "- {} ({}) at {}: {} strength {}, {}",
+            def.id,
+            unit_type,
+            at,
+            step,
+            strength,
+            if supplied {
+                "in communication"
+            } else {
+                "OUT OF COMMUNICATION"
+            }
+        );
+
+        if self.verbosity == Verbosity::Detailed {
+            let terrain = match map.get_hex(&hex) {
+                Some(map_hex) => format!("{:?}", map_hex.terrain),
+                None => "Clear".to_string(),
+            };
+            line.push_str(&format!("; {} MP, {}", def.movement, terrain));
+            if let Some(city) = map.get_hex(&hex).and_then(|map_hex| map_hex.city.as_ref()) {
+                line.push_str(&format!(" ({})", city.name));
+            }
+            if state.has_moved(&def.id) {
+                line.push_str(", moved this phase");
+            }
+            if is_in_enemy_zoc(&hex, state, units, def.side) {
+                line.push_str(", in enemy ZOC");
+            }
+        }
+
+        line.push('\n');
+        line
+    }
+
     /// Generate threat analysis
-    pub fn narrate_threats(&self, state: &Value, side: &str) -> String {
+    pub fn narrate_threats(&self, report: &ThreatReport) -> String {
         let mut output = String::new();
+        let hex = |hex: &Hex| self.format_hex(hex.q as i64, hex.r as i64);
+
+        output.push_str(&format!(
+            "THREATS TO {} POSITION:\n\n",
+            format!("{:?}", report.side).to_uppercase()
+        ));
+
+        output.push_str("UNITS UNDER ATTACK NEXT PHASE:\n");
+        if report.unit_threats.is_empty() {
+            output.push_str("- No enemy unit can reach any of yours.\n");
+        }
+        for threat in &report.unit_threats {
+            output.push_str(&format!(
+                "- {} at {}: {} could attack at {} ({} vs {}), {:.0}% chance of dislodging it\n",
+                threat.unit,
+                hex(&threat.hex),
+                threat.attackers.join(" + "),
+                threat.best_attack.modified_odds,
+                threat.best_attack.attack_strength,
+                threat.best_attack.defense_strength,
+                threat.dislodge_chance * 100.0
+            ));
+        }
+
+        output.push_str("\nCOMMUNICATION:\n");
+        if report.cut_off_risks.is_empty() {
+            output.push_str("- Every unit has safe routes to your map edge.\n");
+        }
+        for risk in &report.cut_off_risks {
+            if risk.routes == 0 {
+                output.push_str(&format!(
+                    "- {} at {} is already cut off from your map edge\n",
+                    risk.unit,
+                    hex(&risk.hex)
+                ));
+                continue;
+            }
+            let chokepoints: Vec<String> = risk.chokepoints.iter().map(hex).collect();
+            output.push_str(&format!(
+                "- {} at {} could be cut off: {} could close {}\n",
+                risk.unit,
+                hex(&risk.hex),
+                risk.enemies.join(" and "),
+                chokepoints.join(" and ")
+            ));
+        }
+
+        output.push_str("\nWEAK POINTS:\n");
+        if report.weak_points.is_empty() {
+            output.push_str("- Your cities are firmly held.\n");
+        }
+        for point in &report.weak_points {
+            let holder = match point.garrison {
+                Some(ref garrison) => format!("held by {}", garrison),
+                None => "empty".to_string(),
+            };
+            output.push_str(&format!(
+                "- {} at {} ({}): {:.0}% chance of falling to {}\n",
+                point.name,
+                hex(&point.hex),
+                holder,
+                point.loss_chance * 100.0,
+                point.enemies.join(", ")
+            ));
+        }
+
+        output.push_str("\nGAPS IN THE LINE:\n");
+        if report.front_gaps.is_empty() {
+            output.push_str("- The enemy cannot get behind your units.\n");
+        }
+        for gap in &report.front_gaps {
+            output.push_str(&format!(
+                "- {} is open to {}\n",
+                hex(&gap.hex),
+                gap.enemies.join(", ")
+            ));
+        }
 
-        output.push_str(&format!("THREATS TO {} POSITION:\n\n", side.to_uppercase()));
-        output.push_str("Analyzing tactical situation...\n");
+        output
+    }
 
-        // TODO: Implement threat analysis logic
+    /// Generate the assessment of who is winning
+    pub fn narrate_evaluation(&self, assessment: &Assessment) -> String {
+        let mut output = String::new();
+        let evaluation = &assessment.evaluation;
+
+        output.push_str("POSITION ASSESSMENT:\n\n");
+        if evaluation.score.abs() < 0.05 {
+            output.push_str("The position is even");
+        } else if evaluation.score > 0.0 {
+            output.push_str(&format!("The Germans lead by {:.2}", evaluation.score));
+        } else {
+            output.push_str(&format!("The Soviets lead by {:.2}", -evaluation.score));
+        }
+        output.push_str(&format!(
+            " (German score {:+.2}, Soviet score {:+.2})\n",
+            assessment.german_score, assessment.soviet_score
+        ));
+
+        output.push_str(&format!(
+            "Chance of winning: German {:.0}%, Soviet {:.0}%",
+            assessment.german_win_probability * 100.0,
+            assessment.soviet_win_probability * 100.0
+        ));
+        if assessment.playouts == 0 {
+            output.push_str(", read from the score\n");
+        } else {
+            output.push_str(&format!(
+                ", from {} random playouts\n",
+                assessment.playouts
+            ));
+        }
+
+        let controller = match evaluation.moscow_controller {
+            Some(side) => format!("{:?} controlled", side),
+            None => "uncontrolled".to_string(),
+        };
+        output.push_str(&format!("Moscow: {}", controller));
+        if let Some(distance) = evaluation.moscow_distance {
+            output.push_str(&format!(
+                ", nearest German unit {} hex{} away",
+                distance,
+                if distance == 1 { "" } else { "es" }
+            ));
+        }
+        output.push_str("\n\nWHAT THE SCORE IS MADE OF (positive favours the Germans):\n");
+        for factor in &evaluation.factors {
+            output.push_str(&format!(
+                "- {}: {:+.2} (measured {:.2})\n",
+                factor_label(&factor.name),
+                factor.contribution,
+                factor.value
+            ));
+        }
 
         output
     }
 
     /// Generate valid moves list
-    pub fn narrate_valid_moves(&self, unit_id: &str, moves: &Value) -> String {
+    pub fn narrate_valid_moves(
+        &self,
+        unit_id: &str,
+        from: Hex,
+        allowance: i32,
+        options: &[MoveOption],
+        map: &Map,
+        mud: bool,
+    ) -> String {
         let mut output = String::new();
 
         output.push_str(&format!("VALID MOVES FOR {}:\n\n", unit_id));
+        output.push_str(&format!(
+            "Current position: {}\n",
+            self.format_hex(from.q as i64, from.r as i64)
+        ));
+        output.push_str(&format!("Movement allowance: {}\n\n", allowance));
+
+        if options.is_empty() {
+            output.push_str("No hexes within reach.\n");
+            return output;
+        }
+
+        output.push_str("Can reach:\n");
+        for option in options {
+            let terrain = match map.get_hex(&option.hex) {
+                Some(map_hex) => format!("{:?}", map_hex.terrain),
+                None => "Clear".to_string(),
+            };
+            output.push_str(&format!(
+                "- {}: {}, {} MP",
+                self.format_hex(option.hex.q as i64, option.hex.r as i64),
+                terrain,
+                option.cost
+            ));
+            if option.stops_in_zoc {
+                output.push_str(". Enters enemy ZOC — movement would end here");
+            }
+            output.push_str(".\n");
+        }
+
+        if mud && allowance == 1 {
+            output.push_str("\nNote: Mud limits all movement to 1 hex this turn.\n");
+        }
+
+        output
+    }
+
+    /// Generate attack preview
+    pub fn narrate_attack_preview(&self, preview: &BattlePreview) -> String {
+        let mut output = String::new();
+
+        output.push_str("ATTACK PREVIEW:\n\n");
+
+        output.push_str(&format!("Attackers: {}\n", preview.attackers.join(", ")));
+        output.push_str(&format!("Total strength: {}", preview.attack_strength));
+        if preview.mud {
+            output.push_str(" (halved for mud)");
+        }
+        output.push_str(&format!(
+            "\nDefender: {} (strength {})\n",
+            preview.defender, preview.defense_strength
+        ));
+        if preview.terrain_modifiers.is_empty() {
+            output.push_str("Terrain: no modifiers\n");
+        } else {
+            output.push_str(&format!(
+                "Terrain: {} (each shifts the odds one column left)\n",
+                preview.terrain_modifiers.join(", ")
+            ));
+        }
+        output.push_str(&format!("\nRaw odds: {}\n", preview.raw_odds));
+        output.push_str(&format!("Final odds: {}\n\n", preview.modified_odds));
+
+        output.push_str("POSSIBLE OUTCOMES:\n");
+        for outcome in &preview.outcomes {
+            if outcome.die_rolls.is_empty() {
+                output.push_str("- No roll: odds below 1:1 have no effect\n");
+                continue;
+            }
+            output.push_str(&format!(
+                "- Roll {} ({:.0}%): {:?} — {}\n",
+                format_rolls(&outcome.die_rolls),
+                outcome.probability * 100.0,
+                outcome.result,
+                outcome_text(&outcome.result)
+            ));
+        }
+
+        let chance = |results: &[CombatResult]| -> f64 {
+            preview
+                .outcomes
+                .iter()
+                .filter(|o| results.contains(&o.result))
+                .fold(0.0, |total, o| total + o.probability)
+                * 100.0
+        };
+        output.push_str("\nRISK ASSESSMENT:\n");
+        output.push_str(&format!(
+            "{:.0}% chance of losing a step; {:.0}% chance of dislodging the defender.\n",
+            chance(&[CombatResult::AL, CombatResult::EX]),
+            chance(&[CombatResult::DR, CombatResult::DRL, CombatResult::DE, CombatResult::EX])
+        ));
+        output.push_str(&format!(
+            "Expected step losses: {:.2} for you, {:.2} for the defender.\n",
+            preview.expected_attacker_losses, preview.expected_defender_losses
+        ));
 
-        if let Some(move_list) = moves.as_array() {
-            for move_option in move_list {
-                let q = move_option["q"].as_i64().unwrap_or(0);
-                let r = move_option["r"].as_i64().unwrap_or(0);
-                let cost = move_option["cost"].as_i64().unwrap_or(0);
+        output
+    }
 
+    /// Generate the list of attacks still open this combat phase
+    pub fn narrate_valid_attacks(&self, options: &[AttackOption]) -> String {
+        let mut output = String::new();
+
+        output.push_str("POSSIBLE ATTACKS THIS PHASE:\n\n");
+        if options.is_empty() {
+            output.push_str("No enemy units can be attacked.\n");
+            return output;
+        }
+
+        for (i, option) in options.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. Target: {} at {}\n",
+                i + 1,
+                option.defender,
+                self.format_hex(option.defender_hex.q as i64, option.defender_hex.r as i64)
+            ));
+            output.push_str(&format!(
+                "   Your adjacent units: {}\n",
+                option.eligible_attackers.join(", ")
+            ));
+            for group in &option.groups {
                 output.push_str(&format!(
-                    "- {}: {} MP\n",
-                    self.format_hex(q, r),
-                    cost
+                    "   - {}: {} vs {} = {}\n",
+                    group.attackers.join(" + "),
+                    group.attack_strength,
+                    group.defense_strength,
+                    group.modified_odds
                 ));
             }
+            output.push('\n');
+        }
+
+        // Units adjacent to several targets must pick one
+        let mut shared = Vec::new();
+        for option in options {
+            for unit in &option.eligible_attackers {
+                let targets: Vec<&str> = options
+                    .iter()
+                    .filter(|o| o.eligible_attackers.contains(unit))
+                    .map(|o| o.defender.as_str())
+                    .collect();
+                if targets.len() > 1 && !shared.iter().any(|(u, _)| u == unit) {
+                    shared.push((unit.clone(), targets));
+                }
+            }
+        }
+        for (unit, targets) in shared {
+            output.push_str(&format!(
+                "Note: {} can reach {} — it may attack only one of them.\n",
+                unit,
+                targets.join(" and ")
+            ));
         }
 
         output
     }
 
-    /// Generate attack preview
-    pub fn narrate_attack_preview(&self, preview: &Value) -> String {
+    /// Generate the ranked attack plans
+    pub fn narrate_attack_plans(&self, plans: &[AttackPlan]) -> String {
         let mut output = String::new();
 
-        output.push_str("ATTACK PREVIEW:\n\n");
+        output.push_str("SUGGESTED ATTACK PLANS:\n\n");
+        if plans.is_empty() {
+            output.push_str("No attack is expected to gain more than it risks.\n");
+            return output;
+        }
 
-        let attack_strength = preview["attack_strength"].as_i64().unwrap_or(0);
-        let defense_strength = preview["defense_strength"].as_i64().unwrap_or(0);
-        let odds = preview["odds"].as_str().unwrap_or("1:1");
+        for (i, plan) in plans.iter().enumerate() {
+            output.push_str(&format!("{}. Score {:.2}\n", i + 1, plan.score));
+            for battle in &plan.battles {
+                output.push_str(&format!(
+                    "   - {} attack {} at {}\n",
+                    battle.attackers.join(" + "),
+                    battle.defender,
+                    battle.modified_odds
+                ));
+            }
+            output.push_str(&format!(
+                "   Expected: {:.2} enemy steps destroyed, {:.2} retreats forced, {:.2} steps lost\n\n",
+                plan.expected_defender_losses, plan.expected_retreats, plan.expected_attacker_losses
+            ));
+        }
 
-        output.push_str(&format!("Attack strength: {}\n", attack_strength));
-        output.push_str(&format!("Defense strength: {}\n", defense_strength));
-        output.push_str(&format!("Odds: {}\n\n", odds));
+        output
+    }
 
-        output.push_str("POSSIBLE OUTCOMES:\n");
-        // TODO: Add outcome probabilities from CRT
+    /// Generate the replacement options of a side
+    pub fn narrate_replacements(
+        &self,
+        side: Side,
+        remaining: i32,
+        options: &[ReplacementOption],
+        units: &Units,
+    ) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!(
+            "{} REPLACEMENTS: {} remaining this turn\n\n",
+            format!("{:?}", side).to_uppercase(),
+            remaining
+        ));
+
+        let restore: Vec<&ReplacementOption> = options.iter().filter(|o| o.hex.is_some()).collect();
+        let place: Vec<&ReplacementOption> = options.iter().filter(|o| o.hex.is_none()).collect();
+
+        output.push_str("Can restore to full strength:\n");
+        if restore.is_empty() {
+            output.push_str("- None\n");
+        }
+        for option in restore {
+            let line = match (option.hex, units.get(&option.unit_id)) {
+                (Some(hex), Some(def)) => format!(
+                    "- {} at {} (currently strength {}, would become {})\n",
+                    option.unit_id,
+                    self.format_hex(hex.q as i64, hex.r as i64),
+                    def.half_strength,
+                    def.full_strength
+                ),
+                _ => format!("- {}\n", option.unit_id),
+            };
+            output.push_str(&line);
+        }
+
+        output.push_str("\nCan bring back from eliminated:\n");
+        if place.is_empty() {
+            output.push_str("- None\n");
+        }
+        for option in place {
+            let strength = units.get(&option.unit_id).map_or(0, |def| def.half_strength);
+            let hexes: Vec<String> = option
+                .placements
+                .iter()
+                .map(|hex| self.format_hex(hex.q as i64, hex.r as i64))
+                .collect();
+            output.push_str(&format!(
+                "- {} (half strength = {}), at: {}\n",
+                option.unit_id,
+                strength,
+                hexes.join(", ")
+            ));
+        }
 
         output
     }
 
     /// Generate combat resolution narrative
-    pub fn narrate_combat_result(&self, result: &Value) -> String {
+    pub fn narrate_combat_result(&self, resolution: &BattleResolution) -> String {
         let mut output = String::new();
 
         output.push_str("BATTLE RESOLVED:\n\n");
 
-        let die_roll = result["die_roll"].as_i64().unwrap_or(1);
-        let outcome = result["outcome"].as_str().unwrap_or("NE");
-
-        output.push_str(&format!("Die roll: {}\n", die_roll));
-        output.push_str(&format!("Result: {}\n\n", outcome));
+        output.push_str(&format!("Odds: {}", resolution.raw_odds));
+        if resolution.modified_odds != resolution.raw_odds {
+            output.push_str(&format!(" shifted to {}", resolution.modified_odds));
+        }
+        if !resolution.terrain_modifiers.is_empty() {
+            output.push_str(&format!(" ({})", resolution.terrain_modifiers.join(", ")));
+        }
+        // Odds below 1:1 are not rolled for
+        match resolution.die_roll {
+            0 => output.push_str("\nNo die roll\n"),
+            roll => output.push_str(&format!("\nDie roll: {}\n", roll)),
+        }
+        output.push_str(&format!("Result: {:?}\n\n", resolution.result));
 
         // Explain the result
-        match outcome {
-            "NE" => output.push_str("No effect.\n"),
-            "DR" => output.push_str("Defender retreats.\n"),
-            "DRL" => output.push_str("Defender retreats with loss.\n"),
-            "DE" => output.push_str("Defender eliminated.\n"),
-            "AL" => output.push_str("Attacker loses a step.\n"),
-            "EX" => output.push_str("Exchange - both sides take losses.\n"),
-            _ => output.push_str("Unknown result.\n"),
+        match resolution.result {
+            CombatResult::NE => output.push_str("No effect.\n"),
+            CombatResult::DR => output.push_str("Defender retreats.\n"),
+            CombatResult::DRL => output.push_str("Defender retreats with loss.\n"),
+            CombatResult::DE => output.push_str("Defender eliminated.\n"),
+            CombatResult::AL => output.push_str("Attacker loses a step.\n"),
+            CombatResult::EX => output.push_str("Exchange - both sides take losses.\n"),
         }
 
         output
     }
 
     /// Format hex coordinates
-    fn format_hex(&self, q: i64, r: i64) -> String {
+    pub fn format_hex(&self, q: i64, r: i64) -> String {
         match self.coordinate_format.as_str() {
             "axial" => format!("[{},{}]", q, r),
-            "original" => {
-                // Convert to XXYY format if needed
-                format!("[{},{}]", q, r)
-            }
+            // Hexes without a printed number fall back to axial
+            "original" => match Hex::new(q as i32, r as i32).to_original() {
+                Some(original) => format!("[{}]", original),
+                None => format!("[{},{}]", q, r),
+            },
             _ => format!("[{},{}]", q, r),
         }
     }
 
+    /// Hex labels for drawn maps, following the configured coordinate format
+    pub fn coordinate_labels(&self) -> CoordinateLabels {
+        match self.coordinate_format.as_str() {
+            "original" => CoordinateLabels::Original,
+            _ => CoordinateLabels::Axial,
+        }
+    }
+
     /// Generate rules explanation
     pub fn narrate_rules(&self, topic: &str) -> String {
         match topic.to_lowercase().as_str() {
@@ -355,3 +945,148 @@ Moscow is worth the entire war - there is no partial victory."#
             .to_string()
     }
 }
+
+/// Die rolls as a range ("1-3") when consecutive, else listed ("2, 5")
+fn format_rolls(rolls: &[i32]) -> String {
+    match rolls {
+        [single] => single.to_string(),
+        [first, .., last] if last - first + 1 == rolls.len() as i32 => {
+            format!("{}-{}", first, last)
+        }
+        _ => rolls
+            .iter()
+            .map(i32::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// What a combat result means for the attacker
+fn outcome_text(result: &CombatResult) -> &'static str {
+    match result {
+        CombatResult::NE => "no effect",
+        CombatResult::DR => "defender retreats 2 hexes",
+        CombatResult::DRL => "defender loses a step, then retreats",
+        CombatResult::AL => "one of your units takes a step loss",
+        CombatResult::DE => "defender eliminated",
+        CombatResult::EX => "exchange — both sides lose strength",
+    }
+}
+
+/// What an evaluation factor measures, for people
+fn factor_label(name: &str) -> &str {
+    match name {
+        "moscow_control" => "German hold on Moscow",
+        "moscow_distance" => "German closeness to Moscow",
+        "strength" => "German share of combat strength",
+        "supply" => "Units in communication, German less Soviet",
+        "front" => "Units in a connected line, German less Soviet",
+        other => other,
+    }
+}
+
+/// Whether a late-arriving unit has entered the game yet
+fn arrival(def: &UnitDefinition, state: &GameState) -> String {
+    match def.available_turn {
+        Some(turn) if state.turn < turn => format!("arrives on turn {}", turn),
+        _ => "has arrived".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::embedded::bundled_data;
+    use backend::game_state::Phase;
+
+    fn start(verbosity: &str) -> (Narrator, GameState, Units, Map) {
+        let (units, map) = bundled_data().unwrap();
+        let mut state = GameState::with_seed("narrator");
+        state.place_initial_units(&units, &map);
+        let narrator = Narrator::new("axial".to_string(), verbosity.to_string());
+        (narrator, state, units, map)
+    }
+
+    #[test]
+    fn test_turn_heading() {
+        let (narrator, mut state, _, _) = start("normal");
+        assert_eq!(
+            narrator.turn_heading(&state),
+            "TURN 1 of 7 (2–8 October 1941) — German Panzer Movement"
+        );
+        state.turn = 4;
+        state.phase = Phase::SovietCombat;
+        assert_eq!(
+            narrator.turn_heading(&state),
+            "TURN 4 of 7 (1–15 November 1941, MUD) — Soviet Combat"
+        );
+        state.turn = 8;
+        assert_eq!(narrator.turn_heading(&state), "GAME OVER after turn 7");
+    }
+
+    #[test]
+    fn test_situation_verbosity() {
+        let (brief, mut state, units, map) = start("brief");
+        state.turn = 3;
+        let text = brief.narrate_situation(&state, &units, &map);
+        assert!(text.contains("Mud: movement 1 hex, attacks halved.\n"));
+        assert!(text.ends_with("Replacements remaining this turn: German 1, Soviet 0\n"));
+
+        let (normal, state, _, _) = start("normal");
+        let text = normal.narrate_situation(&state, &units, &map);
+        assert!(!text.contains("Mud"));
+        assert!(text.contains(
+            "- German: 1 unit on the map (total strength 6), 0 eliminated, 21 not in play\n"
+        ));
+        assert!(text.contains("Every unit on the map is in communication.\n"));
+        assert!(!text.contains("CITIES:"));
+
+        let (detailed, mut state, _, _) = start("detailed");
+        state.get_unit_mut("3").unwrap().take_loss();
+        let text = detailed.narrate_situation(&state, &units, &map);
+        assert!(text.contains("- Soviet: 1 unit on the map (total strength 4)"));
+        assert!(text.contains("CITIES:\n- Moscow at [3,0]: Soviet\n"));
+        assert!(text.contains("1S arrives on turn 4\n"));
+    }
+
+    #[test]
+    fn test_units_verbosity() {
+        let (normal, mut state, units, map) = start("normal");
+        // Soviet 5 and German VI block each other's way to their map edges
+        state.units.push(UnitState::new(
+            "5".to_string(),
+            Some(Hex::new(1, 0)),
+            UnitStrength::Half,
+        ));
+        state.units.push(UnitState::new(
+            "VI".to_string(),
+            Some(Hex::new(2, 0)),
+            UnitStrength::Full,
+        ));
+        let text = normal.narrate_units(&state, &units, &map, Some(Side::German));
+        assert!(text.starts_with(
+            "GERMAN UNITS:\n- V (infantry) at [0,0]: full strength 6, in communication\n"
+        ));
+        assert!(text.contains("- VI (infantry) at [2,0]: full strength 5, OUT OF COMMUNICATION\n"));
+        assert!(!text.contains("SOVIET UNITS:"));
+        let text = normal.narrate_units(&state, &units, &map, Some(Side::Soviet));
+        assert!(text.contains("- 5 (infantry) at [1,0]: half strength 4, OUT OF COMMUNICATION\n"));
+
+        let (brief, _, _, _) = start("brief");
+        let text = brief.narrate_units(&state, &units, &map, Some(Side::Soviet));
+        assert!(text.contains("- 5 [1,0] 4 half, CUT OFF\n"));
+        assert!(!text.contains("Not in play"));
+
+        let (detailed, _, _, _) = start("detailed");
+        state.get_unit_mut("3").unwrap().take_loss();
+        state.get_unit_mut("3").unwrap().take_loss();
+        state.units.push(UnitState::new(
+            "1S".to_string(),
+            None,
+            UnitStrength::Eliminated,
+        ));
+        let text = detailed.narrate_units(&state, &units, &map, None);
+        assert!(text.contains("- V (infantry) at [0,0]: full strength 6, in communication; 4 MP, Clear, in enemy ZOC\n"));
+        assert!(text.contains("Eliminated: 3, 1S (arrives turn 4)\n"));
+    }
+}
@@ -1,11 +1,15 @@
+mod bots;
 mod config;
 mod game;
 mod mcp;
+mod orchestrator;
 mod text;
 
 use anyhow::Result;
+use backend::unit::Side;
 use clap::{Parser, ValueEnum};
-use mcp::server::McpServer;
+use game::Narrator;
+use mcp::McpServer;
 use text::run_text_mode;
 
 #[derive(Parser)]
@@ -15,6 +19,23 @@ struct Cli {
     /// Mode to run in
     #[arg(long, default_value = "text")]
     mode: Mode,
+
+    /// German player: human, heuristic, mcts[:settings] or bot:COMMAND in bots mode
+    /// (default human); openai:MODEL or script:PATH in orchestrate mode
+    #[arg(long)]
+    german: Option<String>,
+
+    /// Soviet player, as for --german (default heuristic in bots mode)
+    #[arg(long)]
+    soviet: Option<String>,
+
+    /// Dice seed for bots and orchestrate modes, random if not given
+    #[arg(long)]
+    seed: Option<String>,
+
+    /// Number of games to play in orchestrate mode
+    #[arg(long, default_value_t = 1)]
+    games: usize,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -23,6 +44,10 @@ enum Mode {
     Text,
     /// MCP (Model Context Protocol) server
     Mcp,
+    /// A game between bots and/or a human at the terminal
+    Bots,
+    /// Games between language models, with a transcript of each
+    Orchestrate,
 }
 
 #[tokio::main]
@@ -39,9 +64,43 @@ async fn main() -> Result<()> {
         }
         Mode::Mcp => {
             // Run MCP server
-            let server = McpServer::new(config);
+            let game_client = game::backend::connect(&config).await?;
+            let server = McpServer::new(config, game_client);
             server.run().await?;
         }
+        Mode::Bots => {
+            // Players block on their own input, so keep them off the async runtime
+            let display = config.display.clone();
+            let german = cli.german.unwrap_or_else(|| "human".to_string());
+            let soviet = cli.soviet.unwrap_or_else(|| "heuristic".to_string());
+            let record = tokio::task::spawn_blocking(move || {
+                bots::run_bot_game(&german, &soviet, cli.seed.as_deref(), || {
+                    Narrator::new(
+                        display.coordinate_format.clone(),
+                        display.verbosity.clone(),
+                    )
+                })
+            })
+            .await??;
+            println!("{}", bots::report(&record));
+        }
+        Mode::Orchestrate => {
+            let (Some(german), Some(soviet)) = (cli.german, cli.soviet) else {
+                anyhow::bail!("Orchestrate mode needs --german and --soviet models");
+            };
+            let results = orchestrator::run_orchestrator(
+                &config,
+                &german,
+                &soviet,
+                cli.games,
+                cli.seed.as_deref(),
+            )
+            .await?;
+            for side in [Side::German, Side::Soviet] {
+                let wins = results.iter().filter(|r| r.winner == Some(side)).count();
+                eprintln!("{:?} wins: {} of {}", side, wins, results.len());
+            }
+        }
     }
 
     Ok(())
@@ -1,5 +1,9 @@
+pub mod prompts;
+pub mod resources;
 pub mod server;
 pub mod tools;
 
+pub use prompts::PromptHandler;
+pub use resources::ResourceHandler;
 pub use server::McpServer;
 pub use tools::ToolHandler;
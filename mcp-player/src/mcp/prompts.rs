@@ -0,0 +1,152 @@
+use crate::mcp::server::RpcError;
+use anyhow::Result;
+use serde_json::{json, Value};
+
+/// A canned prompt from the MCP spec, section 8
+struct Prompt {
+    name: &'static str,
+    description: &'static str,
+    text: &'static str,
+}
+
+const PROMPTS: &[Prompt] = &[
+    Prompt {
+        name: "play_german",
+        description: "Play as German commander",
+        text: PLAY_GERMAN,
+    },
+    Prompt {
+        name: "play_soviet",
+        description: "Play as Soviet commander",
+        text: PLAY_SOVIET,
+    },
+    Prompt {
+        name: "analyse_position",
+        description: "Analyse current position without taking action",
+        text: ANALYSE_POSITION,
+    },
+];
+
+const PLAY_GERMAN: &str = r#"You are playing Battle for Moscow as the German commander.
+
+Your objective: Capture Moscow by the end of Turn 7.
+
+You command Army Group Center's forces including powerful panzer corps.
+The offensive began well but autumn mud is slowing your advance.
+
+Use the available tools to:
+1. Check the situation with get_situation
+2. Review your units with get_units
+3. Preview attacks with preview_attack before committing
+4. Make your moves and attacks
+
+Think strategically:
+- Panzers are your striking power — use them to exploit breakthroughs
+- Infantry holds ground — don't let your line get too thin
+- Moscow is heavily defended — you may need to approach from multiple directions
+- Time is against you — be aggressive but not reckless
+
+It is currently your turn. What would you like to do?"#;
+
+const PLAY_SOVIET: &str = r#"You are playing Battle for Moscow as the Soviet commander.
+
+Your objective: Hold Moscow until the end of Turn 7.
+
+You command the armies of the Western and Reserve Fronts. They are battered and
+at half strength, but five replacements arrive every turn and the railways let you
+shift them quickly.
+
+Use the available tools to:
+1. Check the situation with get_situation
+2. Look for danger with get_threats
+3. Review your units with get_units
+4. Make your moves, and counterattack where the odds favour you
+
+Think strategically:
+- Every hex you give up costs the Germans time — trade space for time
+- Keep a continuous line so panzers cannot slip through your zones of control
+- Rebuild broken armies with replacements; Moscow can take them even when cut off
+- The mud on Turns 3 and 4 halves German attacks — use it to dig in
+- 1st Shock Army is available from Turn 4
+
+It is currently your turn. What would you like to do?"#;
+
+const ANALYSE_POSITION: &str = r#"You are a staff officer reviewing the Battle for Moscow.
+
+Analyse the current position without taking any action: do not move units,
+declare attacks or end phases.
+
+Use the available tools to:
+1. Check the situation with get_situation
+2. Review both sides with get_units
+3. Assess danger with get_threats
+4. Preview the most promising attacks with preview_attack
+
+Then report:
+- Who is winning, and why
+- The strongest and weakest sectors of each front
+- The best moves available to the side whose turn it is
+- What the other side should be preparing for"#;
+
+/// Serves the canned prompts
+pub struct PromptHandler;
+
+impl PromptHandler {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn list_prompts(&self) -> Vec<Value> {
+        PROMPTS
+            .iter()
+            .map(|prompt| {
+                json!({
+                    "name": prompt.name,
+                    "description": prompt.description,
+                    "arguments": []
+                })
+            })
+            .collect()
+    }
+
+    /// The `prompts/get` result for a prompt
+    pub fn get_prompt(&self, name: &str) -> Result<Value> {
+        let prompt = PROMPTS
+            .iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown prompt: {}", name)))?;
+
+        Ok(json!({
+            "description": prompt.description,
+            "messages": [{
+                "role": "user",
+                "content": {
+                    "type": "text",
+                    "text": prompt.text
+                }
+            }]
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_prompt() {
+        let handler = PromptHandler::new();
+        for listed in handler.list_prompts() {
+            let prompt = handler
+                .get_prompt(listed["name"].as_str().unwrap())
+                .unwrap();
+            assert_eq!(prompt["description"], listed["description"]);
+            assert_eq!(prompt["messages"][0]["role"], "user");
+        }
+
+        let german = handler.get_prompt("play_german").unwrap();
+        let text = german["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.starts_with("You are playing Battle for Moscow as the German commander."));
+        assert!(handler.get_prompt("play_italian").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::game::GameBackend;
+use crate::mcp::server::RpcError;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// The original rules, compiled into the binary
+const RULES: &str = include_str!("../../../bfm-project/RULES.md");
+
+/// Heading of the rules section served as `bfm://rules/crt`
+const CRT_HEADING: &str = "Combat Results Table";
+
+/// Serves the rules and the live game as MCP resources
+pub struct ResourceHandler;
+
+impl ResourceHandler {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn list_resources(&self) -> Vec<Value> {
+        let mut resources = vec![
+            json!({
+                "uri": "bfm://rules/full",
+                "name": "Complete Rules",
+                "description": "Full game rules reference",
+                "mimeType": "text/markdown"
+            }),
+            json!({
+                "uri": "bfm://rules/crt",
+                "name": "Combat Results Table",
+                "description": "Combat outcomes by odds and die roll",
+                "mimeType": "text/markdown"
+            }),
+        ];
+
+        for (heading, _) in rules_sections().filter(|(h, _)| *h != CRT_HEADING) {
+            resources.push(json!({
+                "uri": format!("bfm://rules/{}", slug(heading)),
+                "name": format!("Rules: {}", heading),
+                "description": format!("The \"{}\" section of the rules", heading),
+                "mimeType": "text/markdown"
+            }));
+        }
+
+        resources.push(json!({
+            "uri": "bfm://game/state",
+            "name": "Game State",
+            "description": "Current turn, phase, unit positions and battles",
+            "mimeType": "application/json"
+        }));
+        resources.push(json!({
+            "uri": "bfm://game/map",
+            "name": "Map",
+            "description": "Hexes, terrain, cities and setup markers",
+            "mimeType": "application/json"
+        }));
+        resources
+    }
+
+    /// Read a resource, returning its `contents` entries
+    pub async fn read_resource(&self, uri: &str, client: &dyn GameBackend) -> Result<Vec<Value>> {
+        let (mime_type, text) = match uri {
+            "bfm://rules/full" => ("text/markdown", RULES.to_string()),
+            "bfm://rules/crt" => ("text/markdown", rules_section(CRT_HEADING)?.to_string()),
+            "bfm://game/state" => (
+                "application/json",
+                serde_json::to_string_pretty(&client.get_state().await?)?,
+            ),
+            "bfm://game/map" => (
+                "application/json",
+                serde_json::to_string_pretty(&client.get_map().await?)?,
+            ),
+            _ => {
+                let not_found = || {
+                    RpcError::new(
+                        RpcError::RESOURCE_NOT_FOUND,
+                        format!("Unknown resource: {}", uri),
+                    )
+                };
+                let name = uri.strip_prefix("bfm://rules/").ok_or_else(not_found)?;
+                let (_, section) = rules_sections()
+                    .find(|(heading, _)| slug(heading) == name)
+                    .ok_or_else(not_found)?;
+                ("text/markdown", section.to_string())
+            }
+        };
+
+        Ok(vec![json!({
+            "uri": uri,
+            "mimeType": mime_type,
+            "text": text
+        })])
+    }
+}
+
+/// The `##` sections of the rules as (heading, text including the heading)
+fn rules_sections() -> impl Iterator<Item = (&'static str, &'static str)> {
+    let starts: Vec<usize> = RULES.match_indices("\n## ").map(|(i, _)| i + 1).collect();
+    let ends: Vec<usize> = starts
+        .iter()
+        .skip(1)
+        .copied()
+        .chain([RULES.len()])
+        .collect();
+    starts.into_iter().zip(ends).map(|(start, end)| {
+        let section = RULES[start..end].trim_end();
+        let heading = section.lines().next().unwrap_or_default();
+        (heading.trim_start_matches("## ").trim(), section)
+    })
+}
+
+fn rules_section(heading: &str) -> Result<&'static str> {
+    rules_sections()
+        .find(|(h, _)| *h == heading)
+        .map(|(_, text)| text)
+        .ok_or_else(|| anyhow!("The rules have no \"{}\" section", heading))
+}
+
+/// "Zone of Control" -> "zone-of-control"
+fn slug(heading: &str) -> String {
+    heading
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::embedded::EmbeddedEngine;
+
+    #[test]
+    fn test_rules_sections() {
+        let crt = rules_section(CRT_HEADING).unwrap();
+        assert!(crt.starts_with("## Combat Results Table"));
+        assert!(crt.contains("| 6   | DR  | DRL |"));
+        assert!(crt.contains("**DE (Defender Eliminated):**"));
+        assert!(!crt.contains("## Mud"));
+
+        assert_eq!(slug("Zone of Control"), "zone-of-control");
+        assert_eq!(slug("1st Shock Army"), "1st-shock-army");
+    }
+
+    #[tokio::test]
+    async fn test_every_listed_resource_reads() {
+        let handler = ResourceHandler::new();
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let resources = handler.list_resources();
+        assert!(resources.iter().any(|r| r["uri"] == "bfm://rules/mud"));
+        assert!(!resources
+            .iter()
+            .any(|r| r["uri"] == "bfm://rules/combat-results-table"));
+
+        for resource in resources {
+            let uri = resource["uri"].as_str().unwrap();
+            let contents = handler.read_resource(uri, &engine).await.unwrap();
+            assert_eq!(contents[0]["uri"], uri);
+            assert_eq!(contents[0]["mimeType"], resource["mimeType"]);
+            assert!(!contents[0]["text"].as_str().unwrap().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_game_resources() {
+        let handler = ResourceHandler::new();
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let contents = handler
+            .read_resource("bfm://game/state", &engine)
+            .await
+            .unwrap();
+        let state: Value = serde_json::from_str(contents[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(state["turn"], 1);
+
+        let err = handler
+            .read_resource("bfm://rules/nonsense", &engine)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RpcError>().unwrap().code,
+            RpcError::RESOURCE_NOT_FOUND
+        );
+        assert!(handler.read_resource("file:///etc", &engine).await.is_err());
+    }
+}
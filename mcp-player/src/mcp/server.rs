@@ -1,39 +1,94 @@
 use crate::config::Config;
-use crate::game::{GameClient, Narrator};
-use crate::mcp::tools::ToolHandler;
+use crate::game::{GameBackend, Narrator};
+use crate::mcp::{PromptHandler, ResourceHandler, ToolHandler};
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use backend::jsonrpc::{JsonRpcRequest, JsonRpcResponse};
+use backend::turn_file::state_hash;
 use serde_json::{json, Value};
-use std::io::{self, BufRead, Write};
-
-#[derive(Debug, Serialize, Deserialize)]
-struct JsonRpcRequest {
-    jsonrpc: String,
-    id: Option<Value>,
-    method: String,
-    params: Option<Value>,
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinSet};
+
+/// URI of the live game state resource
+const STATE_URI: &str = "bfm://game/state";
+
+/// How often a subscribed game state is checked for changes made by the other player
+const STATE_POLL: Duration = Duration::from_secs(2);
+
+/// A JSON-RPC error with its standard code
+///
+/// Handlers return it inside `anyhow::Error`; any other error is reported as an internal
+/// error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// MCP's code for a resource URI the server does not serve
+    pub const RESOURCE_NOT_FOUND: i64 = -32002;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    fn from_anyhow(err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<RpcError>() {
+            Some(rpc) => rpc.clone(),
+            None => Self::new(Self::INTERNAL_ERROR, format!("Internal error: {}", err)),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JsonRpcResponse {
-    jsonrpc: String,
-    id: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    result: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<Value>,
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+fn missing_params() -> anyhow::Error {
+    RpcError::invalid_params("Missing params").into()
 }
 
 pub struct McpServer {
     config: Config,
-    game_client: GameClient,
+    game_client: Box<dyn GameBackend>,
     narrator: Narrator,
     tool_handler: ToolHandler,
+    resource_handler: ResourceHandler,
+    prompt_handler: PromptHandler,
+    /// Resource URIs the client has subscribed to
+    subscriptions: Mutex<HashSet<String>>,
+    /// Hash of the game state last seen by a subscribed client
+    last_state: Mutex<Option<String>>,
+    /// Held while a tool acts on the game, so actions never interleave
+    acting: tokio::sync::Mutex<()>,
 }
 
+/// Requests still being handled, by id, so they can be cancelled
+type InFlight = Arc<Mutex<HashMap<String, AbortHandle>>>;
+
 impl McpServer {
-    pub fn new(config: Config) -> Self {
-        let game_client = GameClient::new(config.game.api_url.clone());
+    pub fn new(config: Config, game_client: Box<dyn GameBackend>) -> Self {
         let narrator = Narrator::new(
             config.display.coordinate_format.clone(),
             config.display.verbosity.clone(),
@@ -45,51 +100,147 @@ impl McpServer {
             game_client,
             narrator,
             tool_handler,
+            resource_handler: ResourceHandler::new(),
+            prompt_handler: PromptHandler::new(),
+            subscriptions: Mutex::new(HashSet::new()),
+            last_state: Mutex::new(None),
+            acting: tokio::sync::Mutex::new(()),
         }
     }
 
-    pub async fn run(&self) -> Result<()> {
+    pub async fn run(self) -> Result<()> {
         eprintln!("Battle for Moscow MCP Server starting...");
-        eprintln!("API URL: {}", self.config.game.api_url);
+        eprintln!("Game backend: {}", self.config.game.backend);
+        if self.config.game.backend == "http" {
+            eprintln!("API URL: {}", self.config.game.api_url);
+        }
         eprintln!("Player side: {}", self.config.player.side);
 
-        let stdin = io::stdin();
-        let mut stdout = io::stdout();
+        self.serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+            .await
+    }
 
-        for line in stdin.lock().lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
+    /// Serve newline-delimited JSON-RPC until `input` closes
+    ///
+    /// Requests are handled concurrently, so a slow one does not hold up a `ping` or the
+    /// `notifications/cancelled` that aborts it. Tool calls that act on the game still run
+    /// one at a time, and a request reusing the id of one in flight is refused.
+    /// Notifications never get a reply.
+    pub async fn serve<R, W>(self, input: R, mut output: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let server = Arc::new(self);
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+        let writer = tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                let mut line = serde_json::to_string(&message)?;
+                line.push('\n');
+                output.write_all(line.as_bytes()).await?;
+                output.flush().await?;
             }
+            Ok::<_, anyhow::Error>(())
+        });
 
-            eprintln!("Received: {}", line);
+        let in_flight: InFlight = Arc::default();
+        let mut tasks = JoinSet::new();
+        let mut lines = input.lines();
+        let mut poll = tokio::time::interval(STATE_POLL);
 
-            match serde_json::from_str::<JsonRpcRequest>(&line) {
-                Ok(req) => {
-                    let response = self.handle_request(req).await;
-                    let response_str = serde_json::to_string(&response)?;
-                    writeln!(stdout, "{}", response_str)?;
-                    stdout.flush()?;
-                }
-                Err(e) => {
-                    eprintln!("Failed to parse request: {}", e);
-                    let error_response = JsonRpcResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: None,
-                        result: None,
-                        error: Some(json!({
-                            "code": -32700,
-                            "message": "Parse error"
-                        })),
-                    };
-                    let response_str = serde_json::to_string(&error_response)?;
-                    writeln!(stdout, "{}", response_str)?;
-                    stdout.flush()?;
-                }
+        loop {
+            tokio::select! {
+                line = lines.next_line() => match line? {
+                    Some(line) => server.dispatch(line, &tx, &in_flight, &mut tasks),
+                    None => break,
+                },
+                _ = poll.tick() => server.notify_state_change(&tx).await,
+                Some(_) = tasks.join_next(), if !tasks.is_empty() => {}
             }
         }
 
-        Ok(())
+        // Finish what was asked before the client hung up
+        while tasks.join_next().await.is_some() {}
+        drop(tx);
+        writer.await?
+    }
+
+    /// Route one line: reply to errors now, spawn requests, act on notifications
+    fn dispatch(
+        self: &Arc<Self>,
+        line: String,
+        tx: &mpsc::UnboundedSender<Value>,
+        in_flight: &InFlight,
+        tasks: &mut JoinSet<()>,
+    ) {
+        if line.trim().is_empty() {
+            return;
+        }
+        eprintln!("Received: {}", line);
+
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("Failed to parse request: {}", e);
+                let error = RpcError::new(RpcError::PARSE_ERROR, "Parse error");
+                let _ = tx.send(error_response(None, error));
+                return;
+            }
+        };
+
+        let is_notification = message.is_object() && message.get("id").is_none();
+        let req = match serde_json::from_value::<JsonRpcRequest>(message.clone()) {
+            Ok(req) if req.jsonrpc == "2.0" => req,
+            _ if is_notification => return,
+            _ => {
+                let error = RpcError::new(RpcError::INVALID_REQUEST, "Invalid Request");
+                let _ = tx.send(error_response(message.get("id").cloned(), error));
+                return;
+            }
+        };
+
+        if is_notification {
+            self.handle_notification(&req, in_flight);
+            return;
+        }
+
+        let key = req.id.as_ref().map(Value::to_string).unwrap_or_default();
+        // Held until the handle is stored, so the task cannot finish and unregister first
+        let mut registered = in_flight.lock().unwrap();
+        if registered.contains_key(&key) {
+            let message = format!("Request id {} is already in use", key);
+            let error = RpcError::new(RpcError::INVALID_REQUEST, message);
+            let _ = tx.send(error_response(req.id, error));
+            return;
+        }
+        let server = Arc::clone(self);
+        let reply = tx.clone();
+        let running = Arc::clone(in_flight);
+        let task_key = key.clone();
+        let handle = tasks.spawn(async move {
+            let calls_tool = req.method == "tools/call";
+            let response = server.handle_request(req).await;
+            running.lock().unwrap().remove(&task_key);
+            let _ = reply.send(serde_json::to_value(response).unwrap_or_default());
+            if calls_tool {
+                server.notify_state_change(&reply).await;
+            }
+        });
+        registered.insert(key, handle);
+    }
+
+    fn handle_notification(&self, req: &JsonRpcRequest, in_flight: &InFlight) {
+        eprintln!("Handling notification: {}", req.method);
+
+        if req.method == "notifications/cancelled" {
+            let params = req.params.as_ref();
+            if let Some(id) = params.and_then(|p| p.get("requestId")) {
+                if let Some(handle) = in_flight.lock().unwrap().remove(&id.to_string()) {
+                    handle.abort();
+                }
+            }
+        }
+        // Anything else, e.g. notifications/initialized, needs no action
     }
 
     async fn handle_request(&self, req: JsonRpcRequest) -> JsonRpcResponse {
@@ -97,11 +248,20 @@ impl McpServer {
 
         let result = match req.method.as_str() {
             "initialize" => self.handle_initialize(req.params.as_ref()),
+            "ping" => Ok(json!({})),
             "tools/list" => self.handle_list_tools(),
             "tools/call" => self.handle_call_tool(req.params.as_ref()).await,
             "resources/list" => self.handle_list_resources(),
+            "resources/read" => self.handle_read_resource(req.params.as_ref()).await,
+            "resources/subscribe" => self.handle_subscribe(req.params.as_ref()).await,
+            "resources/unsubscribe" => self.handle_unsubscribe(req.params.as_ref()),
             "prompts/list" => self.handle_list_prompts(),
-            _ => Err(anyhow::anyhow!("Unknown method: {}", req.method)),
+            "prompts/get" => self.handle_get_prompt(req.params.as_ref()),
+            _ => Err(RpcError::new(
+                RpcError::METHOD_NOT_FOUND,
+                format!("Unknown method: {}", req.method),
+            )
+            .into()),
         };
 
         match result {
@@ -115,10 +275,7 @@ impl McpServer {
                 jsonrpc: "2.0".to_string(),
                 id: req.id,
                 result: None,
-                error: Some(json!({
-                    "code": -32603,
-                    "message": format!("Internal error: {}", e)
-                })),
+                error: Some(error_object(RpcError::from_anyhow(&e))),
             },
         }
     }
@@ -127,9 +284,9 @@ impl McpServer {
         Ok(json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {},
-                "resources": {},
-                "prompts": {}
+                "tools": { "listChanged": false },
+                "resources": { "subscribe": true, "listChanged": false },
+                "prompts": { "listChanged": false }
             },
             "serverInfo": {
                 "name": "battle-for-moscow-mcp",
@@ -143,62 +300,404 @@ impl McpServer {
         Ok(json!({ "tools": tools }))
     }
 
+    /// Call a tool; a failed action is reported to the model as an `isError` result
     async fn handle_call_tool(&self, params: Option<&Value>) -> Result<Value> {
-        let params = params.ok_or_else(|| anyhow::anyhow!("Missing params"))?;
+        let params = params.ok_or_else(missing_params)?;
 
         let tool_name = params["name"]
             .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing tool name"))?;
+            .ok_or_else(|| RpcError::invalid_params("Missing tool name"))?;
 
         let empty_args = json!({});
         let arguments = params.get("arguments").unwrap_or(&empty_args);
 
         eprintln!("Calling tool: {} with args: {}", tool_name, arguments);
+        let _acting = match self.tool_handler.changes_game(tool_name) {
+            true => Some(self.acting.lock().await),
+            false => None,
+        };
 
         let result = self
             .tool_handler
-            .call_tool(tool_name, arguments, &self.game_client, &self.narrator)
-            .await?;
+            .call_tool(
+                tool_name,
+                arguments,
+                self.game_client.as_ref(),
+                &self.narrator,
+            )
+            .await;
 
+        let (text, is_error) = match result {
+            Ok(text) => (text, false),
+            Err(e) if e.is::<RpcError>() => return Err(e),
+            Err(e) => (e.to_string(), true),
+        };
         Ok(json!({
             "content": [{
                 "type": "text",
-                "text": result
-            }]
+                "text": text
+            }],
+            "isError": is_error
         }))
     }
 
     fn handle_list_resources(&self) -> Result<Value> {
-        Ok(json!({
-            "resources": [
-                {
-                    "uri": "bfm://rules/full",
-                    "name": "Complete Rules",
-                    "description": "Full game rules reference"
-                },
-                {
-                    "uri": "bfm://rules/crt",
-                    "name": "Combat Results Table",
-                    "description": "Combat outcomes by odds and die roll"
-                }
-            ]
-        }))
+        let resources = self.resource_handler.list_resources();
+        Ok(json!({ "resources": resources }))
+    }
+
+    async fn handle_read_resource(&self, params: Option<&Value>) -> Result<Value> {
+        let params = params.ok_or_else(missing_params)?;
+        let uri = params["uri"]
+            .as_str()
+            .ok_or_else(|| RpcError::invalid_params("Missing resource uri"))?;
+
+        let contents = self
+            .resource_handler
+            .read_resource(uri, self.game_client.as_ref())
+            .await?;
+        Ok(json!({ "contents": contents }))
+    }
+
+    async fn handle_subscribe(&self, params: Option<&Value>) -> Result<Value> {
+        let uri = self.subscription_uri(params)?;
+        if uri == STATE_URI {
+            let state = self.game_client.get_state().await?;
+            *self.last_state.lock().unwrap() = Some(state_hash(&state));
+        }
+        self.subscriptions.lock().unwrap().insert(uri);
+        Ok(json!({}))
+    }
+
+    fn handle_unsubscribe(&self, params: Option<&Value>) -> Result<Value> {
+        let uri = self.subscription_uri(params)?;
+        self.subscriptions.lock().unwrap().remove(&uri);
+        Ok(json!({}))
+    }
+
+    /// The listed resource named in a subscribe or unsubscribe request
+    fn subscription_uri(&self, params: Option<&Value>) -> Result<String> {
+        let params = params.ok_or_else(missing_params)?;
+        let uri = params["uri"]
+            .as_str()
+            .ok_or_else(|| RpcError::invalid_params("Missing resource uri"))?;
+        let listed = self.resource_handler.list_resources();
+        if !listed.iter().any(|resource| resource["uri"] == uri) {
+            return Err(RpcError::new(
+                RpcError::RESOURCE_NOT_FOUND,
+                format!("Unknown resource: {}", uri),
+            )
+            .into());
+        }
+        Ok(uri.to_string())
+    }
+
+    /// Tell a subscribed client that the game state has changed since it last looked
+    ///
+    /// Only the state resource changes; the rules and the map are fixed for a session.
+    async fn notify_state_change(&self, tx: &mpsc::UnboundedSender<Value>) {
+        if !self.subscriptions.lock().unwrap().contains(STATE_URI) {
+            return;
+        }
+        let Ok(state) = self.game_client.get_state().await else {
+            return;
+        };
+
+        let hash = state_hash(&state);
+        let changed = {
+            let mut last = self.last_state.lock().unwrap();
+            let changed = last.as_deref() != Some(hash.as_str());
+            *last = Some(hash);
+            changed
+        };
+        if changed {
+            let _ = tx.send(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": STATE_URI }
+            }));
+        }
     }
 
     fn handle_list_prompts(&self) -> Result<Value> {
-        Ok(json!({
-            "prompts": [
-                {
-                    "name": "play_german",
-                    "description": "Play as German commander",
-                    "arguments": []
-                },
-                {
-                    "name": "play_soviet",
-                    "description": "Play as Soviet commander",
-                    "arguments": []
-                }
-            ]
-        }))
+        let prompts = self.prompt_handler.list_prompts();
+        Ok(json!({ "prompts": prompts }))
+    }
+
+    fn handle_get_prompt(&self, params: Option<&Value>) -> Result<Value> {
+        let params = params.ok_or_else(missing_params)?;
+        let name = params["name"]
+            .as_str()
+            .ok_or_else(|| RpcError::invalid_params("Missing prompt name"))?;
+        self.prompt_handler.get_prompt(name)
+    }
+}
+
+fn error_object(error: RpcError) -> Value {
+    json!({
+        "code": error.code,
+        "message": error.message
+    })
+}
+
+fn error_response(id: Option<Value>, error: RpcError) -> Value {
+    let response = JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(error_object(error)),
+    };
+    serde_json::to_value(response).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::client::GameClient;
+    use crate::game::embedded::EmbeddedEngine;
+    use tokio::io::{DuplexStream, Lines, ReadHalf, WriteHalf};
+
+    /// A client driving a server over an in-memory pipe, one JSON message per line
+    struct Session {
+        input: WriteHalf<DuplexStream>,
+        output: Lines<BufReader<ReadHalf<DuplexStream>>>,
+        server: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl Session {
+        fn start(game_client: Box<dyn GameBackend>) -> Self {
+            let (client, server) = tokio::io::duplex(1 << 16);
+            let (server_read, server_write) = tokio::io::split(server);
+            let (client_read, client_write) = tokio::io::split(client);
+            let mcp = McpServer::new(Config::default(), game_client);
+            Session {
+                input: client_write,
+                output: BufReader::new(client_read).lines(),
+                server: tokio::spawn(mcp.serve(BufReader::new(server_read), server_write)),
+            }
+        }
+
+        fn embedded() -> Self {
+            Self::start(Box::new(EmbeddedEngine::bundled().unwrap()))
+        }
+
+        async fn send(&mut self, line: &str) {
+            self.input.write_all(line.as_bytes()).await.unwrap();
+            self.input.write_all(b"\n").await.unwrap();
+        }
+
+        async fn request(&mut self, id: i64, method: &str, params: Value) {
+            let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+            self.send(&message.to_string()).await;
+        }
+
+        async fn notify(&mut self, method: &str, params: Value) {
+            let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+            self.send(&message.to_string()).await;
+        }
+
+        async fn receive(&mut self) -> Value {
+            let line = tokio::time::timeout(Duration::from_secs(5), self.output.next_line())
+                .await
+                .expect("Timed out waiting for the server")
+                .unwrap()
+                .expect("Server closed its output");
+            serde_json::from_str(&line).unwrap()
+        }
+
+        async fn call(&mut self, id: i64, method: &str, params: Value) -> Value {
+            self.request(id, method, params).await;
+            let reply = self.receive().await;
+            assert_eq!(reply["id"], id);
+            reply
+        }
+
+        /// Hang up and collect whatever the server still had to say
+        async fn close(mut self) -> Vec<Value> {
+            self.input.shutdown().await.unwrap();
+            drop(self.input);
+            self.server.await.unwrap().unwrap();
+            let mut rest = Vec::new();
+            while let Some(line) = self.output.next_line().await.unwrap() {
+                rest.push(serde_json::from_str(&line).unwrap());
+            }
+            rest
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_ping() {
+        let mut session = Session::embedded();
+        let reply = session.call(1, "initialize", json!({})).await;
+        assert_eq!(reply["jsonrpc"], "2.0");
+        assert_eq!(
+            reply["result"]["capabilities"]["resources"]["subscribe"],
+            true
+        );
+
+        // Notifications are never answered, known or not
+        session.notify("notifications/initialized", json!({})).await;
+        session.notify("notifications/unheard_of", json!({})).await;
+        let reply = session.call(2, "ping", json!({})).await;
+        assert_eq!(reply["result"], json!({}));
+        assert!(session.close().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_error_codes() {
+        let mut session = Session::embedded();
+        session.send("{not json").await;
+        let reply = session.receive().await;
+        assert_eq!(reply["error"]["code"], RpcError::PARSE_ERROR);
+        assert_eq!(reply["id"], Value::Null);
+
+        session
+            .send(r#"{"jsonrpc": "1.0", "id": 1, "method": "ping"}"#)
+            .await;
+        assert_eq!(
+            session.receive().await["error"]["code"],
+            RpcError::INVALID_REQUEST
+        );
+
+        let reply = session.call(2, "tools/frobnicate", json!({})).await;
+        assert_eq!(reply["error"]["code"], RpcError::METHOD_NOT_FOUND);
+        let reply = session.call(3, "tools/call", json!({"name": "fly"})).await;
+        assert_eq!(reply["error"]["code"], RpcError::INVALID_PARAMS);
+        let reply = session.call(4, "prompts/get", json!({})).await;
+        assert_eq!(reply["error"]["code"], RpcError::INVALID_PARAMS);
+        let uri = json!({"uri": "bfm://nowhere"});
+        let reply = session.call(5, "resources/read", uri).await;
+        assert_eq!(reply["error"]["code"], RpcError::RESOURCE_NOT_FOUND);
+        session.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_tool_errors_are_results() {
+        let mut session = Session::embedded();
+        let args = json!({"name": "move_unit", "arguments": {"unit_id": "nobody", "to": [0, 0]}});
+        let reply = session.call(1, "tools/call", args).await;
+        assert!(reply.get("error").is_none());
+        assert_eq!(reply["result"]["isError"], true);
+
+        let args = json!({"name": "get_rules", "arguments": {"topic": "zoc"}});
+        let reply = session.call(2, "tools/call", args).await;
+        assert_eq!(reply["result"]["isError"], false);
+        session.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_state_subscription() {
+        let mut session = Session::embedded();
+        let uri = json!({"uri": STATE_URI});
+        let reply = session.call(1, "resources/subscribe", uri.clone()).await;
+        assert_eq!(reply["result"], json!({}));
+
+        // Reading leaves the state alone; ending the phase changes it
+        let args = json!({"name": "get_situation"});
+        session.call(2, "tools/call", args).await;
+        let args = json!({"name": "end_phase"});
+        session.call(3, "tools/call", args).await;
+        let update = session.receive().await;
+        assert_eq!(update["method"], "notifications/resources/updated");
+        assert_eq!(update["params"]["uri"], STATE_URI);
+        assert!(update.get("id").is_none());
+
+        session.call(4, "resources/unsubscribe", uri).await;
+        let args = json!({"name": "end_phase"});
+        session.call(5, "tools/call", args).await;
+        assert!(session.close().await.is_empty());
+    }
+
+    /// A game server that accepts connections and never answers, reporting each one
+    async fn stuck_server() -> (GameClient, mpsc::UnboundedReceiver<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, connections) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+                let _ = tx.send(());
+            }
+        });
+        (GameClient::new(format!("http://{}/api", addr)), connections)
+    }
+
+    /// Wait briefly for the stuck server to be asked something
+    async fn connected(connections: &mut mpsc::UnboundedReceiver<()>) -> bool {
+        tokio::time::timeout(Duration::from_millis(500), connections.recv())
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_slow_request() {
+        let (client, _) = stuck_server().await;
+        let mut session = Session::start(Box::new(client));
+
+        session
+            .request(1, "tools/call", json!({"name": "get_situation"}))
+            .await;
+        // The stuck call does not hold up other requests
+        let reply = session.call(2, "ping", json!({})).await;
+        assert_eq!(reply["result"], json!({}));
+
+        session
+            .notify("notifications/cancelled", json!({"requestId": 1}))
+            .await;
+        // A cancelled request is never answered
+        assert!(session.close().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_actions_run_one_at_a_time() {
+        let (client, mut connections) = stuck_server().await;
+        let mut session = Session::start(Box::new(client));
+
+        session
+            .request(1, "tools/call", json!({"name": "end_phase"}))
+            .await;
+        assert!(connected(&mut connections).await);
+        // A second action waits for the stuck one, a query does not
+        session
+            .request(2, "tools/call", json!({"name": "end_phase"}))
+            .await;
+        assert!(!connected(&mut connections).await);
+        session
+            .request(3, "tools/call", json!({"name": "get_situation"}))
+            .await;
+        assert!(connected(&mut connections).await);
+
+        // Cancelling the stuck action lets the next one go
+        session
+            .notify("notifications/cancelled", json!({"requestId": 1}))
+            .await;
+        assert!(connected(&mut connections).await);
+        for id in [2, 3] {
+            session
+                .notify("notifications/cancelled", json!({"requestId": id}))
+                .await;
+        }
+        assert!(session.close().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_ids_are_refused() {
+        let (client, _) = stuck_server().await;
+        let mut session = Session::start(Box::new(client));
+
+        session
+            .request(1, "tools/call", json!({"name": "get_situation"}))
+            .await;
+        let reply = session.call(1, "ping", json!({})).await;
+        assert_eq!(reply["error"]["code"], RpcError::INVALID_REQUEST);
+
+        // The id is free again once its request is over
+        session
+            .notify("notifications/cancelled", json!({"requestId": 1}))
+            .await;
+        let reply = session.call(1, "ping", json!({})).await;
+        assert_eq!(reply["result"], json!({}));
+        assert!(session.close().await.is_empty());
     }
 }
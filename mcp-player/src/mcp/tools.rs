@@ -1,5 +1,14 @@
-use crate::game::{GameClient, Narrator};
+use crate::game::client::rule_error;
+use crate::game::{GameBackend, Narrator};
+use crate::mcp::server::RpcError;
 use anyhow::Result;
+use backend::combat::find_valid_attacks;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::movement::{check_can_move, find_move_options, movement_allowance};
+use backend::replacement::{replacement_options, replacements_remaining};
+use backend::unit::{Side, Units};
 use serde_json::{json, Value};
 
 pub struct ToolHandler;
@@ -9,6 +18,18 @@ impl ToolHandler {
         Self
     }
 
+    /// Whether a tool acts on the game rather than only looking at it
+    pub fn changes_game(&self, name: &str) -> bool {
+        matches!(
+            name,
+            "move_unit"
+                | "declare_attacks"
+                | "resolve_next_battle"
+                | "use_replacement"
+                | "end_phase"
+        )
+    }
+
     pub fn list_tools(&self) -> Vec<Value> {
         vec![
             // State Query Tools
@@ -38,7 +59,7 @@ impl ToolHandler {
             }),
             json!({
                 "name": "get_threats",
-                "description": "Analyze dangerous situations and vulnerabilities",
+                "description": "Find enemy attacks, encirclements, weakly held cities and gaps in your line to watch for next phase",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
@@ -94,6 +115,46 @@ impl ToolHandler {
                     "required": []
                 }
             }),
+            json!({
+                "name": "suggest_attacks",
+                "description": "Rank the best ways to split your units among the possible attacks",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "How many plans to show (default 5)"
+                        }
+                    },
+                    "required": []
+                }
+            }),
+            json!({
+                "name": "evaluate_position",
+                "description": "Analysis mode: who is winning, each side's chance of victory and what the score is made of",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "playouts": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 100,
+                            "description": "Random games to play out for the chances (default 0, reading them from the score)"
+                        }
+                    },
+                    "required": []
+                }
+            }),
+            json!({
+                "name": "get_valid_replacements",
+                "description": "List units the current player can restore or bring back",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            }),
             // Action Tools
             json!({
                 "name": "move_unit",
@@ -106,11 +167,16 @@ impl ToolHandler {
                             "description": "ID of the unit to move"
                         },
                         "to": {
-                            "type": "array",
-                            "items": {"type": "integer"},
-                            "minItems": 2,
-                            "maxItems": 2,
-                            "description": "Destination hex coordinates [q, r]"
+                            "oneOf": [
+                                {
+                                    "type": "array",
+                                    "items": {"type": "integer"},
+                                    "minItems": 2,
+                                    "maxItems": 2
+                                },
+                                {"type": "string", "pattern": "^[0-9]{4}$"}
+                            ],
+                            "description": "Destination hex as axial [q, r] or printed map number \"XXYY\""
                         }
                     },
                     "required": ["unit_id", "to"]
@@ -150,29 +216,6 @@ impl ToolHandler {
                     "required": []
                 }
             }),
-            json!({
-                "name": "advance_unit",
-                "description": "Advance a unit into a vacated hex after combat",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "unit_id": {
-                            "type": "string",
-                            "description": "ID of the unit to advance"
-                        }
-                    },
-                    "required": ["unit_id"]
-                }
-            }),
-            json!({
-                "name": "skip_advance",
-                "description": "Decline to advance after combat",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
-            }),
             json!({
                 "name": "use_replacement",
                 "description": "Use a replacement to restore or place a unit",
@@ -184,11 +227,16 @@ impl ToolHandler {
                             "description": "ID of the unit to restore or place"
                         },
                         "hex": {
-                            "type": "array",
-                            "items": {"type": "integer"},
-                            "minItems": 2,
-                            "maxItems": 2,
-                            "description": "Where to place if bringing back eliminated unit [q, r]"
+                            "oneOf": [
+                                {
+                                    "type": "array",
+                                    "items": {"type": "integer"},
+                                    "minItems": 2,
+                                    "maxItems": 2
+                                },
+                                {"type": "string", "pattern": "^[0-9]{4}$"}
+                            ],
+                            "description": "Where to place if bringing back eliminated unit, as [q, r] or \"XXYY\""
                         }
                     },
                     "required": ["unit_id"]
@@ -226,18 +274,18 @@ impl ToolHandler {
         &self,
         name: &str,
         arguments: &Value,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
     ) -> Result<String> {
         match name {
             // State Query Tools
             "get_situation" => self.get_situation(client, narrator).await,
             "get_units" => {
-                let side = arguments["side"].as_str();
+                let side = side_argument(arguments)?;
                 self.get_units(client, narrator, side).await
             }
             "get_threats" => {
-                let side = arguments["side"].as_str().unwrap_or("soviet");
+                let side = side_argument(arguments)?;
                 self.get_threats(client, narrator, side).await
             }
 
@@ -259,19 +307,26 @@ impl ToolHandler {
                     .await
             }
             "get_valid_attacks" => self.get_valid_attacks(client, narrator).await,
+            "suggest_attacks" => {
+                let limit = arguments["limit"].as_u64().map(|limit| limit as usize);
+                self.suggest_attacks(client, narrator, limit).await
+            }
+            "evaluate_position" => {
+                let playouts = arguments["playouts"].as_u64().map(|n| n as usize);
+                self.evaluate_position(client, narrator, playouts).await
+            }
+            "get_valid_replacements" => self.get_valid_replacements(client, narrator).await,
 
             // Action Tools
             "move_unit" => {
                 let unit_id = arguments["unit_id"]
                     .as_str()
                     .ok_or_else(|| anyhow::anyhow!("Missing unit_id"))?;
-                let to = arguments["to"]
-                    .as_array()
+                let to = arguments
+                    .get("to")
                     .ok_or_else(|| anyhow::anyhow!("Missing to coordinates"))?;
-                let q = to[0].as_i64().ok_or_else(|| anyhow::anyhow!("Invalid q"))?;
-                let r = to[1].as_i64().ok_or_else(|| anyhow::anyhow!("Invalid r"))?;
-                self.move_unit(client, narrator, unit_id, (q as i32, r as i32))
-                    .await
+                let to = parse_hex_arg(to)?;
+                self.move_unit(client, narrator, unit_id, to).await
             }
             "declare_attacks" => {
                 let battles = arguments["battles"]
@@ -280,27 +335,13 @@ impl ToolHandler {
                 self.declare_attacks(client, narrator, battles).await
             }
             "resolve_next_battle" => self.resolve_next_battle(client, narrator).await,
-            "advance_unit" => {
-                let unit_id = arguments["unit_id"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing unit_id"))?;
-                self.advance_unit(client, narrator, unit_id).await
-            }
-            "skip_advance" => self.skip_advance(client, narrator).await,
             "use_replacement" => {
                 let unit_id = arguments["unit_id"]
                     .as_str()
                     .ok_or_else(|| anyhow::anyhow!("Missing unit_id"))?;
-                let hex = if let Some(hex_array) = arguments["hex"].as_array() {
-                    let q = hex_array[0]
-                        .as_i64()
-                        .ok_or_else(|| anyhow::anyhow!("Invalid q"))?;
-                    let r = hex_array[1]
-                        .as_i64()
-                        .ok_or_else(|| anyhow::anyhow!("Invalid r"))?;
-                    Some((q as i32, r as i32))
-                } else {
-                    None
+                let hex = match arguments.get("hex") {
+                    Some(hex) if !hex.is_null() => Some(parse_hex_arg(hex)?),
+                    _ => None,
                 };
                 self.use_replacement(client, narrator, unit_id, hex).await
             }
@@ -314,128 +355,384 @@ impl ToolHandler {
                 Ok(narrator.narrate_rules(topic))
             }
 
-            _ => Err(anyhow::anyhow!("Unknown tool: {}", name)),
+            _ => Err(RpcError::invalid_params(format!("Unknown tool: {}", name)).into()),
         }
     }
 
     // State Query Tool Implementations
-    async fn get_situation(&self, client: &GameClient, narrator: &Narrator) -> Result<String> {
-        let state = client.get_state().await?;
-        Ok(narrator.narrate_situation(&state))
+    async fn get_situation(&self, client: &dyn GameBackend, narrator: &Narrator) -> Result<String> {
+        let (state, units, map) = rules_context(client).await?;
+        Ok(narrator.narrate_situation(&state, &units, &map))
     }
 
     async fn get_units(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
-        side: Option<&str>,
+        side: Option<Side>,
     ) -> Result<String> {
-        let state = client.get_state().await?;
-        Ok(narrator.narrate_units(&state, side))
+        let (state, units, map) = rules_context(client).await?;
+        Ok(narrator.narrate_units(&state, &units, &map, side))
     }
 
     async fn get_threats(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
-        side: &str,
+        side: Option<Side>,
     ) -> Result<String> {
-        let state = client.get_state().await?;
-        Ok(narrator.narrate_threats(&state, side))
+        let report = client.threats(side).await?;
+        Ok(narrator.narrate_threats(&report))
     }
 
     // Action Query Tool Implementations
     async fn get_valid_moves(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
         unit_id: &str,
     ) -> Result<String> {
-        // TODO: Call backend API to get valid moves
-        let moves = json!([]);
-        Ok(narrator.narrate_valid_moves(unit_id, &moves))
+        let (state, units, map) = rules_context(client).await?;
+        check_can_move(unit_id, &state, &units, &map).map_err(rule_error)?;
+        let options = find_move_options(unit_id, &state, &units, &map).map_err(rule_error)?;
+
+        let from = state
+            .get_unit(unit_id)
+            .and_then(|unit| unit.hex())
+            .ok_or_else(|| anyhow::anyhow!("{} is not on the map", unit_id))?;
+        let allowance = units
+            .get(unit_id)
+            .map_or(0, |def| movement_allowance(def, &state));
+        Ok(narrator.narrate_valid_moves(unit_id, from, allowance, &options, &map, state.is_mud()))
     }
 
     async fn preview_attack(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
         attackers: &[Value],
         defender: &str,
     ) -> Result<String> {
-        // TODO: Call backend API to preview attack
-        let preview = json!({
-            "attack_strength": 0,
-            "defense_strength": 0,
-            "odds": "1:1"
-        });
+        let attackers = attackers
+            .iter()
+            .map(|id| {
+                id.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Attacker IDs must be strings"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let preview = client.preview_battle(attackers, defender.to_string()).await?;
         Ok(narrator.narrate_attack_preview(&preview))
     }
 
-    async fn get_valid_attacks(&self, client: &GameClient, narrator: &Narrator) -> Result<String> {
-        // TODO: Call backend API to get valid attacks
-        Ok("POSSIBLE ATTACKS THIS PHASE:\n\nAnalyzing...".to_string())
+    async fn get_valid_attacks(
+        &self,
+        client: &dyn GameBackend,
+        narrator: &Narrator,
+    ) -> Result<String> {
+        let (state, units, map) = rules_context(client).await?;
+        let options = find_valid_attacks(&state, &units, &map).map_err(rule_error)?;
+        Ok(narrator.narrate_valid_attacks(&options))
     }
 
-    // Action Tool Implementations
-    async fn move_unit(
+    async fn suggest_attacks(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
-        unit_id: &str,
-        to: (i32, i32),
+        limit: Option<usize>,
     ) -> Result<String> {
-        let result = client.move_unit(unit_id, to).await?;
-        Ok(format!("MOVED: {} to [{},{}]\n", unit_id, to.0, to.1))
+        let plans = client.attack_plans(limit).await?;
+        Ok(narrator.narrate_attack_plans(&plans))
     }
 
-    async fn declare_attacks(
+    async fn evaluate_position(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
-        battles: &[Value],
+        playouts: Option<usize>,
     ) -> Result<String> {
-        let result = client.declare_attacks(battles.to_vec()).await?;
-        Ok(format!("ATTACKS DECLARED:\n\n{} battles ready to resolve.\n", battles.len()))
+        let assessment = client.evaluate(playouts).await?;
+        Ok(narrator.narrate_evaluation(&assessment))
     }
 
-    async fn resolve_next_battle(
+    async fn get_valid_replacements(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
     ) -> Result<String> {
-        let result = client.resolve_next_battle().await?;
-        Ok(narrator.narrate_combat_result(&result))
+        let (state, units, map) = rules_context(client).await?;
+        let side = state.active_player();
+        let options = replacement_options(side, &state, &units, &map);
+        let mut output = narrator.narrate_replacements(
+            side,
+            replacements_remaining(side, &state),
+            &options,
+            &units,
+        );
+        if !state.phase.is_replacement_phase() {
+            output.push_str(&format!(
+                "\nNote: it is the {} phase; replacements can only be used in a replacement phase.\n",
+                state.phase.display_name()
+            ));
+        }
+        Ok(output)
     }
 
-    async fn advance_unit(
+    // Action Tool Implementations
+    async fn move_unit(
         &self,
-        client: &GameClient,
+        client: &dyn GameBackend,
         narrator: &Narrator,
         unit_id: &str,
+        to: (i32, i32),
+    ) -> Result<String> {
+        client.move_unit(unit_id, to).await?;
+        Ok(format!(
+            "MOVED: {} to {}\n",
+            unit_id,
+            narrator.format_hex(to.0 as i64, to.1 as i64)
+        ))
+    }
+
+    async fn declare_attacks(
+        &self,
+        client: &dyn GameBackend,
+        _narrator: &Narrator,
+        battles: &[Value],
     ) -> Result<String> {
-        let result = client.advance_unit(unit_id).await?;
-        Ok(format!("ADVANCED: {}\n", unit_id))
+        for battle in battles {
+            let attackers = battle["attackers"]
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("Battle is missing attackers"))?
+                .iter()
+                .map(|id| {
+                    id.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| anyhow::anyhow!("Attacker IDs must be strings"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let defender = battle["defender"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Battle is missing defender"))?;
+            client.declare_battle(attackers, defender.to_string()).await?;
+        }
+        Ok(format!("ATTACKS DECLARED:\n\n{} battles ready to resolve.\n", battles.len()))
     }
 
-    async fn skip_advance(&self, client: &GameClient, narrator: &Narrator) -> Result<String> {
-        let result = client.skip_advance().await?;
-        Ok("Advance declined.\n".to_string())
+    async fn resolve_next_battle(
+        &self,
+        client: &dyn GameBackend,
+        narrator: &Narrator,
+    ) -> Result<String> {
+        let (_, resolution) = client.resolve_next_battle().await?;
+        Ok(narrator.narrate_combat_result(&resolution))
     }
 
     async fn use_replacement(
         &self,
-        client: &GameClient,
-        narrator: &Narrator,
+        client: &dyn GameBackend,
+        _narrator: &Narrator,
         unit_id: &str,
         hex: Option<(i32, i32)>,
     ) -> Result<String> {
-        let result = client.use_replacement(unit_id, hex).await?;
+        client.apply_replacement(unit_id, hex).await?;
         Ok(format!("REPLACEMENT USED: {}\n", unit_id))
     }
 
-    async fn end_phase(&self, client: &GameClient, narrator: &Narrator) -> Result<String> {
-        let result = client.end_phase().await?;
+    async fn end_phase(&self, client: &dyn GameBackend, _narrator: &Narrator) -> Result<String> {
+        client.advance_phase().await?;
         Ok("PHASE COMPLETE\n\nAdvanced to next phase.\n".to_string())
     }
 }
+
+/// The state, roster and map the rules engine needs for analysis
+async fn rules_context(client: &dyn GameBackend) -> Result<(GameState, Units, Map)> {
+    Ok((client.get_state().await?, client.get_units().await?, client.get_map().await?))
+}
+
+/// The optional `side` argument; absent means both sides, or the side to move
+fn side_argument(arguments: &Value) -> Result<Option<Side>> {
+    match arguments["side"].as_str() {
+        Some("german") => Ok(Some(Side::German)),
+        Some("soviet") => Ok(Some(Side::Soviet)),
+        Some(other) => Err(anyhow::anyhow!("Unknown side: {}", other)),
+        None => Ok(None),
+    }
+}
+
+/// Read a hex argument given as [q, r] or as a printed map number such as "0505"
+fn parse_hex_arg(value: &Value) -> Result<(i32, i32)> {
+    if let Some(number) = value.as_str() {
+        let hex = Hex::from_original(number)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hex number: {}", number))?;
+        return Ok((hex.q, hex.r));
+    }
+
+    let coords = value
+        .as_array()
+        .filter(|coords| coords.len() == 2)
+        .ok_or_else(|| anyhow::anyhow!("Hex must be [q, r] or a four-digit hex number"))?;
+    let q = coords[0].as_i64().ok_or_else(|| anyhow::anyhow!("Invalid q"))?;
+    let r = coords[1].as_i64().ok_or_else(|| anyhow::anyhow!("Invalid r"))?;
+    Ok((q as i32, r as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::client::EngineError;
+    use crate::game::embedded::EmbeddedEngine;
+
+    async fn call(engine: &EmbeddedEngine, name: &str, arguments: Value) -> Result<String> {
+        let narrator = Narrator::new("axial".to_string(), "normal".to_string());
+        ToolHandler::new()
+            .call_tool(name, &arguments, engine, &narrator)
+            .await
+    }
+
+    fn engine_code(err: &anyhow::Error) -> &str {
+        &err.downcast_ref::<EngineError>()
+            .expect("Expected an engine error")
+            .code
+    }
+
+    #[tokio::test]
+    async fn test_analysis_tools() {
+        // The bundled game has German infantry V at (0, 0) and Soviet army 3 at (3, 0)
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let err = call(&engine, "get_valid_moves", json!({"unit_id": "V"}))
+            .await
+            .unwrap_err();
+        assert_eq!(engine_code(&err), "panzers_only");
+        let err = call(&engine, "get_valid_attacks", json!({})).await.unwrap_err();
+        assert_eq!(engine_code(&err), "wrong_phase");
+        let err = call(&engine, "suggest_attacks", json!({})).await.unwrap_err();
+        assert_eq!(engine_code(&err), "wrong_phase");
+
+        engine.advance_phase().await.unwrap();
+        let attacks = call(&engine, "get_valid_attacks", json!({})).await.unwrap();
+        assert!(attacks.contains("No enemy units can be attacked."));
+        engine.advance_phase().await.unwrap();
+
+        let moves = call(&engine, "get_valid_moves", json!({"unit_id": "V"}))
+            .await
+            .unwrap();
+        assert!(moves.starts_with("VALID MOVES FOR V:"));
+        assert!(moves.contains("- [1,0]: Clear, 1 MP.\n"));
+        assert!(moves.contains("- [2,0]: Forest, 3 MP. Enters enemy ZOC"));
+        call(&engine, "move_unit", json!({"unit_id": "V", "to": [2, 0]}))
+            .await
+            .unwrap();
+        let err = call(&engine, "get_valid_moves", json!({"unit_id": "V"}))
+            .await
+            .unwrap_err();
+        assert_eq!(engine_code(&err), "already_moved");
+
+        engine.advance_phase().await.unwrap();
+        let replacements = call(&engine, "get_valid_replacements", json!({}))
+            .await
+            .unwrap();
+        assert!(replacements.starts_with("SOVIET REPLACEMENTS: 5 remaining this turn"));
+        assert!(!replacements.contains("Note:"));
+        engine.advance_phase().await.unwrap();
+        engine.advance_phase().await.unwrap();
+        let attacks = call(&engine, "get_valid_attacks", json!({})).await.unwrap();
+        assert!(attacks.contains("1. Target: V at [2,0]\n   Your adjacent units: 3\n"));
+        let plans = call(&engine, "suggest_attacks", json!({"limit": 3}))
+            .await
+            .unwrap();
+        assert!(plans.contains("No attack is expected to gain more than it risks."));
+        let preview = call(
+            &engine,
+            "preview_attack",
+            json!({"attackers": ["3"], "defender": "V"}),
+        )
+        .await
+        .unwrap();
+        assert!(preview.contains("Terrain: Forest, River"));
+        assert!(preview.contains("Raw odds: 1:1\nFinal odds: Below 1:1\n"));
+        assert!(preview.contains("Expected step losses: 0.00 for you, 0.00 for the defender."));
+        let err = call(
+            &engine,
+            "preview_attack",
+            json!({"attackers": ["3"], "defender": "nobody"}),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(engine_code(&err), "unit_not_found");
+
+        let replacements = call(&engine, "get_valid_replacements", json!({}))
+            .await
+            .unwrap();
+        assert!(replacements.contains("Note: it is the Soviet Combat phase"));
+    }
+
+    #[tokio::test]
+    async fn test_every_listed_tool_is_handled() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        for tool in ToolHandler::new().list_tools() {
+            let name = tool["name"].as_str().unwrap();
+            if let Err(err) = call(&engine, name, json!({})).await {
+                assert!(!err.to_string().contains("Unknown tool"), "{}", name);
+            }
+        }
+        let err = call(&engine, "advance_unit", json!({"unit_id": "V"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown tool"));
+    }
+
+    #[tokio::test]
+    async fn test_state_queries() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let situation = call(&engine, "get_situation", json!({})).await.unwrap();
+        assert!(situation
+            .starts_with("TURN 1 of 7 (2–8 October 1941) — German Panzer Movement\n\n"));
+        assert!(situation.contains("Moscow: Soviet controlled\n"));
+        assert!(situation.contains("Replacements remaining this turn: German 1, Soviet 0\n"));
+
+        let units = call(&engine, "get_units", json!({"side": "soviet"}))
+            .await
+            .unwrap();
+        assert!(units.starts_with("SOVIET UNITS:\n- 3 (infantry) at [3,0]: full strength 8, in communication\n"));
+        assert!(!units.contains("GERMAN UNITS:"));
+        assert!(call(&engine, "get_units", json!({"side": "finnish"}))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_threats() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let threats = call(&engine, "get_threats", json!({})).await.unwrap();
+        assert!(threats.starts_with("THREATS TO GERMAN POSITION:"));
+
+        // V can reach the forest next to Moscow, though not take the city
+        let threats = call(&engine, "get_threats", json!({"side": "soviet"}))
+            .await
+            .unwrap();
+        assert!(threats.starts_with("THREATS TO SOVIET POSITION:"));
+        assert!(threats.contains("- 3 at [3,0]: V could attack at Below 1:1 (6 vs 8), 0% chance"));
+        assert!(threats.contains("- Moscow approach at [2,0] (empty): 100% chance of falling to V\n"));
+        assert!(!threats.contains("- Moscow at"));
+
+        assert!(call(&engine, "get_threats", json!({"side": "finnish"}))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_position() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let scored = call(&engine, "evaluate_position", json!({})).await.unwrap();
+        assert!(scored.starts_with("POSITION ASSESSMENT:\n\nThe Germans lead by 0.11"));
+        assert!(scored.contains(", read from the score\n"));
+        assert!(scored.contains("Moscow: Soviet controlled, nearest German unit 3 hexes away\n"));
+        assert!(scored.contains("- German hold on Moscow: +0.00 (measured 0.00)\n"));
+
+        let played = call(&engine, "evaluate_position", json!({"playouts": 10}))
+            .await
+            .unwrap();
+        assert!(played.contains(", from 10 random playouts\n"));
+    }
+}
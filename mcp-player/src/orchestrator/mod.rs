@@ -0,0 +1,559 @@
+pub mod model;
+
+use crate::config::{Config, OrchestratorConfig};
+use crate::game::backend::{connect, GameBackend};
+use crate::game::Narrator;
+use crate::text::commands::{parse_command, Command, ParseError};
+use crate::text::map::{render_ascii, AsciiMapOptions};
+use crate::text::prompts::{print_phase_prompt, HELP_TEXT};
+use crate::text::repl::execute_command;
+use anyhow::{anyhow, Result};
+use backend::combat::CombatResult;
+use backend::game_state::{GameState, Phase};
+use backend::hex::Hex;
+use backend::retreat::RetreatResult;
+use backend::unit::Side;
+use model::{model_from_spec, ChatMessage, ModelBackend};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// How one side's model fared in a game
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideStats {
+    pub model: String,
+    pub calls: u32,
+    /// Replies that could not be parsed or whose command was refused
+    pub failed_replies: u32,
+    /// Phases ended and retreats made by the orchestrator after the model ran out of
+    /// retries or budget
+    pub forced_actions: u32,
+}
+
+/// How one orchestrated game went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub game: usize,
+    /// Dice seed the game was played with
+    pub seed: String,
+    pub winner: Option<Side>,
+    pub german: SideStats,
+    pub soviet: SideStats,
+}
+
+/// One exchange between the orchestrator and a model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub turn: i32,
+    pub phase: Phase,
+    pub side: Side,
+    pub prompt: String,
+    /// The model's reply, or `None` when the orchestrator acted for the side
+    pub reply: Option<String>,
+    pub response: String,
+    /// Whether the reply was understood and carried out
+    pub accepted: bool,
+}
+
+/// The exchanges of one game, written to a JSON Lines file as they happen
+pub struct Transcript {
+    file: Option<File>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// A transcript written to `path`
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        Ok(Transcript {
+            file: Some(file),
+            entries: Vec::new(),
+        })
+    }
+
+    /// A transcript kept in memory only
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Transcript {
+            file: None,
+            entries: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, entry: TranscriptEntry) -> Result<()> {
+        if let Some(ref mut file) = self.file {
+            writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// Play games between two models, one JSON result per game on stdout
+///
+/// Each player is `openai:MODEL` or `script:PATH`. With a seed, game `n` rolls its dice
+/// from `"{seed}-{n}"`.
+pub async fn run_orchestrator(
+    config: &Config,
+    german: &str,
+    soviet: &str,
+    games: usize,
+    seed: Option<&str>,
+) -> Result<Vec<GameResult>> {
+    let german_model = model_from_spec(german, &config.orchestrator)?;
+    let soviet_model = model_from_spec(soviet, &config.orchestrator)?;
+    let client = connect(config).await?;
+    let narrator = Narrator::new(
+        config.display.coordinate_format.clone(),
+        config.display.verbosity.clone(),
+    );
+    let dir = Path::new(&config.orchestrator.transcript_dir);
+    fs::create_dir_all(dir).map_err(|e| anyhow!("{}: {}", dir.display(), e))?;
+
+    let mut results = Vec::with_capacity(games);
+    for game in 0..games {
+        let mut transcript = Transcript::create(&dir.join(format!("game-{}.jsonl", game)))?;
+        let mut result = play_game(
+            client.as_ref(),
+            &narrator,
+            &config.orchestrator,
+            [german_model.as_ref(), soviet_model.as_ref()],
+            seed.map(|seed| format!("{}-{}", seed, game)),
+            &mut transcript,
+        )
+        .await?;
+        result.game = game;
+        println!("{}", serde_json::to_string(&result)?);
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Play one game, asking `models` (German first) for every command
+///
+/// The record's `game` is left for the caller to fill in.
+pub async fn play_game(
+    client: &dyn GameBackend,
+    narrator: &Narrator,
+    config: &OrchestratorConfig,
+    models: [&dyn ModelBackend; 2],
+    seed: Option<String>,
+    transcript: &mut Transcript,
+) -> Result<GameResult> {
+    client.new_game(seed).await?;
+    let mut orchestrator = Orchestrator {
+        client,
+        narrator,
+        config,
+        models,
+        stats: models.map(|model| SideStats {
+            model: model.name().to_string(),
+            ..SideStats::default()
+        }),
+        transcript,
+    };
+
+    loop {
+        let state = client.get_state().await?;
+        if state.is_over() {
+            let [german, soviet] = orchestrator.stats;
+            return Ok(GameResult {
+                game: 0,
+                seed: client.dice_audit().await?.seed,
+                winner: state.check_victory(),
+                german,
+                soviet,
+            });
+        }
+        orchestrator.play_phase(&state).await?;
+    }
+}
+
+/// Index of a side in the per-side arrays
+fn seat(side: Side) -> usize {
+    match side {
+        Side::German => 0,
+        Side::Soviet => 1,
+    }
+}
+
+/// The first line of a reply that reads as a command, ignoring code fences and quoting
+pub fn parse_reply(reply: &str) -> Result<Command, ParseError> {
+    let mut first_error = None;
+    for line in reply.lines() {
+        let line = line.trim().trim_matches('`').trim_start_matches('>').trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_command(line) {
+            Ok(command) => return Ok(command),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or(ParseError::Empty))
+}
+
+fn system_prompt(side: Side, phase_budget: u32) -> String {
+    // Starting a new game or exchanging turn files is the orchestrator's business
+    let commands = HELP_TEXT.split("\nGAME:").next().unwrap_or(HELP_TEXT);
+    format!(
+        "You are playing the {:?} side of Battle for Moscow, the wargame of the 1941 German \
+         drive on Moscow, against another player.\n\
+         Answer every message with exactly one command from the list below and nothing else. \
+         Use the information commands and queries to study the position before acting, and \
+         END to finish a phase. After {} commands in one phase the phase is ended for you.\n\n{}",
+        side, phase_budget, commands
+    )
+}
+
+struct Orchestrator<'a> {
+    client: &'a dyn GameBackend,
+    narrator: &'a Narrator,
+    config: &'a OrchestratorConfig,
+    models: [&'a dyn ModelBackend; 2],
+    stats: [SideStats; 2],
+    transcript: &'a mut Transcript,
+}
+
+impl Orchestrator<'_> {
+    /// Let the active side give commands until its phase ends
+    async fn play_phase(&mut self, state: &GameState) -> Result<()> {
+        let side = state.active_player();
+        let start = (state.turn, state.phase.clone());
+        let mut messages = vec![
+            ChatMessage::system(system_prompt(side, self.config.phase_budget)),
+            ChatMessage::user(self.briefing(state).await?),
+        ];
+        let (mut commands, mut failures) = (0, 0);
+
+        loop {
+            let reason = if commands >= self.config.phase_budget {
+                Some("the phase's command budget is used up")
+            } else if failures > self.config.retries {
+                Some("too many failed replies")
+            } else if self.stats[seat(side)].calls >= self.config.game_budget {
+                Some("the game's model budget is used up")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                self.client.advance_phase().await?;
+                return self.forced(state, side, &messages, format!("Phase ended: {}", reason));
+            }
+
+            let prompt = messages
+                .last()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            let Some(reply) = self.ask(side, &messages, &prompt, state).await? else {
+                failures += 1;
+                continue;
+            };
+            messages.push(ChatMessage::assistant(&reply));
+
+            let result = match parse_reply(&reply) {
+                Ok(command) => {
+                    commands += 1;
+                    self.execute(side, command).await
+                }
+                Err(e) => Err(anyhow!(
+                    "Not a command: {}. Reply with one command only.",
+                    e
+                )),
+            };
+            let (response, accepted) = match result {
+                Ok(text) => (text, true),
+                Err(e) => (format!("Error: {}", e), false),
+            };
+            if accepted {
+                failures = 0;
+            } else {
+                failures += 1;
+                self.stats[seat(side)].failed_replies += 1;
+            }
+            self.record(state, side, prompt, Some(reply), &response, accepted)?;
+
+            let now = self.client.get_state().await?;
+            if now.is_over() || (now.turn, now.phase.clone()) != start {
+                return Ok(());
+            }
+            messages.push(ChatMessage::user(response));
+        }
+    }
+
+    /// What a side is told at the start of its phase
+    async fn briefing(&self, state: &GameState) -> Result<String> {
+        let options = AsciiMapOptions {
+            labels: self.narrator.coordinate_labels(),
+            zoom: None,
+            overlay: None,
+        };
+        let map = self.client.get_map().await?;
+        let units = self.client.get_units().await?;
+        Ok(format!(
+            "{}\n{}\n{}",
+            print_phase_prompt(state, self.narrator),
+            self.narrator.narrate_situation(state, &units, &map),
+            render_ascii(&map, &units, state, &options)
+        ))
+    }
+
+    /// Ask a side's model, counting the call; a failed request is recorded and gives `None`
+    async fn ask(
+        &mut self,
+        side: Side,
+        messages: &[ChatMessage],
+        prompt: &str,
+        state: &GameState,
+    ) -> Result<Option<String>> {
+        self.stats[seat(side)].calls += 1;
+        match self.models[seat(side)].complete(messages).await {
+            Ok(reply) => Ok(Some(reply)),
+            Err(e) => {
+                self.stats[seat(side)].failed_replies += 1;
+                let response = format!("Model error: {}", e);
+                self.record(state, side, prompt.to_string(), None, &response, false)?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn execute(&mut self, side: Side, command: Command) -> Result<String> {
+        match command {
+            Command::New
+            | Command::Quit
+            | Command::ExportTurn { .. }
+            | Command::ImportTurn { .. }
+            | Command::VerifyTurn { .. } => Err(anyhow!("That command is not available")),
+            Command::Retreat { .. } => Err(anyhow!(
+                "The defending side chooses its retreat when the battle is resolved"
+            )),
+            Command::Resolve => self.resolve(side).await,
+            command => Ok(execute_command(self.client, self.narrator, command)
+                .await?
+                .text),
+        }
+    }
+
+    /// Resolve the next battle, letting the defender choose where to retreat
+    async fn resolve(&mut self, side: Side) -> Result<String> {
+        let (index, resolution) = self.client.resolve_next_battle().await?;
+        let mut text = self.narrator.narrate_combat_result(&resolution);
+        if matches!(resolution.result, CombatResult::DR | CombatResult::DRL) {
+            let state = self.client.get_state().await?;
+            let defender = state.pending_battles[index].defender.clone();
+            if let Some(from) = state.get_unit(&defender).and_then(|unit| unit.hex()) {
+                let opponent = match side {
+                    Side::German => Side::Soviet,
+                    Side::Soviet => Side::German,
+                };
+                text.push('\n');
+                text.push_str(&self.retreat(opponent, &defender, from, &state).await?);
+            }
+        }
+        Ok(text)
+    }
+
+    /// Ask a side where its beaten unit retreats, falling back to the first open hex
+    async fn retreat(
+        &mut self,
+        side: Side,
+        unit_id: &str,
+        from: Hex,
+        state: &GameState,
+    ) -> Result<String> {
+        let hexes = self.client.retreat_hexes(unit_id).await?;
+        let Some(&fallback) = hexes.first() else {
+            return Ok(format!("{} has nowhere to retreat to.", unit_id));
+        };
+        let options: Vec<String> = hexes.iter().map(|hex| self.hex(hex)).collect();
+        let mut messages = vec![
+            ChatMessage::system(system_prompt(side, self.config.phase_budget)),
+            ChatMessage::user(format!(
+                "Your unit {} lost a battle and must retreat from {}. It can retreat to: {}\n\
+                 Reply RETREAT {} <hex>.",
+                unit_id,
+                self.hex(&from),
+                options.join(" "),
+                unit_id
+            )),
+        ];
+
+        for _ in 0..=self.config.retries {
+            if self.stats[seat(side)].calls >= self.config.game_budget {
+                break;
+            }
+            let prompt = messages
+                .last()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            let Some(reply) = self.ask(side, &messages, &prompt, state).await? else {
+                continue;
+            };
+            messages.push(ChatMessage::assistant(&reply));
+
+            let result = match parse_reply(&reply) {
+                Ok(Command::Retreat { unit_id: id, to }) if id == unit_id => self
+                    .client
+                    .retreat(unit_id, to)
+                    .await
+                    .map(|retreat| self.describe_retreat(unit_id, to, &retreat)),
+                Ok(_) => Err(anyhow!("Only RETREAT {} <hex> is possible now", unit_id)),
+                Err(e) => Err(anyhow!("Not a command: {}", e)),
+            };
+            match result {
+                Ok(text) => {
+                    self.record(state, side, prompt, Some(reply), &text, true)?;
+                    return Ok(text);
+                }
+                Err(e) => {
+                    let response = format!("Error: {}", e);
+                    self.stats[seat(side)].failed_replies += 1;
+                    self.record(state, side, prompt, Some(reply), &response, false)?;
+                    messages.push(ChatMessage::user(response));
+                }
+            }
+        }
+
+        let to = (fallback.q, fallback.r);
+        let retreat = self.client.retreat(unit_id, to).await?;
+        let text = self.describe_retreat(unit_id, to, &retreat);
+        self.forced(state, side, &messages, text.clone())?;
+        Ok(text)
+    }
+
+    fn describe_retreat(&self, unit_id: &str, to: (i32, i32), retreat: &RetreatResult) -> String {
+        match retreat {
+            RetreatResult::Success => format!(
+                "RETREATED: {} → {}",
+                unit_id,
+                self.narrator.format_hex(to.0 as i64, to.1 as i64)
+            ),
+            RetreatResult::EliminatedInZoc => {
+                format!("{} retreated into enemy ZOC and was eliminated", unit_id)
+            }
+        }
+    }
+
+    /// Record an action the orchestrator took for a side
+    fn forced(
+        &mut self,
+        state: &GameState,
+        side: Side,
+        messages: &[ChatMessage],
+        response: String,
+    ) -> Result<()> {
+        self.stats[seat(side)].forced_actions += 1;
+        let prompt = messages
+            .last()
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        self.record(state, side, prompt, None, &response, true)
+    }
+
+    fn record(
+        &mut self,
+        state: &GameState,
+        side: Side,
+        prompt: String,
+        reply: Option<String>,
+        response: &str,
+        accepted: bool,
+    ) -> Result<()> {
+        self.transcript.record(TranscriptEntry {
+            turn: state.turn,
+            phase: state.phase.clone(),
+            side,
+            prompt,
+            reply,
+            response: response.to_string(),
+            accepted,
+        })
+    }
+
+    fn hex(&self, hex: &Hex) -> String {
+        self.narrator.format_hex(hex.q as i64, hex.r as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::embedded::EmbeddedEngine;
+    use model::ScriptedBackend;
+
+    #[test]
+    fn test_parse_reply() {
+        assert!(matches!(
+            parse_reply("```\nMOVE V 1,0\n```"),
+            Ok(Command::Move { ref unit_id, to: (1, 0) }) if unit_id == "V"
+        ));
+        assert!(matches!(
+            parse_reply("I will hold.\n> END"),
+            Ok(Command::End)
+        ));
+        assert!(matches!(
+            parse_reply("Attack!"),
+            Err(ParseError::UnknownCommand(_))
+        ));
+        assert!(matches!(parse_reply("\n  \n"), Err(ParseError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn test_scripted_game() {
+        let engine = EmbeddedEngine::bundled().unwrap();
+        let narrator = Narrator::new("axial".to_string(), "normal".to_string());
+        let config = OrchestratorConfig {
+            retries: 1,
+            phase_budget: 2,
+            ..OrchestratorConfig::default()
+        };
+        let replies = ["Let me think", "MOVE NOPE 1,0", "UNITS", "SITUATION"];
+        let german = ScriptedBackend::new("german", replies.map(str::to_string).to_vec());
+        let soviet = ScriptedBackend::new("soviet", Vec::new());
+
+        let mut transcript = Transcript::in_memory();
+        let result = play_game(
+            &engine,
+            &narrator,
+            &config,
+            [&german, &soviet],
+            Some("orchestrate".to_string()),
+            &mut transcript,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.seed, "orchestrate");
+        assert_eq!(result.winner, Some(Side::Soviet));
+        assert_eq!(result.german.model, "german");
+
+        // Two failures use up the single retry, two commands the phase budget
+        assert_eq!(result.german.failed_replies, 2);
+        assert_eq!(result.german.forced_actions, 2);
+        assert_eq!(result.soviet.failed_replies, 0);
+        assert_eq!(result.soviet.forced_actions, 0);
+
+        let first: Vec<_> = transcript.entries[..3]
+            .iter()
+            .map(|entry| (entry.phase.clone(), entry.reply.clone(), entry.accepted))
+            .collect();
+        let phase = Phase::GermanPanzerMovement;
+        assert_eq!(
+            first,
+            vec![
+                (phase.clone(), Some("Let me think".to_string()), false),
+                (phase.clone(), Some("MOVE NOPE 1,0".to_string()), false),
+                (phase, None, true),
+            ]
+        );
+        assert!(transcript.entries[3].prompt.contains("Combat"));
+        assert!(transcript.entries[1]
+            .prompt
+            .starts_with("Error: Not a command"));
+        let last = transcript.entries.last().unwrap();
+        assert_eq!((last.side, last.turn), (Side::Soviet, 7));
+    }
+}
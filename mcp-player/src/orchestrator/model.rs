@@ -0,0 +1,231 @@
+use crate::config::OrchestratorConfig;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for a model's reply
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// One message of a chat conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// "system", "user" or "assistant"
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new("system", content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new("user", content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new("assistant", content)
+    }
+
+    fn new(role: &str, content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A language model the orchestrator can ask for a side's next command
+#[async_trait]
+pub trait ModelBackend: Send + Sync {
+    /// Name the model is reported by
+    fn name(&self) -> &str;
+
+    /// Reply to a conversation
+    async fn complete(&self, messages: &[ChatMessage]) -> Result<String>;
+}
+
+/// Open the model named by a command-line spec
+///
+/// `openai:MODEL` uses the OpenAI-compatible API at `orchestrator.api_url`, and
+/// `script:PATH` replays the replies in a file, one per line.
+pub fn model_from_spec(spec: &str, config: &OrchestratorConfig) -> Result<Box<dyn ModelBackend>> {
+    let (kind, value) = spec
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Expected openai:MODEL or script:PATH, got \"{}\"", spec))?;
+    match kind {
+        "openai" if !value.is_empty() => {
+            let api_key = std::env::var(&config.api_key_env).ok();
+            Ok(Box::new(OpenAiBackend::new(
+                &config.api_url,
+                value,
+                api_key,
+                config.temperature,
+            )?))
+        }
+        "script" => {
+            let script = std::fs::read_to_string(value)
+                .map_err(|e| anyhow!("Cannot read script {}: {}", value, e))?;
+            let replies = script
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect();
+            Ok(Box::new(ScriptedBackend::new(spec, replies)))
+        }
+        _ => Err(anyhow!("Unknown model \"{}\"", spec)),
+    }
+}
+
+/// A model served by an OpenAI-compatible chat completions API
+///
+/// Works with OpenAI itself and with local servers such as Ollama, vLLM or llama.cpp
+/// that offer the same `/chat/completions` endpoint.
+pub struct OpenAiBackend {
+    http: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    temperature: f64,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+impl OpenAiBackend {
+    /// A backend for `model` at `api_url`, the API root such as `https://api.openai.com/v1`
+    pub fn new(
+        api_url: &str,
+        model: &str,
+        api_key: Option<String>,
+        temperature: f64,
+    ) -> Result<Self> {
+        Ok(OpenAiBackend {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()?,
+            url: format!("{}/chat/completions", api_url.trim_end_matches('/')),
+            model: model.to_string(),
+            api_key,
+            temperature,
+        })
+    }
+}
+
+#[async_trait]
+impl ModelBackend for OpenAiBackend {
+    fn name(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, messages: &[ChatMessage]) -> Result<String> {
+        let mut request = self.http.post(&self.url).json(&json!({
+            "model": self.model,
+            "messages": messages,
+            "temperature": self.temperature,
+        }));
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Model API returned {}: {}", status, body));
+        }
+        let completion: ChatCompletion = response.json().await?;
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| anyhow!("Model API returned no choices"))
+    }
+}
+
+/// A stand-in model that gives canned replies in order, then ends every phase
+pub struct ScriptedBackend {
+    name: String,
+    replies: Mutex<VecDeque<String>>,
+}
+
+impl ScriptedBackend {
+    pub fn new(name: &str, replies: Vec<String>) -> Self {
+        ScriptedBackend {
+            name: name.to_string(),
+            replies: Mutex::new(replies.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl ModelBackend for ScriptedBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(&self, _messages: &[ChatMessage]) -> Result<String> {
+        let reply = self.replies.lock().unwrap().pop_front();
+        Ok(reply.unwrap_or_else(|| "END".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_openai_backend() {
+        // Answers with the model name and the first message
+        async fn complete(Json(body): Json<Value>) -> Json<Value> {
+            let model = body["model"].as_str().unwrap();
+            let last = body["messages"][0]["content"].as_str().unwrap();
+            let content = format!("{}: {}", model, last);
+            Json(json!({ "choices": [{ "message": { "role": "assistant", "content": content } }] }))
+        }
+        let app = Router::new().route("/v1/chat/completions", post(complete));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let url = format!("http://{}/v1/", addr);
+        let model = OpenAiBackend::new(&url, "tiny", None, 0.0).unwrap();
+        let reply = model
+            .complete(&[ChatMessage::user("Your move")])
+            .await
+            .unwrap();
+        assert_eq!(reply, "tiny: Your move");
+
+        let missing = OpenAiBackend::new(&format!("http://{}/none", addr), "tiny", None, 0.0);
+        assert!(missing.unwrap().complete(&[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scripted_backend() {
+        let model = ScriptedBackend::new("script", vec!["MOVE V 1,0".to_string()]);
+        assert_eq!(model.complete(&[]).await.unwrap(), "MOVE V 1,0");
+        assert_eq!(model.complete(&[]).await.unwrap(), "END");
+
+        let config = OrchestratorConfig::default();
+        assert!(model_from_spec("openai:", &config).is_err());
+        assert!(model_from_spec("gpt", &config).is_err());
+        assert_eq!(
+            model_from_spec("openai:qwen2.5", &config).unwrap().name(),
+            "qwen2.5"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use backend::hex::Hex;
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -8,6 +8,11 @@ pub enum Command {
     Units { side: Option<String> },
     Threats,
     Rules { topic: String },
+    Map {
+        center: Option<(i32, i32)>,
+        radius: Option<i32>,
+        overlay: Option<MapOverlay>,
+    },
 
     // Queries
     Moves { unit_id: String },
@@ -20,16 +25,28 @@ pub enum Command {
     Attack { defender: String, attackers: Vec<String> },
     DoneAttacks,
     Resolve,
-    Advance { unit_id: String },
-    Skip,
+    Retreat { unit_id: String, to: (i32, i32) },
     Replace { unit_id: String, hex: Option<(i32, i32)> },
     End,
 
     // Game
     New,
+    ExportTurn { path: String },
+    ImportTurn { path: String },
+    VerifyTurn { path: String },
+    Dice,
     Quit,
 }
 
+/// Hexes highlighted by the MAP command
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapOverlay {
+    Moves { unit_id: String },
+    /// ZOC exerted by the given side, or by the inactive side if none is given
+    Zoc { side: Option<String> },
+    Retreat { unit_id: String },
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     Empty,
@@ -41,6 +58,9 @@ pub enum ParseError {
     MissingAttackers,
     MissingDefender,
     MissingTopic,
+    MissingPath,
+    InvalidSide(String),
+    InvalidRadius(String),
 }
 
 impl std::fmt::Display for ParseError {
@@ -55,6 +75,9 @@ impl std::fmt::Display for ParseError {
             ParseError::MissingAttackers => write!(f, "Missing attacker units"),
             ParseError::MissingDefender => write!(f, "Missing defender unit"),
             ParseError::MissingTopic => write!(f, "Missing topic for RULES command"),
+            ParseError::MissingPath => write!(f, "Missing turn file path"),
+            ParseError::InvalidSide(s) => write!(f, "Invalid side: {}", s),
+            ParseError::InvalidRadius(s) => write!(f, "Invalid zoom radius: {}", s),
         }
     }
 }
@@ -62,7 +85,7 @@ impl std::fmt::Display for ParseError {
 impl std::error::Error for ParseError {}
 
 pub fn parse_command(input: &str) -> Result<Command, ParseError> {
-    let parts: Vec<&str> = input.trim().split_whitespace().collect();
+    let parts: Vec<&str> = input.split_whitespace().collect();
     if parts.is_empty() {
         return Err(ParseError::Empty);
     }
@@ -79,6 +102,7 @@ pub fn parse_command(input: &str) -> Result<Command, ParseError> {
             let topic = parts.get(1).ok_or(ParseError::MissingTopic)?.to_string();
             Ok(Command::Rules { topic })
         }
+        "MAP" => parse_map(&parts),
         "MOVES" | "M" => {
             let unit_id = parts.get(1).ok_or(ParseError::MissingUnitId)?;
             Ok(Command::Moves {
@@ -107,13 +131,14 @@ pub fn parse_command(input: &str) -> Result<Command, ParseError> {
             }
         }
         "RESOLVE" => Ok(Command::Resolve),
-        "ADVANCE" => {
+        "RETREAT" => {
             let unit_id = parts.get(1).ok_or(ParseError::MissingUnitId)?;
-            Ok(Command::Advance {
+            let hex = parts.get(2).ok_or(ParseError::MissingHex)?;
+            Ok(Command::Retreat {
                 unit_id: unit_id.to_string(),
+                to: parse_hex(hex)?,
             })
         }
-        "SKIP" => Ok(Command::Skip),
         "REPLACE" => {
             let unit_id = parts.get(1).ok_or(ParseError::MissingUnitId)?;
             let hex = parts.get(2).map(|h| parse_hex(h)).transpose()?;
@@ -124,6 +149,16 @@ pub fn parse_command(input: &str) -> Result<Command, ParseError> {
         }
         "END" => Ok(Command::End),
         "NEW" => Ok(Command::New),
+        "EXPORT" => Ok(Command::ExportTurn {
+            path: parts.get(1).ok_or(ParseError::MissingPath)?.to_string(),
+        }),
+        "IMPORT" => Ok(Command::ImportTurn {
+            path: parts.get(1).ok_or(ParseError::MissingPath)?.to_string(),
+        }),
+        "DICE" => Ok(Command::Dice),
+        "VERIFY" => Ok(Command::VerifyTurn {
+            path: parts.get(1).ok_or(ParseError::MissingPath)?.to_string(),
+        }),
         "QUIT" | "EXIT" | "Q" => Ok(Command::Quit),
         _ => Err(ParseError::UnknownCommand(parts[0].to_string())),
     }
@@ -139,8 +174,11 @@ fn parse_side(s: &str) -> Result<String, ParseError> {
 }
 
 fn parse_hex(s: &str) -> Result<(i32, i32), ParseError> {
-    // Accept "5,3" or "5 3" or "[5,3]"
+    // Accept "5,3" or "[5,3]", or a printed map hex number such as "0505"
     let clean = s.trim_matches(|c| c == '[' || c == ']');
+    if let Some(hex) = Hex::from_original(clean) {
+        return Ok((hex.q, hex.r));
+    }
     let parts: Vec<&str> = clean.split(',').collect();
     if parts.len() != 2 {
         return Err(ParseError::InvalidHex(s.to_string()));
@@ -156,6 +194,58 @@ fn parse_hex(s: &str) -> Result<(i32, i32), ParseError> {
     Ok((q, r))
 }
 
+fn parse_map(parts: &[&str]) -> Result<Command, ParseError> {
+    // MAP [<hex> [<radius>]] [MOVES <unit> | ZOC [side] | RETREAT <unit>]
+    let mut center = None;
+    let mut radius = None;
+    let mut overlay = None;
+
+    let mut rest = parts[1..].iter();
+    while let Some(part) = rest.next() {
+        match part.to_uppercase().as_str() {
+            "MOVES" => {
+                let unit_id = rest.next().ok_or(ParseError::MissingUnitId)?;
+                overlay = Some(MapOverlay::Moves {
+                    unit_id: unit_id.to_string(),
+                });
+            }
+            "RETREAT" => {
+                let unit_id = rest.next().ok_or(ParseError::MissingUnitId)?;
+                overlay = Some(MapOverlay::Retreat {
+                    unit_id: unit_id.to_string(),
+                });
+            }
+            "ZOC" => {
+                let side = rest
+                    .next()
+                    .map(|s| match parse_side(s)?.as_str() {
+                        side @ ("german" | "soviet") => Ok(side.to_string()),
+                        _ => Err(ParseError::InvalidSide(s.to_string())),
+                    })
+                    .transpose()?;
+                overlay = Some(MapOverlay::Zoc { side });
+            }
+            _ if center.is_none() => center = Some(parse_hex(part)?),
+            _ if radius.is_none() => {
+                let value: i32 = part
+                    .parse()
+                    .map_err(|_| ParseError::InvalidRadius(part.to_string()))?;
+                if value < 0 {
+                    return Err(ParseError::InvalidRadius(part.to_string()));
+                }
+                radius = Some(value);
+            }
+            _ => return Err(ParseError::UnknownCommand(part.to_string())),
+        }
+    }
+
+    Ok(Command::Map {
+        center,
+        radius,
+        overlay,
+    })
+}
+
 fn parse_attack(parts: &[&str]) -> Result<Command, ParseError> {
     // ATTACK <defender> WITH <attacker> [attacker...]
     let with_pos = parts
@@ -239,6 +329,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_retreat() {
+        match parse_command("retreat 16A 0505") {
+            Ok(Command::Retreat { unit_id, to }) => {
+                assert_eq!(unit_id, "16A");
+                assert_eq!(to, (5, 3));
+            }
+            _ => panic!("Failed to parse retreat command"),
+        }
+        assert!(matches!(
+            parse_command("retreat 16A"),
+            Err(ParseError::MissingHex)
+        ));
+    }
+
+    #[test]
+    fn test_parse_move_original_hex() {
+        match parse_command("move XLVII 0505") {
+            Ok(Command::Move { to, .. }) => assert_eq!(to, (5, 3)),
+            _ => panic!("Failed to parse move with original hex number"),
+        }
+        assert!(matches!(
+            parse_command("move XLVII 505"),
+            Err(ParseError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_map() {
+        assert!(matches!(
+            parse_command("map"),
+            Ok(Command::Map {
+                center: None,
+                radius: None,
+                overlay: None
+            })
+        ));
+
+        match parse_command("MAP 0505 2 moves XLVII") {
+            Ok(Command::Map {
+                center,
+                radius,
+                overlay,
+            }) => {
+                assert_eq!(center, Some((5, 3)));
+                assert_eq!(radius, Some(2));
+                assert_eq!(
+                    overlay,
+                    Some(MapOverlay::Moves {
+                        unit_id: "XLVII".to_string()
+                    })
+                );
+            }
+            _ => panic!("Failed to parse map command"),
+        }
+
+        match parse_command("map zoc g") {
+            Ok(Command::Map { overlay, .. }) => assert_eq!(
+                overlay,
+                Some(MapOverlay::Zoc {
+                    side: Some("german".to_string())
+                })
+            ),
+            _ => panic!("Failed to parse map zoc command"),
+        }
+
+        assert!(matches!(
+            parse_command("map zoc finnish"),
+            Err(ParseError::InvalidSide(_))
+        ));
+        assert!(matches!(
+            parse_command("map 5,3 far"),
+            Err(ParseError::InvalidRadius(_))
+        ));
+        assert!(matches!(
+            parse_command("map retreat"),
+            Err(ParseError::MissingUnitId)
+        ));
+    }
+
+    #[test]
+    fn test_parse_turn_files() {
+        match parse_command("export turn3.json") {
+            Ok(Command::ExportTurn { path }) => assert_eq!(path, "turn3.json"),
+            _ => panic!("Failed to parse export command"),
+        }
+        assert!(matches!(
+            parse_command("VERIFY turn3.json"),
+            Ok(Command::VerifyTurn { .. })
+        ));
+        assert!(matches!(parse_command("import"), Err(ParseError::MissingPath)));
+        assert!(matches!(parse_command("dice"), Ok(Command::Dice)));
+    }
+
     #[test]
     fn test_parse_attack() {
         match parse_command("attack 16A WITH XLVII XL") {
@@ -0,0 +1,361 @@
+use backend::game_state::GameState;
+use backend::hex::{Direction, Hex};
+use backend::map::{Map, MapHex, Terrain};
+use backend::render::CoordinateLabels;
+use backend::unit::{Side, UnitStrength, Units};
+
+/// Horizontal distance between neighbouring hex centres, in characters
+const HEX_WIDTH: i32 = 8;
+/// Vertical distance between hex rows, in lines
+const ROW_HEIGHT: i32 = 4;
+
+/// Hexes marked on the map by filling their interior with a character
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    pub name: String,
+    pub hexes: Vec<Hex>,
+    pub fill: char,
+}
+
+/// Options controlling what part of the map is drawn and how
+#[derive(Debug, Clone)]
+pub struct AsciiMapOptions {
+    pub labels: CoordinateLabels,
+    /// Only draw hexes within this distance of the given hex
+    pub zoom: Option<(Hex, i32)>,
+    pub overlay: Option<Overlay>,
+}
+
+/// Render the map and game state as text, one pointy-top hex per 9x6 character cell:
+///
+/// ```text
+///    / \
+///  / 0505\
+/// | Kaluga|
+/// |  16A  |
+///  \ S6-4/
+///    \ /
+/// ```
+pub fn render_ascii(
+    map: &Map,
+    units: &Units,
+    state: &GameState,
+    options: &AsciiMapOptions,
+) -> String {
+    let hexes: Vec<&MapHex> = map
+        .hexes
+        .iter()
+        .filter(|mh| match options.zoom {
+            Some((center, radius)) => center.distance(&mh.hex()) <= radius,
+            None => true,
+        })
+        .collect();
+
+    let mut output = format!(
+        "TURN {}{} — {}\n\n",
+        state.turn,
+        if state.is_mud() { " (MUD)" } else { "" },
+        state.phase.display_name()
+    );
+
+    if hexes.is_empty() {
+        output.push_str("No hexes to show.\n");
+        return output;
+    }
+
+    let min_x = hexes
+        .iter()
+        .map(|mh| center_x(&mh.hex()))
+        .min()
+        .unwrap_or(0)
+        - HEX_WIDTH / 2;
+    let max_x = hexes
+        .iter()
+        .map(|mh| center_x(&mh.hex()))
+        .max()
+        .unwrap_or(0)
+        + HEX_WIDTH / 2;
+    let min_y = hexes.iter().map(|mh| mh.r * ROW_HEIGHT).min().unwrap_or(0);
+    let max_y = hexes.iter().map(|mh| mh.r * ROW_HEIGHT).max().unwrap_or(0) + 5;
+
+    let mut canvas = Canvas {
+        cells: vec![vec![' '; (max_x - min_x + 1) as usize]; (max_y - min_y + 1) as usize],
+        origin: (min_x, min_y),
+    };
+
+    // Outlines first so rivers drawn afterwards replace the shared edges
+    for map_hex in &hexes {
+        let hex = map_hex.hex();
+        for direction in Direction::all() {
+            for (dx, dy, c) in edge_cells(direction) {
+                canvas.put(center_x(&hex) + dx, hex.r * ROW_HEIGHT + dy, c);
+            }
+        }
+    }
+    for map_hex in &hexes {
+        let hex = map_hex.hex();
+        for direction in Direction::all()
+            .into_iter()
+            .filter(|&d| map_hex.has_river_edge(d))
+        {
+            for (dx, dy, _) in edge_cells(direction) {
+                canvas.put(center_x(&hex) + dx, hex.r * ROW_HEIGHT + dy, '~');
+            }
+        }
+    }
+
+    for map_hex in &hexes {
+        draw_contents(&mut canvas, map_hex, units, state, options);
+    }
+
+    for line in canvas.cells {
+        let line: String = line.into_iter().collect();
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    output.push_str("\n^^ forest  # fortification  = rail  ~ river\n");
+    output.push_str("G/S German/Soviet unit at full strength, g/s reduced (strength-movement)\n");
+    if let Some(ref overlay) = options.overlay {
+        output.push_str(&format!("{} {}\n", overlay.fill, overlay.name));
+    }
+
+    output
+}
+
+/// Character grid addressed in map coordinates
+struct Canvas {
+    cells: Vec<Vec<char>>,
+    origin: (i32, i32),
+}
+
+impl Canvas {
+    fn put(&mut self, x: i32, y: i32, c: char) {
+        let (col, row) = (x - self.origin.0, y - self.origin.1);
+        if row < 0 || col < 0 {
+            return;
+        }
+        if let Some(cell) = self
+            .cells
+            .get_mut(row as usize)
+            .and_then(|line| line.get_mut(col as usize))
+        {
+            *cell = c;
+        }
+    }
+
+    /// Write text centred in a span of `width` cells starting at `x`
+    fn put_centered(&mut self, x: i32, y: i32, width: i32, text: &str) {
+        let chars: Vec<char> = text.chars().take(width as usize).collect();
+        let start = x + (width - chars.len() as i32 + 1) / 2;
+        for (i, c) in chars.into_iter().enumerate() {
+            self.put(start + i as i32, y, c);
+        }
+    }
+}
+
+/// Column of a hex centre, matching the pointy-top layout of the web map
+fn center_x(hex: &Hex) -> i32 {
+    hex.q * HEX_WIDTH + hex.r * HEX_WIDTH / 2
+}
+
+/// Cells drawing one edge of a hex, as offsets from its centre column and top line
+fn edge_cells(direction: Direction) -> [(i32, i32, char); 2] {
+    match direction {
+        Direction::NW => [(-1, 0, '/'), (-3, 1, '/')],
+        Direction::NE => [(1, 0, '\\'), (3, 1, '\\')],
+        Direction::W => [(-4, 2, '|'), (-4, 3, '|')],
+        Direction::E => [(4, 2, '|'), (4, 3, '|')],
+        Direction::SW => [(-3, 4, '\\'), (-1, 5, '\\')],
+        Direction::SE => [(3, 4, '/'), (1, 5, '/')],
+    }
+}
+
+/// Draw the label, terrain or city, unit and overlay inside a hex
+fn draw_contents(
+    canvas: &mut Canvas,
+    map_hex: &MapHex,
+    units: &Units,
+    state: &GameState,
+    options: &AsciiMapOptions,
+) {
+    let hex = map_hex.hex();
+    let (x, top) = (center_x(&hex), hex.r * ROW_HEIGHT);
+
+    // Interior rows are 5, 7, 7 and 5 cells wide
+    let rows = [(top + 1, 2), (top + 2, 3), (top + 3, 3), (top + 4, 2)];
+
+    if let Some(ref overlay) = options.overlay {
+        if overlay.hexes.contains(&hex) {
+            for &(y, half) in &rows {
+                for dx in -half..=half {
+                    canvas.put(x + dx, y, overlay.fill);
+                }
+            }
+        }
+    }
+
+    let label = match options.labels {
+        CoordinateLabels::None => String::new(),
+        CoordinateLabels::Axial => format!("{},{}", hex.q, hex.r),
+        CoordinateLabels::Original => hex.to_original().map_or_else(String::new, |o| o.to_string()),
+    };
+    canvas.put_centered(x - 2, top + 1, 5, &label);
+
+    let feature = match map_hex.city {
+        Some(ref city) if city.is_moscow => city.name.to_uppercase(),
+        Some(ref city) => city.name.clone(),
+        None => {
+            let mut symbols = Vec::new();
+            if map_hex.terrain == Terrain::Forest {
+                symbols.push("^^");
+            }
+            if map_hex.fortification {
+                symbols.push("#");
+            }
+            if map_hex.rail {
+                symbols.push("=");
+            }
+            symbols.join(" ")
+        }
+    };
+    canvas.put_centered(x - 3, top + 2, 7, &feature);
+
+    let occupants = state.get_units_at(&hex);
+    if let Some(unit) = occupants.first() {
+        let id = if occupants.len() > 1 {
+            format!("{}+", unit.id)
+        } else {
+            unit.id.clone()
+        };
+        canvas.put_centered(x - 3, top + 3, 7, &id);
+
+        if let Some(def) = units.get(&unit.id) {
+            let letter = match (def.side, &unit.strength) {
+                (Side::German, UnitStrength::Full) => 'G',
+                (Side::German, _) => 'g',
+                (Side::Soviet, UnitStrength::Full) => 'S',
+                (Side::Soviet, _) => 's',
+            };
+            let counter = format!(
+                "{}{}-{}",
+                letter,
+                def.get_combat_strength(&unit.strength),
+                def.movement
+            );
+            canvas.put_centered(x - 2, top + 4, 5, &counter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::map::{City, MapBounds, MapEdges};
+    use backend::unit::{UnitDefinition, UnitState, UnitType};
+
+    fn test_map() -> Map {
+        let hex = |q: i32, r: i32| MapHex {
+            q,
+            r,
+            terrain: Terrain::Clear,
+            city: None,
+            fortification: false,
+            rail: false,
+            river_edges: vec![],
+            setup: None,
+        };
+        let mut hexes = vec![hex(0, 0), hex(1, 0), hex(0, 1)];
+        hexes[0].terrain = Terrain::Forest;
+        hexes[0].rail = true;
+        hexes[0].river_edges = vec!["E".to_string()];
+        hexes[1].river_edges = vec!["W".to_string()];
+        hexes[1].city = Some(City {
+            name: "Kaluga".to_string(),
+            is_moscow: false,
+        });
+        hexes[2].fortification = true;
+
+        Map {
+            hexes,
+            map_bounds: MapBounds {
+                min_q: 0,
+                max_q: 1,
+                min_r: 0,
+                max_r: 1,
+            },
+            edges: MapEdges {
+                west: "german_communication".to_string(),
+                east: "soviet_communication".to_string(),
+            },
+        }
+    }
+
+    fn test_units() -> Units {
+        Units {
+            units: vec![UnitDefinition {
+                id: "16A".to_string(),
+                side: Side::Soviet,
+                unit_type: UnitType::Infantry,
+                full_strength: 6,
+                half_strength: 3,
+                movement: 4,
+                available_turn: None,
+            }],
+        }
+    }
+
+    fn test_state() -> GameState {
+        let mut state = GameState::new();
+        state.units.push(UnitState::new(
+            "16A".to_string(),
+            Some(Hex::new(1, 0)),
+            UnitStrength::Half,
+        ));
+        state
+    }
+
+    fn options() -> AsciiMapOptions {
+        AsciiMapOptions {
+            labels: CoordinateLabels::Axial,
+            zoom: None,
+            overlay: None,
+        }
+    }
+
+    #[test]
+    fn test_render_map_features() {
+        let text = render_ascii(&test_map(), &test_units(), &test_state(), &options());
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "TURN 1 — German Panzer Movement");
+        assert_eq!(lines[2], "   / \\     / \\");
+        assert_eq!(lines[3], " / 0,0 \\ / 1,0 \\");
+        assert_eq!(lines[4], "|  ^^ = ~ Kaluga|");
+        assert_eq!(lines[5], "|       ~  16A  |");
+        assert_eq!(lines[6], " \\     / \\ s3-4/");
+        assert_eq!(lines[7], "   \\ / 0,1 \\ /");
+        assert_eq!(lines[8], "    |   #   |");
+    }
+
+    #[test]
+    fn test_render_zoom_and_overlay() {
+        let options = AsciiMapOptions {
+            labels: CoordinateLabels::Original,
+            zoom: Some((Hex::new(0, 1), 0)),
+            overlay: Some(Overlay {
+                name: "valid moves".to_string(),
+                hexes: vec![Hex::new(0, 1)],
+                fill: '.',
+            }),
+        };
+        let text = render_ascii(&test_map(), &test_units(), &test_state(), &options);
+
+        assert!(!text.contains("Kaluga"));
+        assert!(text.contains("|...#...|"));
+        // Column 00 has no printed number, so the label is left blank
+        assert!(text.contains(" /.....\\\n"));
+        assert!(!text.contains("0001"));
+        assert!(text.contains(". valid moves"));
+    }
+}
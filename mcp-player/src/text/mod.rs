@@ -1,4 +1,5 @@
 pub mod commands;
+pub mod map;
 pub mod prompts;
 pub mod repl;
 
@@ -1,5 +1,5 @@
 use crate::game::narrator::Narrator;
-use serde_json::Value;
+use backend::game_state::{GameState, Phase};
 
 pub const HELP_TEXT: &str = r#"BATTLE FOR MOSCOW - COMMANDS
 
@@ -9,6 +9,10 @@ INFORMATION:
   UNITS [side]                 List units (german/soviet/all)
   THREATS, T                   Danger analysis
   RULES <topic>                Explain a rule
+  MAP [<hex> [<radius>]]       Draw the map, or the area around a hex
+      [MOVES <unit>]           ...marking valid moves
+      [ZOC [side]]             ...marking a side's zone of control
+      [RETREAT <unit>]         ...marking retreat hexes
 
 QUERIES:
   MOVES <unit>, M <unit>       Valid moves for unit
@@ -17,67 +21,57 @@ QUERIES:
   REPLACEMENTS, REP            Replacement options
 
 ACTIONS:
-  MOVE <unit> <q>,<r>          Move unit to hex
+  MOVE <unit> <q>,<r>          Move unit to hex (or XXYY map number)
   ATTACK <def> WITH <atk...>   Declare attack
   DONE ATTACKS                 Finish declaring, start resolution
   RESOLVE                      Resolve next battle
-  ADVANCE <unit>               Advance after combat
-  SKIP                         Skip advance
-  REPLACE <unit> [<q>,<r>]     Use replacement
+  RETREAT <unit> <hex>         Retreat a defender after DR or DRL
+  REPLACE <unit> [<hex>]       Use replacement
   END, DONE                    End current phase
 
 GAME:
   NEW                          New game
+  EXPORT <file>                Save the last side's turn for your opponent
+  VERIFY <file>                Check an opponent's turn file
+  IMPORT <file>                Check and apply an opponent's turn file
+  DICE                         Check every die roll once the game is over
   QUIT, EXIT, Q                Exit
 
 Examples:
   > sit
+  > map 0505 2 moves XLVII
   > moves XLVII
   > move XLVII 4,5
+  > move XLVII 0407
   > attack 16A WITH XLVII XL
   > preview 16A WITH XLVII XL
   > end
 "#;
 
-pub fn print_phase_prompt(state: &Value, narrator: &Narrator) -> String {
+pub fn print_phase_prompt(state: &GameState, narrator: &Narrator) -> String {
     let mut output = String::new();
 
     output.push_str("══════════════════════════════════════════════════════════════\n\n");
 
     // Phase header
-    let turn = state["turn"].as_i64().unwrap_or(1);
-    let phase = state["phase"].as_str().unwrap_or("Unknown");
-    let weather = state["weather"].as_str().unwrap_or("Clear");
-
-    let mud = if weather == "Mud" { " (MUD)" } else { "" };
-    output.push_str(&format!("TURN {}{} — {}\n\n", turn, mud, format_phase(phase)));
+    output.push_str(&format!("{}\n\n", narrator.turn_heading(state)));
+    if state.is_over() {
+        output.push_str("The game is over. Type SITUATION for the result.\n");
+        return output;
+    }
 
     // Phase-specific prompt
-    output.push_str(&get_phase_description(phase, weather));
+    output.push_str(&get_phase_description(&state.phase, state.is_mud()));
 
     output
 }
 
-fn format_phase(phase: &str) -> String {
-    match phase {
-        "GermanPanzerMovement" => "German Panzer Movement Phase",
-        "GermanCombat" => "German Combat Phase",
-        "GermanMovement" => "German Movement Phase",
-        "GermanReplacement" => "German Replacement Phase",
-        "SovietCombat" => "Soviet Combat Phase",
-        "SovietMovement" => "Soviet Movement Phase",
-        "SovietRailMovement" => "Soviet Rail Movement Phase",
-        "SovietReplacement" => "Soviet Replacement Phase",
-        _ => phase,
-    }
-    .to_string()
-}
-
-fn get_phase_description(phase: &str, weather: &str) -> String {
+fn get_phase_description(phase: &Phase, mud: bool) -> String {
+    let side = format!("{:?}", phase.active_player());
     match phase {
-        "GermanPanzerMovement" => {
+        Phase::GermanPanzerMovement => {
             let mut desc = String::from("Move your Panzer units (6 MP each");
-            if weather == "Mud" {
+            if mud {
                 desc.push_str(", limited to 1 hex in mud");
             }
             desc.push_str(").\n");
@@ -85,15 +79,10 @@ fn get_phase_description(phase: &str, weather: &str) -> String {
             desc.push_str("\nCommands: MOVES <unit>, MOVE <unit> <q>,<r>, END\n");
             desc
         }
-        "GermanCombat" | "SovietCombat" => {
-            let side = if phase.starts_with("German") {
-                "German"
-            } else {
-                "Soviet"
-            };
+        Phase::GermanCombat | Phase::SovietCombat => {
             let mut desc = format!("{} Combat Phase\n\n", side);
             desc.push_str("Declare attacks, then resolve them one by one.\n");
-            if weather == "Mud" {
+            if mud {
                 desc.push_str("REMINDER: Attack strengths are halved in mud!\n");
             }
             desc.push_str("\nCommands:\n");
@@ -102,19 +91,13 @@ fn get_phase_description(phase: &str, weather: &str) -> String {
             desc.push_str("  ATTACK <def> WITH <atk...>  - Declare attack\n");
             desc.push_str("  DONE ATTACKS         - Finish declaring\n");
             desc.push_str("  RESOLVE              - Resolve next battle\n");
-            desc.push_str("  ADVANCE <unit>       - Advance after victory\n");
-            desc.push_str("  SKIP                 - Skip advance\n");
+            desc.push_str("  RETREAT <unit> <hex> - Retreat a defender\n");
             desc
         }
-        "GermanMovement" | "SovietMovement" => {
-            let side = if phase.starts_with("German") {
-                "German"
-            } else {
-                "Soviet"
-            };
+        Phase::GermanMovement | Phase::SovietMovement => {
             let mut desc = format!("{} Movement Phase\n\n", side);
-            desc.push_str("Move your infantry units (4 MP each");
-            if weather == "Mud" {
+            desc.push_str("Move your units (infantry 4 MP, panzers 6 MP");
+            if mud {
                 desc.push_str(", limited to 1 hex in mud");
             }
             desc.push_str(").\n");
@@ -122,18 +105,13 @@ fn get_phase_description(phase: &str, weather: &str) -> String {
             desc.push_str("\nCommands: MOVES <unit>, MOVE <unit> <q>,<r>, END\n");
             desc
         }
-        "SovietRailMovement" => {
+        Phase::SovietRailMovement => {
             let mut desc = String::from("Soviet Rail Movement Phase\n\n");
             desc.push_str("Move units along rail lines. Rail movement is NOT affected by mud.\n");
             desc.push_str("\nCommands: MOVES <unit>, MOVE <unit> <q>,<r>, END\n");
             desc
         }
-        "GermanReplacement" | "SovietReplacement" => {
-            let side = if phase.starts_with("German") {
-                "German"
-            } else {
-                "Soviet"
-            };
+        Phase::GermanReplacement | Phase::SovietReplacement => {
             let mut desc = format!("{} Replacement Phase\n\n", side);
             desc.push_str("Use replacements to restore or bring back units.\n");
             desc.push_str("Must be placed in friendly-controlled cities with supply.\n");
@@ -143,6 +121,5 @@ fn get_phase_description(phase: &str, weather: &str) -> String {
             desc.push_str("  END                  - Finish phase\n");
             desc
         }
-        _ => format!("Phase: {}\n\nCommands: Type HELP for command list\n", phase),
     }
 }
@@ -1,12 +1,22 @@
 use crate::config::Config;
-use crate::game::client::GameClient;
+use crate::game::backend::GameBackend;
 use crate::game::narrator::Narrator;
-use crate::text::commands::{parse_command, Command, ParseError};
+use crate::text::commands::{parse_command, Command, MapOverlay, ParseError};
+use crate::text::map::{render_ascii, AsciiMapOptions, Overlay};
 use crate::text::prompts::{print_phase_prompt, HELP_TEXT};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use backend::combat::CombatResult;
+use backend::dice::verify_audit;
+use backend::game_state::GameState;
+use backend::hex::Hex;
+use backend::map::Map;
+use backend::movement::find_valid_moves;
+use backend::retreat::{find_valid_retreat_hexes, RetreatResult};
+use backend::turn_file::TurnFile;
+use backend::unit::{Side, Units};
+use backend::zoc::calculate_enemy_zoc;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use serde_json::Value;
 
 pub struct CommandOutput {
     pub text: String,
@@ -30,7 +40,16 @@ impl CommandOutput {
 }
 
 pub async fn run_text_mode(config: &Config) -> Result<()> {
-    let client = GameClient::new(config.game.api_url.clone());
+    let client = match crate::game::backend::connect(config).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error joining game: {}", e);
+            if config.game.backend == "http" {
+                eprintln!("\nMake sure the game server is running at: {}", config.game.api_url);
+            }
+            return Ok(());
+        }
+    };
     let narrator = Narrator::new(
         config.display.coordinate_format.clone(),
         config.display.verbosity.clone(),
@@ -64,7 +83,7 @@ pub async fn run_text_mode(config: &Config) -> Result<()> {
                 }
 
                 match parse_command(&line) {
-                    Ok(cmd) => match execute_command(&client, &narrator, cmd).await {
+                    Ok(cmd) => match execute_command(client.as_ref(), &narrator, cmd).await {
                         Ok(output) => {
                             println!("{}\n", output.text);
 
@@ -102,8 +121,66 @@ pub async fn run_text_mode(config: &Config) -> Result<()> {
     Ok(())
 }
 
-async fn execute_command(
-    client: &GameClient,
+/// List hexes in the configured coordinate format
+fn format_hexes(narrator: &Narrator, hexes: &[Hex]) -> String {
+    if hexes.is_empty() {
+        return "none".to_string();
+    }
+    hexes
+        .iter()
+        .map(|hex| narrator.format_hex(hex.q as i64, hex.r as i64))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn read_turn_file(path: &str) -> Result<TurnFile> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Work out the hexes marked by a MAP overlay
+fn map_overlay(overlay: &MapOverlay, map: &Map, units: &Units, state: &GameState) -> Result<Overlay> {
+    match overlay {
+        MapOverlay::Moves { unit_id } => Ok(Overlay {
+            name: format!("valid moves for {}", unit_id),
+            hexes: find_valid_moves(unit_id, state, units, map).map_err(|e| anyhow!(e))?,
+            fill: '.',
+        }),
+        MapOverlay::Zoc { side } => {
+            let side = match side.as_deref() {
+                Some("german") => Side::German,
+                Some("soviet") => Side::Soviet,
+                _ => match state.active_player() {
+                    Side::German => Side::Soviet,
+                    Side::Soviet => Side::German,
+                },
+            };
+            let opposing = match side {
+                Side::German => Side::Soviet,
+                Side::Soviet => Side::German,
+            };
+            Ok(Overlay {
+                name: format!("{:?} zone of control", side),
+                hexes: calculate_enemy_zoc(state, units, opposing).into_iter().collect(),
+                fill: ':',
+            })
+        }
+        MapOverlay::Retreat { unit_id } => {
+            let from = state
+                .get_unit(unit_id)
+                .and_then(|unit| unit.hex())
+                .ok_or_else(|| anyhow!("Unit {} is not on the map", unit_id))?;
+            Ok(Overlay {
+                name: format!("retreat hexes for {}", unit_id),
+                hexes: find_valid_retreat_hexes(unit_id, &from, state, units, map),
+                fill: '>',
+            })
+        }
+    }
+}
+
+pub async fn execute_command(
+    client: &dyn GameBackend,
     narrator: &Narrator,
     cmd: Command,
 ) -> Result<CommandOutput> {
@@ -112,95 +189,103 @@ async fn execute_command(
 
         Command::Situation => {
             let state = client.get_state().await?;
-            Ok(CommandOutput::text(narrator.narrate_situation(&state)))
+            let units = client.get_units().await?;
+            let map = client.get_map().await?;
+            Ok(CommandOutput::text(narrator.narrate_situation(&state, &units, &map)))
         }
 
         Command::Units { side } => {
             let state = client.get_state().await?;
-            let side_str = side.as_deref();
-            Ok(CommandOutput::text(narrator.narrate_units(&state, side_str)))
+            let units = client.get_units().await?;
+            let map = client.get_map().await?;
+            // "all", or no side, lists both
+            let side = match side.as_deref() {
+                Some("german") => Some(Side::German),
+                Some("soviet") => Some(Side::Soviet),
+                _ => None,
+            };
+            Ok(CommandOutput::text(narrator.narrate_units(&state, &units, &map, side)))
         }
 
         Command::Threats => {
-            let state = client.get_state().await?;
-            // Determine current side from phase
-            let phase = state["phase"].as_str().unwrap_or("");
-            let side = if phase.contains("German") {
-                "german"
-            } else {
-                "soviet"
-            };
-            Ok(CommandOutput::text(narrator.narrate_threats(&state, side)))
+            let report = client.threats(None).await?;
+            Ok(CommandOutput::text(narrator.narrate_threats(&report)))
         }
 
         Command::Rules { topic } => Ok(CommandOutput::text(narrator.narrate_rules(&topic))),
 
-        Command::Moves { unit_id } => {
+        Command::Map {
+            center,
+            radius,
+            overlay,
+        } => {
+            let map = client.get_map().await?;
+            let units = client.get_units().await?;
             let state = client.get_state().await?;
-            // For now, return a placeholder - actual API endpoint would be needed
+
+            let overlay = overlay
+                .map(|overlay| map_overlay(&overlay, &map, &units, &state))
+                .transpose()?;
+            let options = AsciiMapOptions {
+                labels: narrator.coordinate_labels(),
+                zoom: center.map(|(q, r)| (Hex::new(q, r), radius.unwrap_or(3))),
+                overlay,
+            };
+            Ok(CommandOutput::text(render_ascii(&map, &units, &state, &options)))
+        }
+
+        Command::Moves { unit_id } => {
+            let moves = client.valid_moves(&unit_id).await?;
             Ok(CommandOutput::text(format!(
-                "Valid moves for {}: [Feature requires API endpoint]\n\
-                 The game engine needs to expose a /valid_moves endpoint.",
-                unit_id
+                "Valid moves for {}: {}",
+                unit_id,
+                format_hexes(narrator, &moves)
             )))
         }
 
         Command::Attacks => {
-            let state = client.get_state().await?;
-            Ok(CommandOutput::text(format!(
+            client.get_state().await?;
+            Ok(CommandOutput::text(
                 "Possible attacks: [Feature requires API endpoint]\n\
                  The game engine needs to expose an /available_attacks endpoint."
-            )))
+                    .to_string(),
+            ))
         }
 
-        Command::Preview { defender, attackers } => Ok(CommandOutput::text(format!(
-            "Attack preview: {} vs {}\n\
-             [Feature requires API endpoint]\n\
-             The game engine needs to expose a /preview_attack endpoint.",
-            attackers.join(" + "),
-            defender
-        ))),
+        Command::Preview { defender, attackers } => {
+            let preview = client.preview_battle(attackers, defender).await?;
+            Ok(CommandOutput::text(narrator.narrate_attack_preview(&preview)))
+        }
 
         Command::Replacements => {
-            let state = client.get_state().await?;
+            let hexes = client.replacement_hexes().await?;
             Ok(CommandOutput::text(format!(
-                "Available replacements: [Feature requires API endpoint]\n\
-                 The game engine needs to expose a /replacements endpoint."
+                "Replacement hexes: {}",
+                format_hexes(narrator, &hexes)
             )))
         }
 
         Command::Move { unit_id, to } => {
-            let result = client.move_unit(&unit_id, to).await?;
-            if result["success"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text(format!(
-                    "MOVED: {} → [{},{}]",
-                    unit_id, to.0, to.1
-                )))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!("Move failed: {}", error)))
-            }
+            client
+                .move_unit(&unit_id, to)
+                .await?;
+            Ok(CommandOutput::text(format!(
+                "MOVED: {} → {}",
+                unit_id,
+                narrator.format_hex(to.0 as i64, to.1 as i64)
+            )))
         }
 
         Command::Attack { defender, attackers } => {
-            // Create battle declaration
-            let battle = serde_json::json!({
-                "defender": defender,
-                "attackers": attackers,
-            });
-            let result = client.declare_attacks(vec![battle]).await?;
-
-            if result["success"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text(format!(
-                    "DECLARED: {} vs {}\n\n\
-                     More attacks? Or DONE ATTACKS to resolve.",
-                    attackers.join(" + "),
-                    defender
-                )))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!("Attack failed: {}", error)))
-            }
+            client
+                .declare_battle(attackers.clone(), defender.clone())
+                .await?;
+            Ok(CommandOutput::text(format!(
+                "DECLARED: {} vs {}\n\n\
+                 More attacks? Or DONE ATTACKS to resolve.",
+                attackers.join(" + "),
+                defender
+            )))
         }
 
         Command::DoneAttacks => Ok(CommandOutput::text(
@@ -208,77 +293,100 @@ async fn execute_command(
         )),
 
         Command::Resolve => {
-            let result = client.resolve_next_battle().await?;
-            let output = narrator.narrate_combat_result(&result);
-
-            // Check if advance is available
-            if result["can_advance"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text(format!(
-                    "{}\n\nADVANCE <unit> or SKIP",
-                    output
-                )))
-            } else {
-                Ok(CommandOutput::text(output))
-            }
-        }
-
-        Command::Advance { unit_id } => {
-            let result = client.advance_unit(&unit_id).await?;
-            if result["success"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text(format!("Advanced: {}", unit_id)))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!("Advance failed: {}", error)))
+            let (index, resolution) = client.resolve_next_battle().await?;
+            let mut output = narrator.narrate_combat_result(&resolution);
+
+            // The defender picks its own retreat hex
+            if matches!(resolution.result, CombatResult::DR | CombatResult::DRL) {
+                let state = client.get_state().await?;
+                let defender = &state.pending_battles[index].defender;
+                if state.get_unit(defender).and_then(|u| u.hex()).is_some() {
+                    let hexes = client.retreat_hexes(defender).await?;
+                    output.push_str(&format!(
+                        "\nRETREAT {} <hex> to one of: {}",
+                        defender,
+                        format_hexes(narrator, &hexes)
+                    ));
+                }
             }
+            Ok(CommandOutput::text(output))
         }
 
-        Command::Skip => {
-            let result = client.skip_advance().await?;
-            if result["success"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text("Advance skipped.".to_string()))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!("Skip failed: {}", error)))
-            }
+        Command::Retreat { unit_id, to } => {
+            let text = match client.retreat(&unit_id, to).await? {
+                RetreatResult::Success => format!(
+                    "RETREATED: {} → {}",
+                    unit_id,
+                    narrator.format_hex(to.0 as i64, to.1 as i64)
+                ),
+                RetreatResult::EliminatedInZoc => {
+                    format!("{} retreated into enemy ZOC and was eliminated", unit_id)
+                }
+            };
+            Ok(CommandOutput::text(text))
         }
 
         Command::Replace { unit_id, hex } => {
-            let result = client.use_replacement(&unit_id, hex).await?;
-            if result["success"].as_bool().unwrap_or(false) {
-                Ok(CommandOutput::text(format!("Replacement used: {}", unit_id)))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!(
-                    "Replacement failed: {}",
-                    error
-                )))
-            }
+            client
+                .apply_replacement(&unit_id, hex)
+                .await?;
+            Ok(CommandOutput::text(format!("Replacement used: {}", unit_id)))
         }
 
         Command::End => {
-            let old_state = client.get_state().await?;
-            let old_phase = old_state["phase"].as_str().unwrap_or("");
+            client
+                .advance_phase()
+                .await?;
+            Ok(CommandOutput::with_phase_change(
+                "Phase complete.".to_string(),
+            ))
+        }
 
-            let result = client.end_phase().await?;
+        Command::New => {
+            client.new_game(None).await?;
+            Ok(CommandOutput::with_phase_change(
+                "New game started.".to_string(),
+            ))
+        }
 
-            if result["success"].as_bool().unwrap_or(false) {
-                let new_state = client.get_state().await?;
-                let new_phase = new_state["phase"].as_str().unwrap_or("");
+        Command::ExportTurn { path } => {
+            let turn = client.export_turn().await?;
+            std::fs::write(&path, serde_json::to_string_pretty(&turn)?)?;
+            Ok(CommandOutput::text(format!(
+                "Exported {:?} turn ({} actions) to {}",
+                turn.side,
+                turn.actions.len(),
+                path
+            )))
+        }
 
-                Ok(CommandOutput::with_phase_change(
-                    "Phase complete.".to_string(),
-                ))
-            } else {
-                let error = result["error"].as_str().unwrap_or("Unknown error");
-                Ok(CommandOutput::text(format!("End phase failed: {}", error)))
-            }
+        Command::VerifyTurn { path } => {
+            let turn = read_turn_file(&path)?;
+            let check = client.verify_turn(&turn).await?;
+            Ok(CommandOutput::text(format!(
+                "{} is valid: {:?} turn, {} actions, die rolls {:?}",
+                path, check.side, check.actions, check.die_rolls
+            )))
         }
 
-        Command::New => Ok(CommandOutput::text(
-            "New game: [Feature requires API endpoint]\n\
-             The game engine needs to expose a /new_game endpoint."
-                .to_string(),
-        )),
+        Command::Dice => {
+            let audit = client.dice_audit().await?;
+            verify_audit(&audit).map_err(|e| anyhow!("Dice audit failed: {}", e))?;
+            Ok(CommandOutput::text(format!(
+                "All {} die rolls follow from the committed seed {}",
+                audit.rolls.len(),
+                audit.commitment
+            )))
+        }
+
+        Command::ImportTurn { path } => {
+            let turn = read_turn_file(&path)?;
+            client.import_turn(&turn).await?;
+            Ok(CommandOutput::with_phase_change(format!(
+                "Imported {:?} turn from {}",
+                turn.side, path
+            )))
+        }
 
         Command::Quit => {
             println!("Goodbye!");
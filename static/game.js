@@ -28,6 +28,9 @@ class BattleForMoscowGame {
         // Render initial state
         this.render();
 
+        // Follow changes made by other players and spectators' windows
+        this.subscribeToEvents();
+
         this.log('Game initialized. Click "New Game" to start.');
     }
 
@@ -154,6 +157,27 @@ class BattleForMoscowGame {
         }
     }
 
+    subscribeToEvents() {
+        // EventSource reconnects by itself, resuming after the last event ID it saw
+        const events = new EventSource('/api/events');
+        const types = ['game_started', 'unit_moved', 'battle_declared', 'die_rolled',
+            'unit_retreated', 'replacement_applied', 'phase_advanced'];
+        for (const type of types) {
+            events.addEventListener(type, () => this.scheduleRefresh());
+        }
+    }
+
+    scheduleRefresh() {
+        // Coalesce bursts of events, such as the backlog sent on connect, into one reload
+        if (this.refreshPending) return;
+        this.refreshPending = true;
+        setTimeout(async () => {
+            this.refreshPending = false;
+            await this.loadGameState();
+            this.render();
+        }, 100);
+    }
+
     setupEventListeners() {
         document.getElementById('new-game-btn').addEventListener('click', () => this.newGame());
         document.getElementById('advance-phase-btn').addEventListener('click', () => this.advancePhase());
@@ -162,10 +186,11 @@ class BattleForMoscowGame {
     }
 
     async newGame() {
+        // This page plays both sides on one screen
         const response = await fetch('/api/game/new', {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
-            body: JSON.stringify({})
+            body: JSON.stringify({ hotseat: true })
         });
 
         const result = await response.json();